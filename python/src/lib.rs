@@ -0,0 +1,305 @@
+//! Python bindings of the simulation, built with [maturin](https://www.maturin.rs):
+//!
+//! ```sh
+//! cd python
+//! maturin develop --release
+//! ```
+//!
+//! ```python
+//! import pywavefront
+//!
+//! sim = pywavefront.Simulation()
+//! sim.add_source(200, 350, "sin", frequency=500.0)
+//! mic = sim.add_microphone(500, 350)
+//! sim.step(1000)
+//! t, p = sim.mic_record(mic)
+//! field = sim.pressure_field()
+//!
+//! sim.start_field_recording("field.npy", spatial_decimation=2)
+//! sim.step(1000)
+//! sim.stop_field_recording()
+//! ```
+
+use std::path::PathBuf;
+
+use bevy::math::UVec2;
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use wavefront::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use wavefront::simulation::headless::HeadlessSimulation;
+use wavefront::simulation::validation::{self, InputError};
+
+/// Number of steps between two checks for interrupts (Ctrl+C or the stop button of Jupyter)
+const STEPS_PER_SIGNAL_CHECK: usize = 100;
+
+/// A simulation without a window. Positions are given in cells of the
+/// `SIMULATION_WIDTH` × `SIMULATION_HEIGHT` simulation area.
+#[pyclass(unsendable, name = "Simulation")]
+struct Simulation {
+    inner: HeadlessSimulation,
+}
+
+#[pymethods]
+impl Simulation {
+    #[new]
+    #[pyo3(signature = (delta_l = None, boundary_width = None))]
+    fn new(delta_l: Option<f32>, boundary_width: Option<u32>) -> PyResult<Self> {
+        let mut simulation = Self {
+            inner: HeadlessSimulation::new(),
+        };
+        if let Some(delta_l) = delta_l {
+            simulation.set_delta_l(delta_l)?;
+        }
+        if let Some(boundary_width) = boundary_width {
+            simulation.set_boundary_width(boundary_width)?;
+        }
+        Ok(simulation)
+    }
+
+    /// Size of a cell in meters
+    #[getter]
+    fn delta_l(&self) -> f32 {
+        self.inner.ui_state().delta_l
+    }
+
+    #[setter]
+    fn set_delta_l(&mut self, delta_l: f32) -> PyResult<()> {
+        if !delta_l.is_finite() || delta_l <= 0. {
+            return Err(PyValueError::new_err(format!("Invalid delta_l: {delta_l}")));
+        }
+        self.inner.ui_state_mut().delta_l = delta_l;
+        Ok(())
+    }
+
+    /// Width of the absorbing boundary in cells. Changing it resets the simulation.
+    #[getter]
+    fn boundary_width(&self) -> u32 {
+        self.inner.ui_state().boundary_width
+    }
+
+    #[setter]
+    fn set_boundary_width(&mut self, boundary_width: u32) -> PyResult<()> {
+        if !(2..=200).contains(&boundary_width) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid boundary width: {boundary_width}, expected a value between 2 and 200"
+            )));
+        }
+        self.inner.set_boundary_width(boundary_width);
+        Ok(())
+    }
+
+    /// Simulation time in seconds
+    #[getter]
+    fn time(&self) -> f32 {
+        self.inner.time()
+    }
+
+    /// Time between two steps in seconds
+    #[getter]
+    fn delta_t(&self) -> f32 {
+        self.inner.delta_t()
+    }
+
+    /// Adds a source and returns its id. `kind` is one of `sin`, `gauss`, `noise`, `impulse`
+    /// and `sweep`, the keyword arguments set the parameters of the source type
+    /// (`frequency`, `amplitude`, `phase`, `std_dev`, `start_frequency`, `end_frequency`, `duration`).
+    #[pyo3(signature = (x, y, kind = "sin", **parameters))]
+    fn add_source(
+        &mut self,
+        x: u32,
+        y: u32,
+        kind: &str,
+        parameters: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<usize> {
+        let position = position(x, y)?;
+        let mut source_type = validation::source_type(kind).map_err(value_error)?;
+        if let Some(parameters) = parameters {
+            for (name, value) in parameters.iter() {
+                let name = name.extract::<String>()?;
+                validation::set_parameter(&mut source_type, &name, value.extract()?)
+                    .map_err(value_error)?;
+            }
+        }
+        Ok(self.inner.add_source(position.x, position.y, source_type))
+    }
+
+    /// Adds a microphone and returns its id.
+    fn add_microphone(&mut self, x: u32, y: u32) -> PyResult<usize> {
+        let position = position(x, y)?;
+        Ok(self.inner.add_microphone(position.x, position.y))
+    }
+
+    /// Adds a rectangular wall between two corners (inclusive) and returns its id.
+    #[pyo3(signature = (x0, y0, x1, y1, hollow = false, reflection_factor = 1.))]
+    fn add_rect_wall(
+        &mut self,
+        x0: u32,
+        y0: u32,
+        x1: u32,
+        y1: u32,
+        hollow: bool,
+        reflection_factor: f32,
+    ) -> PyResult<usize> {
+        let a = position(x0, y0)?;
+        let b = position(x1, y1)?;
+        let reflection_factor = reflection(reflection_factor)?;
+        Ok(self
+            .inner
+            .add_rect_wall(a.min(b), a.max(b), hollow, reflection_factor))
+    }
+
+    /// Adds a circular wall and returns its id.
+    #[pyo3(signature = (x, y, radius, hollow = false, reflection_factor = 1.))]
+    fn add_circ_wall(
+        &mut self,
+        x: u32,
+        y: u32,
+        radius: u32,
+        hollow: bool,
+        reflection_factor: f32,
+    ) -> PyResult<usize> {
+        let center = position(x, y)?;
+        let radius = validation::radius(radius.into()).map_err(value_error)?;
+        let reflection_factor = reflection(reflection_factor)?;
+        Ok(self
+            .inner
+            .add_circ_wall(center, radius, hollow, reflection_factor))
+    }
+
+    /// Removes all sources, microphones and walls and resets the simulation.
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Resets the pressure field, the simulation time and all records.
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    /// Advances the simulation by `steps` time steps.
+    #[pyo3(signature = (steps = 1))]
+    fn step(&mut self, py: Python<'_>, steps: usize) -> PyResult<()> {
+        let mut remaining = steps;
+        while remaining > 0 {
+            let chunk = remaining.min(STEPS_PER_SIGNAL_CHECK);
+            self.inner.step(chunk);
+            remaining -= chunk;
+            py.check_signals()?;
+        }
+        Ok(())
+    }
+
+    /// The ids of all microphones
+    fn microphone_ids(&mut self) -> Vec<usize> {
+        self.inner.microphones().iter().map(|mic| mic.id).collect()
+    }
+
+    /// The record of a microphone as a tuple of NumPy arrays `(time, pressure)`.
+    fn mic_record<'py>(
+        &mut self,
+        py: Python<'py>,
+        id: usize,
+    ) -> PyResult<(Bound<'py, PyArray1<f64>>, Bound<'py, PyArray1<f64>>)> {
+        let record = self
+            .inner
+            .mic_record(id)
+            .ok_or_else(|| PyValueError::new_err(format!("There is no microphone with id {id}")))?;
+        let (times, pressures): (Vec<f64>, Vec<f64>) = record
+            .into_iter()
+            .map(|[time, value]| (time, value))
+            .unzip();
+        Ok((
+            times.into_pyarray_bound(py),
+            pressures.into_pyarray_bound(py),
+        ))
+    }
+
+    /// The current pressure at a position.
+    fn pressure(&self, x: u32, y: u32) -> PyResult<f32> {
+        let position = position(x, y)?;
+        Ok(self
+            .inner
+            .pressure(position.x, position.y)
+            .expect("the position is inside the simulation area"))
+    }
+
+    /// The pressure of the simulation area as a NumPy array indexed by `[y, x]`.
+    fn pressure_field<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f32>> {
+        Array2::from_shape_vec(
+            (SIMULATION_HEIGHT as usize, SIMULATION_WIDTH as usize),
+            self.inner.pressure_field(),
+        )
+        .expect("the field has the size of the simulation area")
+        .into_pyarray_bound(py)
+    }
+
+    /// Starts recording the pressure of the cells between `(x0, y0)` and `(x1, y1)` (inclusive)
+    /// to a NumPy `.npy` file with shape `(frames, height, width)`. Only every
+    /// `spatial_decimation`-th cell of every `time_decimation`-th step is recorded.
+    /// The cell size and time step are written to a `.json` file next to it when the recording stops.
+    #[pyo3(signature = (
+        path,
+        x0 = 0,
+        y0 = 0,
+        x1 = SIMULATION_WIDTH - 1,
+        y1 = SIMULATION_HEIGHT - 1,
+        spatial_decimation = 1,
+        time_decimation = 1
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn start_field_recording(
+        &mut self,
+        path: PathBuf,
+        x0: u32,
+        y0: u32,
+        x1: u32,
+        y1: u32,
+        spatial_decimation: u32,
+        time_decimation: u32,
+    ) -> PyResult<()> {
+        let a = position(x0, y0)?;
+        let b = position(x1, y1)?;
+        let spatial_decimation =
+            validation::decimation(spatial_decimation.into()).map_err(value_error)?;
+        let time_decimation =
+            validation::decimation(time_decimation.into()).map_err(value_error)?;
+        self.inner.start_field_recording(
+            path,
+            a.min(b),
+            a.max(b),
+            spatial_decimation,
+            time_decimation,
+        )?;
+        Ok(())
+    }
+
+    /// Finishes the field recording and writes its metadata.
+    fn stop_field_recording(&mut self) -> PyResult<()> {
+        self.inner.stop_field_recording()?;
+        Ok(())
+    }
+}
+
+/// Converts a rejected input into a `ValueError`.
+fn value_error(err: InputError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn position(x: u32, y: u32) -> PyResult<UVec2> {
+    validation::position(x.into(), y.into()).map_err(value_error)
+}
+
+fn reflection(reflection_factor: f32) -> PyResult<f32> {
+    validation::reflection_factor(reflection_factor).map_err(value_error)
+}
+
+#[pymodule]
+fn pywavefront(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Simulation>()?;
+    m.add("SIMULATION_WIDTH", SIMULATION_WIDTH)?;
+    m.add("SIMULATION_HEIGHT", SIMULATION_HEIGHT)?;
+    Ok(())
+}
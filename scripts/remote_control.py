@@ -0,0 +1,25 @@
+# Controls a running wavefront instance over the remote control server.
+# Enable it in the preferences first, the commands are documented in src/remote/protocol.rs.
+import json
+import socket
+
+with socket.create_connection(("127.0.0.1", 7341)) as connection:
+    stream = connection.makefile("rw")
+
+    def send(**command):
+        stream.write(json.dumps(command) + "\n")
+        stream.flush()
+        response = json.loads(stream.readline())
+        if not response["ok"]:
+            raise RuntimeError(response["error"])
+        return response["result"]
+
+    send(command="new")
+    send(command="add_source", x=200, y=350, kind="sin", parameters={"frequency": 500})
+    mic = send(command="add_microphone", x=500, y=350)
+    send(command="start")
+
+    input("Press enter to fetch the microphone record")
+    record = send(command="mic_record", id=mic)
+    print(f"{len(record)} samples")
+    send(command="screenshot", path="remote_screenshot.png")
@@ -0,0 +1,19 @@
+# Simulates an impulse response without the app and plots its spectrum.
+# Requires the python bindings: cd python && maturin develop --release
+import pyfar as pf
+import matplotlib.pyplot as plt
+import pywavefront
+
+sim = pywavefront.Simulation()
+sim.add_rect_wall(150, 150, 550, 550, hollow=True, reflection_factor=0.9)
+sim.add_source(250, 300, "impulse", amplitude=10.0)
+mic = sim.add_microphone(450, 420)
+
+sim.step(10000)
+t, p = sim.mic_record(mic)
+
+signal = pf.Signal(p, 1 / sim.delta_t)
+
+ax = pf.plot.freq(signal, label="Signal in dB")
+ax.legend(loc='upper left')
+plt.show()
@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::microphone::Microphone;
+
+/// A group of [`Microphone`]s that are evaluated together (beamforming, DOA estimation)
+#[derive(Debug, Default, Component, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MicArray {
+    pub id: usize,
+    /// ids of the microphones that are part of the array
+    pub mic_ids: Vec<usize>,
+    /// steering direction of the beamformer (in °), 0° points to the right, counterclockwise
+    pub steering_angle: f32,
+}
+
+impl MicArray {
+    pub fn new(id: usize, mic_ids: Vec<usize>) -> Self {
+        Self {
+            id,
+            mic_ids,
+            steering_angle: 0.,
+        }
+    }
+
+    /// Returns the microphones of `mics` that are part of this array.
+    /// Ids of deleted microphones are skipped.
+    pub fn mics<'a>(&self, mics: &[&'a Microphone]) -> Vec<&'a Microphone> {
+        self.mic_ids
+            .iter()
+            .filter_map(|id| mics.iter().find(|mic| mic.id == *id).copied())
+            .collect()
+    }
+}
@@ -1,3 +1,8 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
 use bevy::prelude::*;
 use egui::epaint::{CircleShape, TextShape};
 use egui::text::LayoutJob;
@@ -6,6 +11,7 @@ use serde::{Deserialize, Serialize};
 
 use super::gizmo::GizmoComponent;
 use crate::math::transformations::grid_to_image;
+use crate::math::units::PressureUnits;
 use crate::render::gradient::Gradient;
 use crate::simulation::plugin::ComponentIDs;
 use crate::ui::state::ToolType;
@@ -19,6 +25,116 @@ pub struct Microphone {
     #[serde(skip_serializing, skip_deserializing)]
     pub record: Vec<[f64; 2]>,
     pub show_fft: bool,
+    /// whether the microphone records while global recording is enabled
+    #[serde(default = "armed_default")]
+    pub armed: bool,
+    /// sum of the samples of the current decimation block
+    #[serde(skip_serializing, skip_deserializing)]
+    decimation_sum: f64,
+    /// amount of samples in the current decimation block
+    #[serde(skip_serializing, skip_deserializing)]
+    decimation_count: usize,
+    /// samples that have not been streamed to disk yet
+    #[serde(skip_serializing, skip_deserializing)]
+    pending: Vec<[f64; 2]>,
+    /// amount of samples already streamed to disk
+    #[serde(skip_serializing, skip_deserializing)]
+    streamed: usize,
+    /// amount of samples dropped because they could not be streamed to disk
+    #[serde(skip_serializing, skip_deserializing)]
+    dropped: usize,
+    /// why the last attempt to stream the record failed
+    #[serde(skip_serializing, skip_deserializing)]
+    stream_error: Option<String>,
+}
+
+/// Amount of chunks that are kept while streaming fails, older samples are dropped.
+const MAX_PENDING_CHUNKS: usize = 16;
+
+fn armed_default() -> bool {
+    true
+}
+
+/// The recording state of a microphone that is not part of the scene.
+/// Saved in checkpoints, so the record continues where it stopped.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordState {
+    pub record: Vec<[f64; 2]>,
+    /// sum and amount of samples in the current decimation block
+    #[serde(default)]
+    pub decimation: (f64, usize),
+    /// samples that have not been streamed to disk yet
+    #[serde(default)]
+    pub pending: Vec<[f64; 2]>,
+    /// amount of samples already streamed to disk,
+    /// later samples are appended to the stream file if it is not zero
+    #[serde(default)]
+    pub streamed: usize,
+}
+
+/// The directory microphones stream their records to with [`RecordingPolicy::StreamToDisk`].
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct StreamDirectory(pub PathBuf);
+
+impl Default for StreamDirectory {
+    fn default() -> Self {
+        StreamDirectory(PathBuf::from("."))
+    }
+}
+
+/// How microphones store the samples they record.
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RecordingPolicy {
+    /// Keep every sample of the whole run
+    #[default]
+    Unbounded,
+    /// Keep only the last `seconds` of simulation time
+    RingBuffer { seconds: f32 },
+    /// Average every `factor` samples into one and keep the last `seconds`
+    Decimated { factor: usize, seconds: f32 },
+    /// Write every sample to disk in chunks of `chunk_size` samples
+    /// and keep the last `seconds` in memory
+    StreamToDisk { chunk_size: usize, seconds: f32 },
+}
+
+impl RecordingPolicy {
+    /// The amount of simulation steps that are combined into one recorded sample.
+    pub fn decimation(&self) -> usize {
+        match self {
+            RecordingPolicy::Decimated { factor, .. } => (*factor).max(1),
+            _ => 1,
+        }
+    }
+
+    /// The time between two recorded samples.
+    /// * `delta_t` - The time between two simulation steps.
+    pub fn sample_interval(&self, delta_t: f32) -> f32 {
+        delta_t * self.decimation() as f32
+    }
+
+    /// The amount of samples kept in memory, `None` if the record is unbounded.
+    /// * `delta_t` - The time between two simulation steps.
+    pub fn capacity(&self, delta_t: f32) -> Option<usize> {
+        match self {
+            RecordingPolicy::Unbounded => None,
+            RecordingPolicy::RingBuffer { seconds }
+            | RecordingPolicy::Decimated { seconds, .. }
+            | RecordingPolicy::StreamToDisk { seconds, .. } => {
+                Some(((seconds / self.sample_interval(delta_t)).ceil() as usize).max(1))
+            }
+        }
+    }
+}
+
+impl fmt::Display for RecordingPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingPolicy::Unbounded => write!(f, "Unbounded"),
+            RecordingPolicy::RingBuffer { .. } => write!(f, "Ring Buffer"),
+            RecordingPolicy::Decimated { .. } => write!(f, "Decimated"),
+            RecordingPolicy::StreamToDisk { .. } => write!(f, "Stream to Disk"),
+        }
+    }
 }
 
 impl Microphone {
@@ -29,6 +145,13 @@ impl Microphone {
             id,
             record: vec![],
             show_fft: false,
+            armed: true,
+            decimation_sum: 0.,
+            decimation_count: 0,
+            pending: vec![],
+            streamed: 0,
+            dropped: 0,
+            stream_error: None,
         }
     }
 
@@ -41,18 +164,221 @@ impl Microphone {
         commands.spawn(Microphone::new(650, 650, component_ids.get_new_mic_id()));
     }
 
+    /// Returns a copy of the microphone and its settings without the recorded data.
+    pub fn without_record(&self) -> Self {
+        Self {
+            show_fft: self.show_fft,
+            armed: self.armed,
+            ..Self::new(self.x, self.y, self.id)
+        }
+    }
+
+    /// The span of simulation time covered by the record in seconds.
+    pub fn recorded_duration(&self) -> f64 {
+        match (self.record.first(), self.record.last()) {
+            (Some(first), Some(last)) => last[0] - first[0],
+            _ => 0.,
+        }
+    }
+
+    /// A copy of the record and the state of the decimation and the stream.
+    pub fn record_state(&self) -> RecordState {
+        RecordState {
+            record: self.record.clone(),
+            decimation: (self.decimation_sum, self.decimation_count),
+            pending: self.pending.clone(),
+            streamed: self.streamed,
+        }
+    }
+
+    /// Replaces the record and the state of the decimation and the stream,
+    /// e.g. with the state saved in a checkpoint.
+    pub fn restore_record_state(&mut self, state: RecordState) {
+        self.record = state.record;
+        (self.decimation_sum, self.decimation_count) = state.decimation;
+        self.pending = state.pending;
+        self.streamed = state.streamed;
+    }
+
     pub fn clear(&mut self) {
         self.record = vec![];
+        self.decimation_sum = 0.;
+        self.decimation_count = 0;
+        self.pending = vec![];
+        self.streamed = 0;
+        self.dropped = 0;
+        self.stream_error = None;
     }
 
-    pub fn write_to_file(&mut self, path: &str) {
+    /// Records a pressure value according to the given [`RecordingPolicy`].
+    /// * `time` - The simulation time of the value.
+    /// * `value` - The pressure at the position of the microphone.
+    /// * `policy` - The policy used to store the value.
+    /// * `delta_t` - The time between two simulation steps.
+    /// * `stream_directory` - The directory of the stream file with [`RecordingPolicy::StreamToDisk`].
+    pub fn record_sample(
+        &mut self,
+        time: f64,
+        value: f64,
+        policy: RecordingPolicy,
+        delta_t: f32,
+        stream_directory: &Path,
+    ) {
+        let factor = policy.decimation();
+        self.decimation_sum += value;
+        self.decimation_count += 1;
+        if self.decimation_count < factor {
+            return;
+        }
+
+        let sample = [time, self.decimation_sum / self.decimation_count as f64];
+        self.decimation_sum = 0.;
+        self.decimation_count = 0;
+        self.record.push(sample);
+
+        if let RecordingPolicy::StreamToDisk { chunk_size, .. } = policy {
+            self.pending.push(sample);
+            // after a failed write, the next attempt is made once another chunk is complete
+            if self.pending.len().is_multiple_of(chunk_size.max(1)) {
+                self.flush_stream(stream_directory);
+            }
+            // a whole chunk is dropped, so the next attempt is still made at the end of a chunk
+            if self.pending.len() > chunk_size.max(1) * MAX_PENDING_CHUNKS {
+                self.pending.drain(..chunk_size.max(1));
+                self.dropped += chunk_size.max(1);
+            }
+        }
+
+        if let Some(capacity) = policy.capacity(delta_t) {
+            // trimming in batches keeps removing samples from the front cheap
+            if self.record.len() > capacity + capacity / 4 {
+                self.record.drain(..self.record.len() - capacity);
+            }
+        }
+    }
+
+    /// The file in `directory` the record is streamed to with [`RecordingPolicy::StreamToDisk`].
+    pub fn stream_path(&self, directory: &Path) -> PathBuf {
+        directory.join(format!("mic_{}_stream.csv", self.id))
+    }
+
+    /// Appends all samples that have not been streamed yet to the file at [`Microphone::stream_path`].
+    /// The file is truncated when the first chunk is written.
+    /// If writing fails, the samples are kept and written with the next chunk,
+    /// up to [`MAX_PENDING_CHUNKS`] chunks. The error is kept until a write succeeds.
+    pub fn flush_stream(&mut self, directory: &Path) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        match self.write_pending(directory) {
+            Ok(()) => {
+                self.streamed += self.pending.len();
+                self.pending.clear();
+                self.stream_error = None;
+            }
+            Err(e) => {
+                // the paused simulation retries every frame, so only new errors are logged
+                let error = e.to_string();
+                if self.stream_error.as_ref() != Some(&error) {
+                    warn!("could not stream microphone {}: {}", self.id, error);
+                    self.stream_error = Some(error);
+                }
+            }
+        }
+    }
+
+    /// Appends the pending samples to the stream file at once,
+    /// a partially written chunk is removed again so it is not duplicated by the next attempt.
+    fn write_pending(&self, directory: &Path) -> io::Result<()> {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        for record in &self.pending {
+            wtr.write_record(&[record[0].to_string(), record[1].to_string()])?;
+        }
+        let chunk = wtr.into_inner().map_err(|e| e.into_error())?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(self.streamed > 0)
+            .truncate(self.streamed == 0)
+            .open(self.stream_path(directory))?;
+        let len = file.metadata()?.len();
+        file.write_all(&chunk).inspect_err(|_| {
+            let _ = file.set_len(len);
+        })
+    }
+
+    /// Returns true if there are samples that have not been streamed to disk yet.
+    pub fn has_pending_samples(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// The amount of samples streamed to disk since the last reset.
+    pub fn streamed_samples(&self) -> usize {
+        self.streamed + self.pending.len()
+    }
+
+    /// The amount of samples that were dropped because they could not be streamed to disk.
+    pub fn dropped_samples(&self) -> usize {
+        self.dropped
+    }
+
+    /// Why the last attempt to stream the record to disk failed, `None` if it succeeded.
+    pub fn stream_error(&self) -> Option<&str> {
+        self.stream_error.as_deref()
+    }
+
+    /// The amount of memory used by the record in bytes.
+    pub fn memory_usage(&self) -> usize {
+        (self.record.capacity() + self.pending.capacity()) * std::mem::size_of::<[f64; 2]>()
+    }
+
+    pub fn write_to_file(&mut self, path: &str, units: PressureUnits) {
         let mut wtr = csv::Writer::from_path(path).unwrap();
+        wtr.write_record(["time (s)".to_string(), units.pressure_column("pressure")])
+            .unwrap();
         for record in &self.record {
             wtr.write_record(&[record[0].to_string(), record[1].to_string()])
                 .unwrap();
         }
         wtr.flush().unwrap();
     }
+
+    /// Draws a small dot next to the microphone gizmo if the microphone is armed.
+    /// The dot is filled while samples are being recorded and hollow while recording is paused.
+    pub fn draw_recording_indicator(
+        &self,
+        painter: &egui::Painter,
+        image_rect: &Rect,
+        is_recording: bool,
+    ) {
+        if !self.armed {
+            return;
+        }
+
+        let center = grid_to_image(
+            Pos2 {
+                x: self.x as f32,
+                y: self.y as f32,
+            },
+            image_rect,
+        ) + egui::Vec2::new(10., -10.);
+
+        if is_recording {
+            painter.add(egui::Shape::Circle(CircleShape::filled(
+                center,
+                4.,
+                Color32::RED,
+            )));
+        } else {
+            painter.add(egui::Shape::Circle(CircleShape::stroke(
+                center,
+                4.,
+                (1.5, Color32::RED),
+            )));
+        }
+    }
 }
 
 impl GizmoComponent for Microphone {
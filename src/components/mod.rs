@@ -1,5 +1,7 @@
 pub mod gizmo;
+pub mod mic_array;
 pub mod microphone;
+pub mod probe;
 pub mod source;
 pub mod states;
 pub mod wall;
@@ -0,0 +1,336 @@
+use bevy::prelude::*;
+use egui::epaint::CircleShape;
+use egui::{Align2, Color32, FontId, Pos2, Rect, Stroke};
+use serde::{Deserialize, Serialize};
+
+use super::gizmo::GizmoComponent;
+use super::microphone::RecordingPolicy;
+use crate::math::transformations::{coords_to_index, grid_to_image};
+use crate::math::units::PressureUnits;
+use crate::render::gradient::Gradient;
+use crate::ui::state::ToolType;
+
+const PROBE_COLOR: Color32 = Color32::from_rgb(255, 200, 60);
+
+/// A probe that samples the pressure of every cell on a line between two points
+#[derive(Debug, Default, Component, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LineProbe {
+    pub id: usize,
+    /// start point in grid coordinates
+    pub start: UVec2,
+    /// end point in grid coordinates
+    pub end: UVec2,
+    /// whether the level in dB is plotted instead of the instantaneous pressure
+    pub show_level: bool,
+    /// pressure of every cell on the line at the current time
+    #[serde(skip_serializing, skip_deserializing)]
+    pub pressure: Vec<f64>,
+    /// exponentially time weighted mean square pressure of every cell on the line
+    #[serde(skip_serializing, skip_deserializing)]
+    pub mean_square: Vec<f64>,
+}
+
+impl LineProbe {
+    pub fn new(id: usize, start: UVec2, end: UVec2) -> Self {
+        Self {
+            id,
+            start,
+            end,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the cells on the line, one per step along the longer axis.
+    pub fn cells(&self) -> Vec<UVec2> {
+        let start = self.start.as_vec2();
+        let end = self.end.as_vec2();
+        let steps = (end - start).abs().max_element() as u32;
+        if steps == 0 {
+            return vec![self.start];
+        }
+        (0..=steps)
+            .map(|step| {
+                start
+                    .lerp(end, step as f32 / steps as f32)
+                    .round()
+                    .as_uvec2()
+            })
+            .collect()
+    }
+
+    /// Length of the line in meters.
+    pub fn length(&self, delta_l: f32) -> f32 {
+        self.start.as_vec2().distance(self.end.as_vec2()) * delta_l
+    }
+
+    pub fn clear(&mut self) {
+        self.pressure.clear();
+        self.mean_square.clear();
+    }
+
+    /// Samples the pressure of all cells on the line.
+    /// * `alpha` - The weight of the new value in the exponentially averaged mean square.
+    pub fn update(&mut self, pressure: &[f32], boundary_width: u32, alpha: f64) {
+        let cells = self.cells();
+        if self.mean_square.len() != cells.len() {
+            self.mean_square = vec![0.; cells.len()];
+        }
+
+        self.pressure = cells
+            .iter()
+            .map(|cell| {
+                pressure[coords_to_index(
+                    cell.x + boundary_width,
+                    cell.y + boundary_width,
+                    boundary_width,
+                )] as f64
+            })
+            .collect();
+
+        for (mean_square, p) in self.mean_square.iter_mut().zip(&self.pressure) {
+            *mean_square += alpha * (p * p - *mean_square);
+        }
+    }
+
+    /// Returns the pressure or the level in dB over the position along the line in meters.
+    pub fn profile(&self, delta_l: f32, units: PressureUnits) -> Vec<[f64; 2]> {
+        let cells = self.pressure.len().max(1);
+        let step = if cells > 1 {
+            self.length(delta_l) as f64 / (cells - 1) as f64
+        } else {
+            0.
+        };
+        if self.show_level {
+            self.mean_square
+                .iter()
+                .enumerate()
+                .map(|(i, mean_square)| {
+                    [
+                        i as f64 * step,
+                        10. * mean_square.log10() + units.level_offset() as f64,
+                    ]
+                })
+                .collect()
+        } else {
+            self.pressure
+                .iter()
+                .enumerate()
+                .map(|(i, p)| [i as f64 * step, *p])
+                .collect()
+        }
+    }
+
+    /// Returns the current profile of the line as CSV.
+    pub fn to_csv(&self, delta_l: f32, units: PressureUnits) -> Vec<u8> {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.write_record([
+            "x",
+            "y",
+            "position (m)",
+            &units.pressure_column("pressure"),
+            &format!("level ({})", units.level_suffix()),
+        ])
+        .unwrap();
+        let step = self.length(delta_l) as f64 / (self.pressure.len().max(2) - 1) as f64;
+        for (i, ((cell, p), mean_square)) in self
+            .cells()
+            .iter()
+            .zip(&self.pressure)
+            .zip(&self.mean_square)
+            .enumerate()
+        {
+            wtr.write_record(&[
+                cell.x.to_string(),
+                cell.y.to_string(),
+                (i as f64 * step).to_string(),
+                p.to_string(),
+                (10. * mean_square.log10() + units.level_offset() as f64).to_string(),
+            ])
+            .unwrap();
+        }
+        wtr.into_inner().unwrap()
+    }
+}
+
+impl GizmoComponent for LineProbe {
+    fn get_gizmo_positions(&self, _tool_type: &ToolType) -> Vec<Pos2> {
+        vec![
+            Pos2::new(self.start.x as f32, self.start.y as f32),
+            Pos2::new(self.end.x as f32, self.end.y as f32),
+        ]
+    }
+
+    fn draw_gizmo(
+        &self,
+        painter: &egui::Painter,
+        tool_type: &ToolType,
+        highlight: bool,
+        image_rect: &Rect,
+        text: Option<&str>,
+        _delta_l: f32,
+        _current_gradient: Gradient,
+    ) {
+        let positions = self
+            .get_gizmo_positions(tool_type)
+            .into_iter()
+            .map(|pos| grid_to_image(pos, image_rect))
+            .collect::<Vec<_>>();
+
+        painter.line_segment(
+            [positions[0], positions[1]],
+            Stroke::new(if highlight { 3. } else { 2. }, PROBE_COLOR),
+        );
+        for pos in &positions {
+            painter.add(egui::Shape::Circle(CircleShape::filled(
+                *pos,
+                if highlight { 6. } else { 4. },
+                PROBE_COLOR,
+            )));
+        }
+        if let Some(text) = text {
+            painter.text(
+                positions[0] - egui::Vec2::new(0., 8.),
+                Align2::CENTER_BOTTOM,
+                text,
+                FontId::proportional(14.),
+                PROBE_COLOR,
+            );
+        }
+    }
+}
+
+/// A probe that records the RMS and the maximum of the pressure over a rectangular area
+#[derive(Debug, Default, Component, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AreaProbe {
+    pub id: usize,
+    /// first corner in grid coordinates
+    pub start: UVec2,
+    /// opposite corner in grid coordinates
+    pub end: UVec2,
+    /// time, spatial RMS and spatial maximum of the absolute pressure
+    #[serde(skip_serializing, skip_deserializing)]
+    pub record: Vec<[f64; 3]>,
+}
+
+impl AreaProbe {
+    pub fn new(id: usize, start: UVec2, end: UVec2) -> Self {
+        Self {
+            id,
+            start,
+            end,
+            ..Default::default()
+        }
+    }
+
+    /// The top left and bottom right corner of the area.
+    pub fn bounds(&self) -> (UVec2, UVec2) {
+        (self.start.min(self.end), self.start.max(self.end))
+    }
+
+    /// Amount of cells in the area
+    pub fn cell_count(&self) -> usize {
+        let (min, max) = self.bounds();
+        ((max.x - min.x + 1) * (max.y - min.y + 1)) as usize
+    }
+
+    pub fn clear(&mut self) {
+        self.record.clear();
+    }
+
+    /// Records the RMS and the maximum of the absolute pressure over the area.
+    /// The record is trimmed like microphone records according to `policy`.
+    pub fn update(
+        &mut self,
+        pressure: &[f32],
+        boundary_width: u32,
+        time: f64,
+        policy: RecordingPolicy,
+        delta_t: f32,
+    ) {
+        let (min, max) = self.bounds();
+        let mut sum_squares = 0.;
+        let mut maximum = 0_f64;
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let p = pressure
+                    [coords_to_index(x + boundary_width, y + boundary_width, boundary_width)]
+                    as f64;
+                sum_squares += p * p;
+                maximum = maximum.max(p.abs());
+            }
+        }
+        self.record.push([
+            time,
+            (sum_squares / self.cell_count() as f64).sqrt(),
+            maximum,
+        ]);
+
+        // the area is sampled every step, so the retained time is kept instead of the sample count
+        if let Some(capacity) = policy
+            .capacity(delta_t)
+            .map(|capacity| capacity * policy.decimation())
+        {
+            if self.record.len() > capacity + capacity / 4 {
+                self.record.drain(..self.record.len() - capacity);
+            }
+        }
+    }
+
+    /// Returns the record as CSV.
+    pub fn to_csv(&self, units: PressureUnits) -> Vec<u8> {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.write_record([
+            "time (s)".to_string(),
+            units.pressure_column("rms"),
+            units.pressure_column("max"),
+        ])
+        .unwrap();
+        for [time, rms, max] in &self.record {
+            wtr.write_record(&[time.to_string(), rms.to_string(), max.to_string()])
+                .unwrap();
+        }
+        wtr.into_inner().unwrap()
+    }
+}
+
+impl GizmoComponent for AreaProbe {
+    fn get_gizmo_positions(&self, _tool_type: &ToolType) -> Vec<Pos2> {
+        let (min, max) = self.bounds();
+        vec![
+            Pos2::new(min.x as f32, min.y as f32),
+            Pos2::new(max.x as f32 + 1., max.y as f32 + 1.),
+        ]
+    }
+
+    fn draw_gizmo(
+        &self,
+        painter: &egui::Painter,
+        tool_type: &ToolType,
+        highlight: bool,
+        image_rect: &Rect,
+        text: Option<&str>,
+        _delta_l: f32,
+        _current_gradient: Gradient,
+    ) {
+        let positions = self.get_gizmo_positions(tool_type);
+        let rect = Rect::from_two_pos(
+            grid_to_image(positions[0], image_rect),
+            grid_to_image(positions[1], image_rect),
+        );
+
+        painter.rect_stroke(
+            rect,
+            0.,
+            Stroke::new(if highlight { 3. } else { 2. }, PROBE_COLOR),
+        );
+        if let Some(text) = text {
+            painter.text(
+                rect.left_top() + egui::Vec2::new(4., 2.),
+                Align2::LEFT_TOP,
+                text,
+                FontId::proportional(14.),
+                PROBE_COLOR,
+            );
+        }
+    }
+}
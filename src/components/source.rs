@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::f32::consts::PI;
 use std::fmt;
 
@@ -9,6 +10,7 @@ use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 
 use super::gizmo::GizmoComponent;
+use super::microphone::RecordingPolicy;
 use crate::math::constants::*;
 use crate::math::transformations::grid_to_image;
 use crate::render::gradient::Gradient;
@@ -25,6 +27,71 @@ pub struct Source {
     pub id: usize,
 }
 
+/// A resource to store the signals emitted by the sources, indexed by source id.
+/// Only the source selected as reference of the transfer function is recorded.
+/// The signals are stored like microphone records with the current [`RecordingPolicy`],
+/// but are never streamed to disk. Saved in checkpoints.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct SourceRecords {
+    records: HashMap<usize, Vec<[f64; 2]>>,
+    /// sum and amount of samples in the current decimation block of every source
+    decimation: HashMap<usize, (f64, usize)>,
+}
+
+impl SourceRecords {
+    /// The emitted signal of the source with the given id as `[time, value]` pairs.
+    pub fn get(&self, id: usize) -> Option<&[[f64; 2]]> {
+        self.records.get(&id).map(|record| record.as_slice())
+    }
+
+    /// Records a value emitted by a source.
+    /// * `id` - The id of the source.
+    /// * `time` - The simulation time of the value.
+    /// * `value` - The emitted value.
+    /// * `policy` - The policy used to store the value.
+    /// * `delta_t` - The time between two simulation steps.
+    pub fn record(
+        &mut self,
+        id: usize,
+        time: f64,
+        value: f64,
+        policy: RecordingPolicy,
+        delta_t: f32,
+    ) {
+        let (sum, count) = self.decimation.entry(id).or_insert((0., 0));
+        *sum += value;
+        *count += 1;
+        if *count < policy.decimation() {
+            return;
+        }
+
+        let record = self.records.entry(id).or_default();
+        record.push([time, *sum / *count as f64]);
+        *sum = 0.;
+        *count = 0;
+
+        if let Some(capacity) = policy.capacity(delta_t) {
+            // trimming in batches keeps removing samples from the front cheap
+            if record.len() > capacity + capacity / 4 {
+                record.drain(..record.len() - capacity);
+            }
+        }
+    }
+
+    /// Removes the signals of all sources except the one with the given id.
+    pub fn retain(&mut self, id: Option<usize>) {
+        self.records.retain(|source, _| Some(*source) == id);
+        self.decimation.retain(|source, _| Some(*source) == id);
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+        self.decimation.clear();
+    }
+}
+
+/// The signal of a source. Amplitudes are in grid units, their level in Pa is found by
+/// [`SourceCalibration`](crate::simulation::calibration::SourceCalibration).
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
 pub enum SourceType {
     Sin {
@@ -32,7 +99,7 @@ pub enum SourceType {
         phase: f32,
         /// frequency of the sin (in Hz)
         frequency: f32,
-        /// amplitude of the sin (currently unitless)
+        /// amplitude of the sin (in grid units)
         amplitude: f32,
     },
     Gauss {
@@ -40,14 +107,30 @@ pub enum SourceType {
         phase: f32,
         /// frequency of the bell (in Hz)
         frequency: f32,
-        /// amplitude of the bell (currently unitless)
+        /// amplitude of the bell (in grid units)
         amplitude: f32,
         std_dev: f32,
     },
     WhiteNoise {
-        /// amplitude of the noise (currently unitless)
+        /// amplitude of the noise (in grid units)
         amplitude: f32,
     },
+    /// A single pulse at the start of the simulation
+    Impulse {
+        /// amplitude of the pulse (in grid units)
+        amplitude: f32,
+    },
+    /// An exponential sine sweep starting at the start of the simulation
+    Sweep {
+        /// amplitude of the sweep (in grid units)
+        amplitude: f32,
+        /// frequency at the start of the sweep (in Hz)
+        start_frequency: f32,
+        /// frequency at the end of the sweep (in Hz)
+        end_frequency: f32,
+        /// duration of the sweep (in s)
+        duration: f32,
+    },
 }
 
 impl Default for SourceType {
@@ -79,6 +162,80 @@ impl SourceType {
     pub fn default_noise() -> SourceType {
         SourceType::WhiteNoise { amplitude: 10. }
     }
+    pub fn default_impulse() -> SourceType {
+        SourceType::Impulse { amplitude: 10. }
+    }
+    pub fn default_sweep() -> SourceType {
+        SourceType::Sweep {
+            amplitude: 10.,
+            start_frequency: 50.,
+            end_frequency: 20000.,
+            duration: 0.05,
+        }
+    }
+
+    /// The highest frequency the source emits with a relevant level in Hz,
+    /// `None` for noise and impulses that excite all frequencies.
+    pub fn highest_frequency(&self) -> Option<f32> {
+        match *self {
+            SourceType::Sin { frequency, .. } => Some(frequency),
+            // the spectrum of the pulses is a gaussian with a standard deviation of
+            // frequency / std_dev, three standard deviations contain almost all of the energy
+            SourceType::Gauss {
+                frequency, std_dev, ..
+            } => Some(3. * frequency / std_dev.max(f32::EPSILON)),
+            SourceType::WhiteNoise { .. } | SourceType::Impulse { .. } => None,
+            SourceType::Sweep {
+                start_frequency,
+                end_frequency,
+                ..
+            } => Some(start_frequency.max(end_frequency)),
+        }
+    }
+
+    /// The default source type for a short name: `sin`, `gauss`, `noise`, `impulse` or `sweep`.
+    pub fn from_name(name: &str) -> Option<SourceType> {
+        match name {
+            "sin" => Some(SourceType::default_sin()),
+            "gauss" => Some(SourceType::default_gauss()),
+            "noise" => Some(SourceType::default_noise()),
+            "impulse" => Some(SourceType::default_impulse()),
+            "sweep" => Some(SourceType::default_sweep()),
+            _ => None,
+        }
+    }
+
+    /// A parameter of the source type by its field name,
+    /// `None` if the source type has no such parameter.
+    pub fn parameter_mut(&mut self, name: &str) -> Option<&mut f32> {
+        match (self, name) {
+            (SourceType::Sin { phase, .. } | SourceType::Gauss { phase, .. }, "phase") => {
+                Some(phase)
+            }
+            (
+                SourceType::Sin { frequency, .. } | SourceType::Gauss { frequency, .. },
+                "frequency",
+            ) => Some(frequency),
+            (
+                SourceType::Sin { amplitude, .. }
+                | SourceType::Gauss { amplitude, .. }
+                | SourceType::WhiteNoise { amplitude }
+                | SourceType::Impulse { amplitude }
+                | SourceType::Sweep { amplitude, .. },
+                "amplitude",
+            ) => Some(amplitude),
+            (SourceType::Gauss { std_dev, .. }, "std_dev") => Some(std_dev),
+            (
+                SourceType::Sweep {
+                    start_frequency, ..
+                },
+                "start_frequency",
+            ) => Some(start_frequency),
+            (SourceType::Sweep { end_frequency, .. }, "end_frequency") => Some(end_frequency),
+            (SourceType::Sweep { duration, .. }, "duration") => Some(duration),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for SourceType {
@@ -87,6 +244,8 @@ impl fmt::Display for SourceType {
             SourceType::Sin { .. } => write!(f, "Sinusoidal"),
             SourceType::Gauss { .. } => write!(f, "Gaussian"),
             SourceType::WhiteNoise { .. } => write!(f, "White Noise"),
+            SourceType::Impulse { .. } => write!(f, "Impulse"),
+            SourceType::Sweep { .. } => write!(f, "Sine Sweep"),
         }
     }
 }
@@ -117,7 +276,36 @@ impl Source {
             SourceType::WhiteNoise { amplitude } => {
                 thread_rng().sample::<f32, _>(rand_distr::StandardNormal) * amplitude
             }
+            SourceType::Impulse { amplitude } => {
+                if time <= 0. {
+                    amplitude
+                } else {
+                    0.
+                }
+            }
+            SourceType::Sweep {
+                amplitude,
+                start_frequency,
+                end_frequency,
+                duration,
+            } => self.sweep(time, amplitude, start_frequency, end_frequency, duration),
+        }
+    }
+
+    /// Exponential sine sweep as described by Farina, silent after `duration`.
+    fn sweep(
+        &self,
+        time: f32,
+        amplitude: f32,
+        start_frequency: f32,
+        end_frequency: f32,
+        duration: f32,
+    ) -> f32 {
+        if time > duration || end_frequency <= start_frequency {
+            return 0.;
         }
+        let rate = duration / (end_frequency / start_frequency).ln();
+        amplitude * (2. * PI * start_frequency * rate * ((time / rate).exp() - 1.)).sin()
     }
 
     fn sin(&self, time: f32, phase: f32, frequency: f32, amplitude: f32) -> f32 {
@@ -12,6 +12,7 @@ use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
 use crate::math::rect::WRect;
 use crate::math::transformations::grid_to_image;
 use crate::render::gradient::Gradient;
+use crate::simulation::validation::MAX_RADIUS;
 use crate::ui::state::{PlaceType, ToolType};
 
 #[derive(Debug, Default, Clone)]
@@ -519,7 +520,8 @@ impl Wall for CircWall {
 
                 let x_offset = self.center.x as i32 - x as i32;
                 let y_offset = self.center.y as i32 - y as i32;
-                self.radius = ((x_offset.pow(2) + y_offset.pow(2)) as f32).sqrt() as u32;
+                self.radius =
+                    (((x_offset.pow(2) + y_offset.pow(2)) as f32).sqrt() as u32).min(MAX_RADIUS);
             }
             WResize::Menu => {}
             _ => {
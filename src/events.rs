@@ -1,14 +1,21 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_file_dialog::FileDialogExt;
 
+use crate::components::mic_array::MicArray;
 use crate::components::microphone::Microphone;
-use crate::components::source::Source;
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::components::source::{Source, SourceRecords};
 use crate::components::wall::{CircWall, RectWall};
+use crate::remote::osc::OscControl;
 use crate::render::gradient::Gradient;
+use crate::simulation::field_map::FieldMap;
 use crate::simulation::grid::Grid;
 use crate::simulation::plugin::ComponentIDs;
+use crate::simulation::steady_state::SteadyStateMap;
 use crate::ui::loading::SaveFileContents;
 use crate::ui::state::{SimTime, UiState};
+use crate::ui::tabs::DockState;
 
 pub struct EventPlugin;
 
@@ -27,6 +34,7 @@ impl Plugin for EventPlugin {
         .add_event::<UpdateWalls>()
         .add_event::<Reset>()
         .add_event::<Load>()
+        .add_event::<LoadFile>()
         .add_event::<Save>()
         .add_event::<New>();
     }
@@ -59,12 +67,22 @@ pub fn reset_event(
     mut sim_time: ResMut<SimTime>,
     mut ui_state: ResMut<UiState>,
     mut mics: Query<&mut Microphone>,
+    mut source_records: ResMut<SourceRecords>,
+    mut field_map: ResMut<FieldMap>,
+    mut steady_state: ResMut<SteadyStateMap>,
+    mut line_probes: Query<&mut LineProbe>,
+    mut area_probes: Query<&mut AreaProbe>,
 ) {
     for r in reset_ev.read() {
         if ui_state.reset_on_change || r.force {
             sim_time.time_since_start = 0f32;
             grid.reset_cells(ui_state.boundary_width);
             mics.iter_mut().for_each(|mut mic| mic.clear());
+            source_records.clear();
+            field_map.clear();
+            steady_state.clear();
+            line_probes.iter_mut().for_each(|mut probe| probe.clear());
+            area_probes.iter_mut().for_each(|mut probe| probe.clear());
             ui_state.highest_y_volume_plot = 0f64;
         }
     }
@@ -80,6 +98,9 @@ pub fn new_event(
     mics: Query<(Entity, &Microphone)>,
     rect_walls: Query<(Entity, &RectWall)>,
     circ_walls: Query<(Entity, &CircWall)>,
+    mic_arrays: Query<(Entity, &MicArray)>,
+    line_probes: Query<(Entity, &LineProbe)>,
+    area_probes: Query<(Entity, &AreaProbe)>,
     mut ui_state: ResMut<UiState>,
     mut grid: ResMut<Grid>,
     mut wall_update_ev: EventWriter<UpdateWalls>,
@@ -101,6 +122,15 @@ pub fn new_event(
         for (e, _) in mics.iter() {
             commands.entity(e).despawn();
         }
+        for (e, _) in mic_arrays.iter() {
+            commands.entity(e).despawn();
+        }
+        for (e, _) in line_probes.iter() {
+            commands.entity(e).despawn();
+        }
+        for (e, _) in area_probes.iter() {
+            commands.entity(e).despawn();
+        }
 
         grid.reset_cells(ui_state.boundary_width);
         wall_update_ev.send(UpdateWalls);
@@ -113,46 +143,99 @@ pub fn new_event(
     }
 }
 
+/// Event that opens a dialog to save the scene.
+/// If checkpoint is set to true, the state of the simulation is saved as well.
 #[derive(Event)]
 pub struct Save {
     pub new_file: bool,
+    pub checkpoint: bool,
 }
 
-pub fn save_event(
-    mut commands: Commands,
-    mut save_ev: EventReader<Save>,
-    mut new_ev: EventWriter<New>,
-    sources: Query<&Source>,
-    mics: Query<&Microphone>,
-    rect_walls: Query<&RectWall>,
-    circ_walls: Query<&CircWall>,
-    gradient: Res<Gradient>,
-    ui_state: Res<UiState>,
-) {
-    for event in save_ev.read() {
-        let sources = sources.iter().collect::<Vec<_>>();
-        let mics = mics.iter().collect::<Vec<_>>();
-        let rect_walls = rect_walls.iter().collect::<Vec<_>>();
-        let circ_walls = circ_walls.iter().collect::<Vec<_>>();
+/// The scene and the state of the simulation that are saved to scene and checkpoint files.
+#[derive(SystemParam)]
+pub struct SceneParams<'w, 's> {
+    sources: Query<'w, 's, &'static Source>,
+    mics: Query<'w, 's, &'static Microphone>,
+    rect_walls: Query<'w, 's, &'static RectWall>,
+    circ_walls: Query<'w, 's, &'static CircWall>,
+    mic_arrays: Query<'w, 's, &'static MicArray>,
+    line_probes: Query<'w, 's, &'static LineProbe>,
+    area_probes: Query<'w, 's, &'static AreaProbe>,
+    gradient: Res<'w, Gradient>,
+    ui_state: Res<'w, UiState>,
+    grid: Res<'w, Grid>,
+    sim_time: Res<'w, SimTime>,
+    dock_state: Res<'w, DockState>,
+    source_records: Res<'w, SourceRecords>,
+    osc: Res<'w, OscControl>,
+}
+
+impl SceneParams<'_, '_> {
+    /// Serializes the scene as JSON, or a checkpoint of the simulation if `checkpoint` is set.
+    pub fn serialize(&self, checkpoint: bool) -> Result<Vec<u8>, serde_json::Error> {
+        let sources = self.sources.iter().collect::<Vec<_>>();
+        let mics = self.mics.iter().collect::<Vec<_>>();
+        let rect_walls = self.rect_walls.iter().collect::<Vec<_>>();
+        let circ_walls = self.circ_walls.iter().collect::<Vec<_>>();
+        let mic_arrays = self.mic_arrays.iter().collect::<Vec<_>>();
+        let line_probes = self.line_probes.iter().collect::<Vec<_>>();
+        let area_probes = self.area_probes.iter().collect::<Vec<_>>();
 
         let data = crate::ui::saving::serialize(
             &sources,
             &mics,
             &rect_walls,
             &circ_walls,
-            &gradient,
-            ui_state.max_gradient,
-            ui_state.min_gradient,
-        )
-        .unwrap();
+            &mic_arrays,
+            &line_probes,
+            &area_probes,
+            &self.gradient,
+            &self.ui_state,
+            &self.dock_state.tree,
+            &self.osc,
+        )?;
 
-        commands
-            .dialog()
-            .add_filter("JSON", &["json"])
-            .set_file_name("save.json")
-            .set_directory("./")
-            .set_title("Select a file to save to")
-            .save_file::<SaveFileContents>(data);
+        if checkpoint {
+            crate::ui::saving::serialize_checkpoint(
+                &data,
+                &self.grid,
+                &self.ui_state,
+                self.sim_time.time_since_start,
+                &mics,
+                &self.source_records,
+            )
+        } else {
+            Ok(data)
+        }
+    }
+}
+
+pub fn save_event(
+    mut commands: Commands,
+    mut save_ev: EventReader<Save>,
+    mut new_ev: EventWriter<New>,
+    scene: SceneParams,
+) {
+    for event in save_ev.read() {
+        let data = scene.serialize(event.checkpoint).unwrap();
+
+        if event.checkpoint {
+            commands
+                .dialog()
+                .add_filter("Checkpoint", &["ckpt"])
+                .set_file_name("checkpoint.ckpt")
+                .set_directory("./")
+                .set_title("Select a file to save to")
+                .save_file::<SaveFileContents>(data);
+        } else {
+            commands
+                .dialog()
+                .add_filter("JSON", &["json"])
+                .set_file_name("save.json")
+                .set_directory("./")
+                .set_title("Select a file to save to")
+                .save_file::<SaveFileContents>(data);
+        }
 
         if event.new_file {
             new_ev.send(New);
@@ -163,11 +246,17 @@ pub fn save_event(
 #[derive(Event)]
 pub struct Load;
 
+/// Event that loads a scene or checkpoint from the contents of a file, without opening a dialog.
+#[derive(Event)]
+pub struct LoadFile {
+    pub contents: Vec<u8>,
+}
+
 pub fn load_event(mut commands: Commands, mut load_ev: EventReader<Load>) {
     for _ in load_ev.read() {
         commands
             .dialog()
-            .add_filter("JSON", &["json"])
+            .add_filter("Scene or checkpoint", &["json", "ckpt"])
             .set_directory("./")
             .set_title("Select a file to load")
             .load_file::<SaveFileContents>();
@@ -0,0 +1,102 @@
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// Total size of the header in bytes, large enough for any shape of three dimensions.
+/// The header has a fixed size so the amount of frames can be rewritten in place.
+const HEADER_SIZE: usize = 256;
+
+/// Writes a 32 bit float NumPy `.npy` array frame by frame.
+/// The first dimension is the amount of frames and grows with every written frame,
+/// so arbitrarily long recordings never have to be held in memory.
+#[derive(Debug)]
+pub struct NpyWriter<W: Write + Seek> {
+    inner: W,
+    /// shape of a single frame in C order
+    frame_shape: Vec<usize>,
+    frames: usize,
+}
+
+impl<W: Write + Seek> NpyWriter<W> {
+    /// Writes the header of an empty array.
+    /// * `frame_shape` - The shape of a single frame in C order, e.g. `[height, width]`.
+    pub fn new(inner: W, frame_shape: &[usize]) -> io::Result<Self> {
+        let mut writer = Self {
+            inner,
+            frame_shape: frame_shape.to_vec(),
+            frames: 0,
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    /// Amount of values in a single frame
+    pub fn frame_len(&self) -> usize {
+        self.frame_shape.iter().product()
+    }
+
+    /// Amount of frames written so far
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Size of the file in bytes
+    pub fn bytes(&self) -> usize {
+        HEADER_SIZE + self.frames * self.frame_len() * std::mem::size_of::<f32>()
+    }
+
+    /// Appends a frame to the array.
+    pub fn write_frame(&mut self, frame: &[f32]) -> io::Result<()> {
+        if frame.len() != self.frame_len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "frame has {} values, expected {}",
+                    frame.len(),
+                    self.frame_len()
+                ),
+            ));
+        }
+        let bytes = frame
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect::<Vec<_>>();
+        self.inner.write_all(&bytes)?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Rewrites the header with the final amount of frames and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.seek(SeekFrom::Start(0))?;
+        self.write_header()?;
+        self.inner.seek(SeekFrom::End(0))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let mut shape = std::iter::once(self.frames)
+            .chain(self.frame_shape.iter().copied())
+            .map(|dim| dim.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        // a tuple with a single element needs a trailing comma
+        if self.frame_shape.is_empty() {
+            shape.push(',');
+        }
+        let mut dict = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': ({shape}), }}");
+        // magic string, version and header length take 10 bytes, the header ends with a newline
+        if dict.len() + 11 > HEADER_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "shape does not fit into the header",
+            ));
+        }
+        dict.push_str(&" ".repeat(HEADER_SIZE - 11 - dict.len()));
+        dict.push('\n');
+
+        self.inner.write_all(b"\x93NUMPY\x01\x00")?;
+        self.inner
+            .write_all(&((HEADER_SIZE - 10) as u16).to_le_bytes())?;
+        self.inner.write_all(dict.as_bytes())
+    }
+}
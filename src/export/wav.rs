@@ -0,0 +1,212 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Sample formats that can be written to a WAV file.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SampleFormat {
+    Int16,
+    Int24,
+    Float32,
+}
+
+impl SampleFormat {
+    fn bits(&self) -> u16 {
+        match self {
+            SampleFormat::Int16 => 16,
+            SampleFormat::Int24 => 24,
+            SampleFormat::Float32 => 32,
+        }
+    }
+}
+
+impl fmt::Display for SampleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SampleFormat::Int16 => write!(f, "16 bit PCM"),
+            SampleFormat::Int24 => write!(f, "24 bit PCM"),
+            SampleFormat::Float32 => write!(f, "32 bit float"),
+        }
+    }
+}
+
+/// Encodes one or more channels as a WAV file.
+/// Channels of different length are padded with zeros at the end.
+/// Integer formats clip samples outside of -1..=1.
+/// * `channels` - The samples of every channel.
+/// * `sample_rate` - The sample rate in Hz.
+/// * `format` - The sample format to write.
+pub fn encode_wav(channels: &[Vec<f64>], sample_rate: u32, format: SampleFormat) -> Vec<u8> {
+    let num_channels = channels.len().max(1) as u16;
+    let num_frames = channels.iter().map(|c| c.len()).max().unwrap_or(0);
+    let bytes_per_sample = format.bits() / 8;
+    let block_align = num_channels * bytes_per_sample;
+    let data_size = (num_frames * block_align as usize) as u32;
+    // chunks have to start at even offsets, an odd data chunk is followed by a pad byte
+    let pad = data_size % 2;
+    // WAVE_FORMAT_EXTENSIBLE is needed to describe more than two channels
+    // and sample sizes other than 8 or 16 bit PCM
+    let extensible = num_channels > 2 || format == SampleFormat::Int24;
+    let fmt_size: u32 = if extensible { 40 } else { 18 };
+    let format_tag: u16 = match format {
+        SampleFormat::Int16 | SampleFormat::Int24 => 1,
+        SampleFormat::Float32 => 3,
+    };
+
+    let mut data = Vec::with_capacity(data_size as usize + 80);
+    data.extend_from_slice(b"RIFF");
+    data.extend_from_slice(&(4 + 8 + fmt_size + 8 + data_size + pad).to_le_bytes());
+    data.extend_from_slice(b"WAVE");
+
+    data.extend_from_slice(b"fmt ");
+    data.extend_from_slice(&fmt_size.to_le_bytes());
+    data.extend_from_slice(&(if extensible { 0xFFFE } else { format_tag }).to_le_bytes());
+    data.extend_from_slice(&num_channels.to_le_bytes());
+    data.extend_from_slice(&sample_rate.to_le_bytes());
+    data.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    data.extend_from_slice(&block_align.to_le_bytes());
+    data.extend_from_slice(&format.bits().to_le_bytes());
+    if extensible {
+        data.extend_from_slice(&22u16.to_le_bytes());
+        // valid bits per sample
+        data.extend_from_slice(&format.bits().to_le_bytes());
+        // channel mask, no speaker positions assigned
+        data.extend_from_slice(&0u32.to_le_bytes());
+        // sub format GUID
+        data.extend_from_slice(&format_tag.to_le_bytes());
+        data.extend_from_slice(&[
+            0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+        ]);
+    } else {
+        data.extend_from_slice(&0u16.to_le_bytes());
+    }
+
+    data.extend_from_slice(b"data");
+    data.extend_from_slice(&data_size.to_le_bytes());
+    for frame in 0..num_frames {
+        for channel in channels {
+            let sample = channel.get(frame).copied().unwrap_or(0.);
+            match format {
+                SampleFormat::Int16 => {
+                    let value = (sample.clamp(-1., 1.) * i16::MAX as f64).round() as i16;
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+                SampleFormat::Int24 => {
+                    let value = (sample.clamp(-1., 1.) * 8_388_607.).round() as i32;
+                    data.extend_from_slice(&value.to_le_bytes()[..3]);
+                }
+                SampleFormat::Float32 => {
+                    data.extend_from_slice(&(sample as f32).to_le_bytes());
+                }
+            }
+        }
+    }
+    if pad == 1 {
+        data.push(0);
+    }
+
+    data
+}
+
+/// The time between two samples of records of `[time, value]` pairs in seconds,
+/// the median difference of consecutive times of the most finely sampled record.
+/// The median ignores gaps, e.g. while a microphone was disarmed.
+/// Returns `None` if no record has two samples.
+pub fn sample_interval(records: &[&[[f64; 2]]]) -> Option<f64> {
+    records
+        .iter()
+        .filter(|record| record.len() > 1)
+        .map(|record| {
+            let mut differences = record
+                .windows(2)
+                .map(|pair| pair[1][0] - pair[0][0])
+                .collect::<Vec<_>>();
+            differences.sort_by(f64::total_cmp);
+            differences[differences.len() / 2]
+        })
+        .filter(|interval| *interval > 0.)
+        .reduce(f64::min)
+}
+
+/// Places records of `[time, value]` pairs on a common time base with `interval` seconds
+/// between two samples, starting at the earliest time of all records.
+/// Every sample goes to the nearest point of the time base,
+/// points a record has no sample for are silent.
+pub fn align(records: &[&[[f64; 2]]], interval: f64) -> Vec<Vec<f64>> {
+    let times = records
+        .iter()
+        .flat_map(|record| record.iter().map(|x| x[0]));
+    let start = times.clone().reduce(f64::min).unwrap_or(0.);
+    let end = times.reduce(f64::max).unwrap_or(start);
+    let len = if records.iter().all(|record| record.is_empty()) {
+        0
+    } else {
+        ((end - start) / interval).round() as usize + 1
+    };
+
+    records
+        .iter()
+        .map(|record| {
+            let mut samples = vec![0.; len];
+            for [time, value] in record.iter() {
+                let index = ((time - start) / interval).round() as usize;
+                samples[index.min(len - 1)] = *value;
+            }
+            samples
+        })
+        .collect()
+}
+
+/// Resamples a signal with a Hann windowed sinc interpolator.
+/// When downsampling, the cutoff frequency is lowered to avoid aliasing.
+/// * `samples` - The signal to resample.
+/// * `from` - The sample rate of the signal in Hz.
+/// * `to` - The target sample rate in Hz.
+pub fn resample(samples: &[f64], from: f64, to: f64) -> Vec<f64> {
+    if samples.is_empty() || (from - to).abs() < f64::EPSILON {
+        return samples.to_vec();
+    }
+
+    // amount of zero crossings of the sinc on each side
+    const HALF_WIDTH: f64 = 16.;
+
+    let ratio = to / from;
+    let cutoff = ratio.min(1.);
+    let support = HALF_WIDTH / cutoff;
+    let out_len = (samples.len() as f64 * ratio).round() as usize;
+
+    (0..out_len)
+        .map(|n| {
+            let t = n as f64 / ratio;
+            let first = (t - support).ceil().max(0.) as usize;
+            let last = ((t + support).floor() as usize).min(samples.len() - 1);
+            (first..=last)
+                .map(|k| {
+                    let x = t - k as f64;
+                    let sinc = if x.abs() < 1e-12 {
+                        1.
+                    } else {
+                        (PI * x * cutoff).sin() / (PI * x * cutoff)
+                    };
+                    let window = 0.5 * (1. + (PI * x / support).cos());
+                    samples[k] * cutoff * sinc * window
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Scales all channels by the same factor so that the highest absolute sample is `peak`.
+pub fn normalize(channels: &mut [Vec<f64>], peak: f64) {
+    let max = channels
+        .iter()
+        .flat_map(|c| c.iter())
+        .map(|x| x.abs())
+        .reduce(f64::max)
+        .unwrap_or(0.);
+    if max > 0. {
+        channels
+            .iter_mut()
+            .flat_map(|c| c.iter_mut())
+            .for_each(|x| *x *= peak / max);
+    }
+}
@@ -52,7 +52,7 @@ pub fn copy_paste_system(
                 circ_wall.set_center(circ_wall.get_center().x + 5, circ_wall.get_center().y + 5);
                 commands.spawn(circ_wall);
             } else if let Ok((_, mic)) = mics.get(entity) {
-                let mut mic = mic.clone();
+                let mut mic = mic.without_record();
                 mic.id = ids.get_new_mic_id();
                 commands.spawn(mic);
             }
@@ -500,7 +500,10 @@ pub fn event_input(
     }
     // save file
     if ctrl && keys.just_pressed(KeyCode::KeyS) {
-        save_ev.send(Save { new_file: false });
+        save_ev.send(Save {
+            new_file: false,
+            checkpoint: false,
+        });
     }
     // quit program
     if ctrl && keys.just_pressed(KeyCode::KeyQ) {
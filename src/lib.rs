@@ -2,9 +2,12 @@
 
 pub mod components;
 pub mod events;
+pub mod export;
 pub mod input;
 pub mod math;
+pub mod remote;
 pub mod render;
+pub mod scripting;
 pub mod simulation;
 pub mod ui;
 pub mod undo;
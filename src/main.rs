@@ -1,5 +1,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::process::ExitCode;
+use std::sync::Arc;
+
 use bevy::prelude::*;
 use bevy::window::PresentMode;
 use bevy::winit::WinitWindows;
@@ -7,13 +10,28 @@ use bevy_pixel_buffer::bevy_egui::EguiPlugin;
 use bevy_pixel_buffer::prelude::*;
 use wavefront::events::EventPlugin;
 use wavefront::input::plugin::InputPlugin;
+use wavefront::remote::plugin::RemotePlugin;
 use wavefront::render::plugin::RenderPlugin;
+use wavefront::scripting::engine::run_script;
+use wavefront::simulation::headless::HeadlessSimulation;
 use wavefront::simulation::plugin::GridPlugin;
 use wavefront::ui::plugin::UiPlugin;
 use wavefront::undo::UndoPlugin;
 use winit::window::Icon;
 
-fn main() {
+fn main() -> ExitCode {
+    let args = std::env::args().collect::<Vec<_>>();
+    if let Some(position) = args.iter().position(|arg| arg == "--script") {
+        attach_console();
+        return match args.get(position + 1) {
+            Some(path) => run_script_file(path),
+            None => {
+                eprintln!("Usage: wavefront --script <file>");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins.set(WindowPlugin {
@@ -32,12 +50,59 @@ fn main() {
             UiPlugin,
             EventPlugin,
             UndoPlugin,
+            RemotePlugin,
         ))
         .add_systems(Startup, set_window_icon)
         .insert_resource(Time::<Fixed>::from_hz(60.0))
         .run();
+
+    ExitCode::SUCCESS
 }
 
+/// Runs a script without opening a window.
+fn run_script_file(path: &str) -> ExitCode {
+    let script = match std::fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(err) => {
+            eprintln!("Could not read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (_, result) = run_script(
+        &script,
+        HeadlessSimulation::new(),
+        |text| println!("{text}"),
+        Arc::default(),
+    );
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Release builds use the windows subsystem and start without a console,
+/// so the output of the command line mode is written to the console of the parent process.
+#[cfg(windows)]
+fn attach_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    // fails if there is no parent console or one is attached already, nothing is printed then
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
 fn set_window_icon(windows: NonSend<WinitWindows>) {
     let (icon_rgba, icon_width, icon_height) = {
         let image = include_bytes!("../assets/icon.png");
@@ -0,0 +1,204 @@
+use std::f64::consts::SQRT_2;
+
+use super::constants::PROPAGATION_SPEED;
+use super::fft::fft;
+use crate::components::microphone::Microphone;
+
+/// Position of a [`Microphone`] in meters, with the y-axis pointing up.
+fn mic_position(mic: &Microphone, delta_l: f32) -> (f64, f64) {
+    (
+        mic.x as f64 * delta_l as f64,
+        -(mic.y as f64) * delta_l as f64,
+    )
+}
+
+/// Speed of the waves on the mesh in m/s, see [`Grid::wave_speed`](crate::simulation::grid::Grid::wave_speed).
+/// It does not depend on the time between the samples of decimated records.
+const WAVE_SPEED: f64 = PROPAGATION_SPEED as f64 / SQRT_2;
+
+/// Unit vector pointing into the direction `angle` (in °).
+/// 0° points to the right, angles increase counterclockwise.
+fn direction(angle: f32) -> (f64, f64) {
+    let (sin, cos) = (angle as f64).to_radians().sin_cos();
+    (cos, sin)
+}
+
+/// Linearly interpolated value of `signal` at the fractional index `index`.
+fn sample_at(signal: &[f64], index: f64) -> f64 {
+    if index < 0. || index > (signal.len() - 1) as f64 {
+        return 0.;
+    }
+    let lower = index.floor() as usize;
+    let frac = index - lower as f64;
+    if lower + 1 < signal.len() {
+        signal[lower] * (1. - frac) + signal[lower + 1] * frac
+    } else {
+        signal[lower]
+    }
+}
+
+/// Calculates the output of a delay-and-sum beamformer steered into `steering_angle` (in °).
+/// A plane wave is assumed, the delays are applied with linear interpolation.
+/// Only the samples that all microphones have in common (the most recent ones) are used.
+/// Returns the beamformer output as `[time, value]` pairs.
+/// * `mics` - The microphones of the array.
+/// * `steering_angle` - Direction the array is steered to, 0° points to the right, counterclockwise.
+/// * `delta_l` - The size of one cell in meters.
+/// * `delta_t` - The time between each sample in the records.
+pub fn delay_and_sum(
+    mics: &[&Microphone],
+    steering_angle: f32,
+    delta_l: f32,
+    delta_t: f32,
+) -> Vec<[f64; 2]> {
+    let len = match mics.iter().map(|mic| mic.record.len()).min() {
+        Some(len) if len > 0 => len,
+        _ => return vec![],
+    };
+
+    let u = direction(steering_angle);
+    let projections = mics
+        .iter()
+        .map(|mic| {
+            let (x, y) = mic_position(mic, delta_l);
+            x * u.0 + y * u.1
+        })
+        .collect::<Vec<_>>();
+    let min_projection = projections.iter().copied().reduce(f64::min).unwrap_or(0.);
+
+    // mics closer to the steering direction receive the wave earlier and are delayed more
+    let delays = projections
+        .iter()
+        .map(|p| (p - min_projection) / WAVE_SPEED / delta_t as f64)
+        .collect::<Vec<_>>();
+
+    let signals = mics
+        .iter()
+        .map(|mic| {
+            mic.record[mic.record.len() - len..]
+                .iter()
+                .map(|x| x[1])
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let times = &mics[0].record[mics[0].record.len() - len..];
+
+    (0..len)
+        .map(|n| {
+            let sum = signals
+                .iter()
+                .zip(&delays)
+                .map(|(signal, delay)| sample_at(signal, n as f64 - delay))
+                .sum::<f64>();
+            [times[n][0], sum / mics.len() as f64]
+        })
+        .collect()
+}
+
+/// Calculates a direction-of-arrival power map using the steered response power
+/// with phase transform (SRP-PHAT).
+/// The GCC-PHAT of every microphone pair is calculated on the last `window_size` samples
+/// and summed up at the time differences of arrival expected for each direction.
+/// Returns `[angle (in °), power]` pairs, the power is normalized to a maximum of one.
+/// * `mics` - The microphones of the array (at least two are needed).
+/// * `delta_l` - The size of one cell in meters.
+/// * `delta_t` - The time between each sample in the records.
+/// * `window_size` - The amount of samples to evaluate, rounded up to a power of two.
+/// * `resolution` - The angular resolution of the map in degrees.
+pub fn srp_phat(
+    mics: &[&Microphone],
+    delta_l: f32,
+    delta_t: f32,
+    window_size: usize,
+    resolution: f32,
+) -> Vec<[f64; 2]> {
+    if mics.len() < 2 || resolution <= 0. {
+        return vec![];
+    }
+
+    let window_size = window_size.next_power_of_two();
+    // zero padding to avoid circular correlation
+    let fft_size = 2 * window_size;
+
+    let spectra = mics
+        .iter()
+        .map(|mic| {
+            let start = mic.record.len().saturating_sub(window_size);
+            let mut re = vec![0.; fft_size];
+            let mut im = vec![0.; fft_size];
+            for (i, sample) in mic.record[start..].iter().enumerate() {
+                re[i] = sample[1];
+            }
+            fft(&mut re, &mut im, false);
+            (re, im)
+        })
+        .collect::<Vec<_>>();
+
+    let positions = mics
+        .iter()
+        .map(|mic| mic_position(mic, delta_l))
+        .collect::<Vec<_>>();
+
+    let mut pairs = vec![];
+    for i in 0..mics.len() {
+        for j in i + 1..mics.len() {
+            let (re_i, im_i) = &spectra[i];
+            let (re_j, im_j) = &spectra[j];
+            // cross spectrum X_i * conj(X_j), weighted by the phase transform
+            let (mut re, mut im): (Vec<f64>, Vec<f64>) = re_i
+                .iter()
+                .zip(im_i)
+                .zip(re_j.iter().zip(im_j))
+                .map(|((a_re, a_im), (b_re, b_im))| {
+                    let cross_re = a_re * b_re + a_im * b_im;
+                    let cross_im = a_im * b_re - a_re * b_im;
+                    let magnitude = (cross_re * cross_re + cross_im * cross_im).sqrt();
+                    if magnitude > f64::EPSILON {
+                        (cross_re / magnitude, cross_im / magnitude)
+                    } else {
+                        (0., 0.)
+                    }
+                })
+                .unzip();
+            fft(&mut re, &mut im, true);
+            pairs.push((i, j, re));
+        }
+    }
+
+    let steps = (360. / resolution).ceil() as usize;
+    let mut map = (0..steps)
+        .map(|step| {
+            let angle = step as f32 * resolution;
+            let u = direction(angle);
+            let power = pairs
+                .iter()
+                .map(|(i, j, gcc)| {
+                    let (x_i, y_i) = positions[*i];
+                    let (x_j, y_j) = positions[*j];
+                    // expected lag of mic i relative to mic j in samples
+                    let lag = ((x_j - x_i) * u.0 + (y_j - y_i) * u.1) / WAVE_SPEED / delta_t as f64;
+                    // negative lags wrap around
+                    let index = if lag < 0. { lag + fft_size as f64 } else { lag };
+                    sample_at(gcc, index.min((fft_size - 1) as f64))
+                })
+                .sum::<f64>();
+            [angle as f64, power]
+        })
+        .collect::<Vec<_>>();
+
+    let min_power = map.iter().map(|x| x[1]).reduce(f64::min).unwrap_or(0.);
+    let max_power = map.iter().map(|x| x[1]).reduce(f64::max).unwrap_or(0.);
+    if max_power - min_power > f64::EPSILON {
+        map.iter_mut()
+            .for_each(|x| x[1] = (x[1] - min_power) / (max_power - min_power));
+    }
+
+    map
+}
+
+/// Returns the direction (in °) with the highest power of a DOA map from [`srp_phat`].
+pub fn estimate_doa(map: &[[f64; 2]]) -> Option<f64> {
+    map.iter()
+        .max_by(|a, b| a[1].total_cmp(&b[1]))
+        .map(|x| x[0])
+}
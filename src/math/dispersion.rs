@@ -0,0 +1,86 @@
+use std::f32::consts::{PI, SQRT_2};
+
+use super::constants::PROPAGATION_SPEED;
+
+/// Largest relative error of the wave speed that is considered accurate
+pub const DISPERSION_ERROR_THRESHOLD: f32 = 0.01;
+
+/// How accurately waves of a frequency are simulated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    /// the relative error of the wave speed is below [`DISPERSION_ERROR_THRESHOLD`]
+    Accurate(f32),
+    /// the waves are noticeably slower than they should be, contains the relative error
+    Dispersive(f32),
+    /// the waves are above the cutoff frequency of the mesh and do not propagate
+    AboveCutoff,
+    /// the waves cannot be sampled by the time steps
+    AboveNyquist,
+}
+
+impl Resolution {
+    pub fn of(frequency: f32, delta_l: f32) -> Resolution {
+        if frequency > nyquist_frequency(delta_l) {
+            return Resolution::AboveNyquist;
+        }
+        match dispersion_error(frequency, delta_l) {
+            Some(error) if error <= DISPERSION_ERROR_THRESHOLD => Resolution::Accurate(error),
+            Some(error) => Resolution::Dispersive(error),
+            None => Resolution::AboveCutoff,
+        }
+    }
+}
+
+/// The wavelength of a frequency on the grid in cells.
+/// Waves travel with `PROPAGATION_SPEED / √2` on the grid, see [`Grid::wave_speed`].
+///
+/// [`Grid::wave_speed`]: crate::simulation::grid::Grid::wave_speed
+pub fn cells_per_wavelength(frequency: f32, delta_l: f32) -> f32 {
+    PROPAGATION_SPEED / (SQRT_2 * frequency * delta_l)
+}
+
+/// The relative error of the wave speed along the axes of the grid,
+/// `None` above the cutoff frequency where waves do not propagate anymore.
+///
+/// The dispersion relation of a two dimensional TLM mesh is `sin²(kΔl/2) = 2 sin²(ωΔt/2)`,
+/// so waves get slower the less cells a wavelength spans.
+pub fn dispersion_error(frequency: f32, delta_l: f32) -> Option<f32> {
+    if frequency <= 0. {
+        return Some(0.);
+    }
+
+    let delta_t = delta_l / PROPAGATION_SPEED;
+    // ωΔt/2
+    let half_phase = PI * frequency * delta_t;
+    let sin_half_wavenumber = SQRT_2 * half_phase.sin();
+    if sin_half_wavenumber > 1. {
+        return None;
+    }
+    // ratio of the wavenumber without and with dispersion
+    Some(1. - SQRT_2 * half_phase / sin_half_wavenumber.asin())
+}
+
+/// Waves above this frequency do not propagate on the grid.
+pub fn cutoff_frequency(delta_l: f32) -> f32 {
+    PROPAGATION_SPEED / (4. * delta_l)
+}
+
+/// Half of the sample rate of the simulation.
+pub fn nyquist_frequency(delta_l: f32) -> f32 {
+    PROPAGATION_SPEED / (2. * delta_l)
+}
+
+/// The highest frequency with a dispersion error of at most [`DISPERSION_ERROR_THRESHOLD`].
+pub fn max_reliable_frequency(delta_l: f32) -> f32 {
+    // the error grows monotonically up to the cutoff frequency
+    let mut low = 0.;
+    let mut high = cutoff_frequency(delta_l);
+    for _ in 0..40 {
+        let frequency = (low + high) / 2.;
+        match dispersion_error(frequency, delta_l) {
+            Some(error) if error <= DISPERSION_ERROR_THRESHOLD => low = frequency,
+            _ => high = frequency,
+        }
+    }
+    low
+}
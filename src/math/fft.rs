@@ -18,15 +18,15 @@ pub fn calc_mic_spectrum(
     delta_t: f32,
     fft_size: usize,
 ) -> Vec<[f64; 2]> {
-    let samples = if microphone.record.len() < fft_size {
-        let mut s = microphone.record.clone();
-        s.resize(fft_size, [0.0, 0.0]);
-        s
-    } else {
-        microphone.record[microphone.record.len() - fft_size..].to_vec()
-    };
-
-    let hann_window = hann_window(&samples.iter().map(|x| x[1] as f32).collect::<Vec<_>>());
+    // only the last window of the record is needed
+    let start = microphone.record.len().saturating_sub(fft_size);
+    let mut samples = microphone.record[start..]
+        .iter()
+        .map(|x| x[1] as f32)
+        .collect::<Vec<_>>();
+    samples.resize(fft_size, 0.);
+
+    let hann_window = hann_window(&samples);
     // always returns frequencies up to sampling_rate/2
     let spectrum_hann_window = samples_fft_to_spectrum(
         &hann_window,
@@ -55,3 +55,57 @@ pub fn calc_mic_spectrum(
 
     mapped_spectrum
 }
+
+/// In-place radix-2 FFT on separate real and imaginary parts.
+/// The length of both slices has to be a power of two.
+/// The inverse transform is scaled by `1 / n`.
+pub fn fft(re: &mut [f64], im: &mut [f64], inverse: bool) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two(), "fft length must be a power of two");
+    debug_assert_eq!(n, im.len());
+
+    // bit reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1. } else { -1. };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * std::f64::consts::TAU / len as f64;
+        let (w_im, w_re) = angle.sin_cos();
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1., 0.);
+            for k in 0..len / 2 {
+                let a = start + k;
+                let b = a + len / 2;
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        re.iter_mut()
+            .chain(im.iter_mut())
+            .for_each(|x| *x /= n as f64);
+    }
+}
@@ -1,4 +1,10 @@
+pub mod beamforming;
 pub mod constants;
+pub mod dispersion;
 pub mod fft;
 pub mod rect;
+pub mod room_acoustics;
+pub mod room_modes;
+pub mod transfer_function;
 pub mod transformations;
+pub mod units;
@@ -0,0 +1,276 @@
+use super::fft::fft;
+
+/// Center frequencies of the octave bands used for room acoustic parameters (in Hz).
+pub const OCTAVE_BANDS: [f64; 8] = [63., 125., 250., 500., 1000., 2000., 4000., 8000.];
+
+/// Room acoustic parameters according to ISO 3382-1.
+/// Parameters that cannot be determined (e.g. because the decay is not deep enough) are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RoomParameters {
+    /// early decay time in seconds (0 dB to -10 dB)
+    pub edt: Option<f64>,
+    /// reverberation time in seconds evaluated from -5 dB to -25 dB
+    pub t20: Option<f64>,
+    /// reverberation time in seconds evaluated from -5 dB to -35 dB
+    pub t30: Option<f64>,
+    /// clarity for speech in dB (50 ms)
+    pub c50: Option<f64>,
+    /// clarity for music in dB (80 ms)
+    pub c80: Option<f64>,
+    /// definition (ratio of the energy in the first 50 ms to the total energy)
+    pub d50: Option<f64>,
+    /// centre time in seconds
+    pub centre_time: Option<f64>,
+}
+
+/// The analysis of an impulse response in one frequency band.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BandAnalysis {
+    /// center frequency of the octave band in Hz, `None` for the broadband analysis
+    pub center_frequency: Option<f64>,
+    /// Schroeder energy decay curve in dB, starting at the onset of the impulse response
+    pub decay_curve: Vec<f64>,
+    pub parameters: RoomParameters,
+}
+
+/// Calculates the impulse response of a system by deconvolving its response with the excitation signal.
+/// The spectral division is regularized to stay stable in bands with little excitation energy.
+/// Returns an impulse response with the same length as `response`.
+/// * `response` - The recorded response of the system.
+/// * `excitation` - The signal that was used to excite the system.
+pub fn deconvolve(response: &[f64], excitation: &[f64]) -> Vec<f64> {
+    if response.is_empty() || excitation.is_empty() {
+        return vec![];
+    }
+
+    // zero padding to avoid circular convolution
+    let size = (response.len() + excitation.len()).next_power_of_two();
+
+    let mut response_re = response.to_vec();
+    response_re.resize(size, 0.);
+    let mut response_im = vec![0.; size];
+    fft(&mut response_re, &mut response_im, false);
+
+    let mut excitation_re = excitation.to_vec();
+    excitation_re.resize(size, 0.);
+    let mut excitation_im = vec![0.; size];
+    fft(&mut excitation_re, &mut excitation_im, false);
+
+    let max_energy = excitation_re
+        .iter()
+        .zip(&excitation_im)
+        .map(|(re, im)| re * re + im * im)
+        .reduce(f64::max)
+        .unwrap_or(0.);
+    // regularization at -60 dB of the strongest excitation frequency
+    let epsilon = (max_energy * 1e-6).max(f64::MIN_POSITIVE);
+
+    // Y * conj(X) / (|X|^2 + epsilon)
+    let (mut re, mut im): (Vec<f64>, Vec<f64>) = response_re
+        .iter()
+        .zip(&response_im)
+        .zip(excitation_re.iter().zip(&excitation_im))
+        .map(|((y_re, y_im), (x_re, x_im))| {
+            let denominator = x_re * x_re + x_im * x_im + epsilon;
+            (
+                (y_re * x_re + y_im * x_im) / denominator,
+                (y_im * x_re - y_re * x_im) / denominator,
+            )
+        })
+        .unzip();
+    fft(&mut re, &mut im, true);
+
+    re.truncate(response.len());
+    re
+}
+
+/// Filters a signal with a zero-phase octave band filter.
+/// The magnitude response is that of a third order Butterworth band pass, applied in the frequency domain.
+/// * `signal` - The signal to filter.
+/// * `center_frequency` - The center frequency of the octave band in Hz.
+/// * `sample_interval` - The time between two samples in seconds.
+pub fn octave_band_filter(signal: &[f64], center_frequency: f64, sample_interval: f64) -> Vec<f64> {
+    if signal.is_empty() {
+        return vec![];
+    }
+
+    const ORDER: i32 = 3;
+    // quality factor of an octave band
+    let q = 1. / (2_f64.sqrt() - 1. / 2_f64.sqrt());
+
+    let size = (2 * signal.len()).next_power_of_two();
+    let mut re = signal.to_vec();
+    re.resize(size, 0.);
+    let mut im = vec![0.; size];
+    fft(&mut re, &mut im, false);
+
+    let resolution = 1. / (size as f64 * sample_interval);
+    for (k, (re, im)) in re.iter_mut().zip(im.iter_mut()).enumerate() {
+        // negative frequencies are mirrored
+        let frequency = k.min(size - k) as f64 * resolution;
+        let gain = if frequency > 0. {
+            let x = q * (frequency / center_frequency - center_frequency / frequency);
+            1. / (1. + x.powi(2 * ORDER)).sqrt()
+        } else {
+            0.
+        };
+        *re *= gain;
+        *im *= gain;
+    }
+    fft(&mut re, &mut im, true);
+
+    re.truncate(signal.len());
+    re
+}
+
+/// Returns the index of the direct sound in an impulse response.
+/// Following ISO 3382-1 this is the first sample that is at most 20 dB below the maximum.
+pub fn onset(impulse_response: &[f64]) -> usize {
+    let max_energy = impulse_response
+        .iter()
+        .map(|x| x * x)
+        .reduce(f64::max)
+        .unwrap_or(0.);
+    impulse_response
+        .iter()
+        .position(|x| x * x >= max_energy * 0.01)
+        .unwrap_or(0)
+}
+
+/// Calculates the Schroeder energy decay curve (backwards integrated squared impulse response) in dB.
+/// The curve is normalized to 0 dB at the first sample.
+pub fn schroeder_curve(impulse_response: &[f64]) -> Vec<f64> {
+    let mut energy = 0.;
+    let mut curve = impulse_response
+        .iter()
+        .rev()
+        .map(|x| {
+            energy += x * x;
+            energy
+        })
+        .collect::<Vec<_>>();
+    curve.reverse();
+
+    let total = curve.first().copied().unwrap_or(0.);
+    if total <= 0. {
+        return vec![f64::NEG_INFINITY; curve.len()];
+    }
+    curve.iter().map(|e| 10. * (e / total).log10()).collect()
+}
+
+/// Calculates a decay time extrapolated to 60 dB from a linear regression of the decay curve
+/// between `start` and `end` (both in dB, e.g. -5 and -25 for T20).
+/// Returns `None` if the decay curve does not reach `end`.
+/// * `decay_curve` - The Schroeder decay curve in dB.
+/// * `sample_interval` - The time between two samples in seconds.
+pub fn decay_time(decay_curve: &[f64], sample_interval: f64, start: f64, end: f64) -> Option<f64> {
+    let first = decay_curve.iter().position(|x| *x <= start)?;
+    let last = decay_curve.iter().position(|x| *x <= end)?;
+    if last <= first {
+        return None;
+    }
+
+    // least squares fit of level over time
+    let n = (last - first + 1) as f64;
+    let (sum_t, sum_l, sum_tt, sum_tl) = decay_curve[first..=last].iter().enumerate().fold(
+        (0., 0., 0., 0.),
+        |(sum_t, sum_l, sum_tt, sum_tl), (i, level)| {
+            let t = (first + i) as f64 * sample_interval;
+            (sum_t + t, sum_l + level, sum_tt + t * t, sum_tl + t * level)
+        },
+    );
+    let slope = (n * sum_tl - sum_t * sum_l) / (n * sum_tt - sum_t * sum_t);
+
+    if slope < 0. {
+        Some(-60. / slope)
+    } else {
+        None
+    }
+}
+
+/// Sums up the squared impulse response before and after `time` (in seconds).
+fn energy_split(impulse_response: &[f64], sample_interval: f64, time: f64) -> (f64, f64) {
+    let split = ((time / sample_interval).round() as usize).min(impulse_response.len());
+    let early = impulse_response[..split].iter().map(|x| x * x).sum();
+    let late = impulse_response[split..].iter().map(|x| x * x).sum();
+    (early, late)
+}
+
+/// Calculates the ISO 3382-1 parameters of an impulse response that starts at the direct sound.
+/// * `impulse_response` - The impulse response, starting at the direct sound.
+/// * `sample_interval` - The time between two samples in seconds.
+pub fn room_parameters(impulse_response: &[f64], sample_interval: f64) -> RoomParameters {
+    let decay_curve = schroeder_curve(impulse_response);
+
+    let clarity = |time: f64| {
+        let (early, late) = energy_split(impulse_response, sample_interval, time);
+        if early > 0. && late > 0. {
+            Some(10. * (early / late).log10())
+        } else {
+            None
+        }
+    };
+
+    let (early, late) = energy_split(impulse_response, sample_interval, 0.05);
+    let total = early + late;
+
+    let centre_time = if total > 0. {
+        Some(
+            impulse_response
+                .iter()
+                .enumerate()
+                .map(|(i, x)| i as f64 * sample_interval * x * x)
+                .sum::<f64>()
+                / total,
+        )
+    } else {
+        None
+    };
+
+    RoomParameters {
+        edt: decay_time(&decay_curve, sample_interval, 0., -10.),
+        t20: decay_time(&decay_curve, sample_interval, -5., -25.),
+        t30: decay_time(&decay_curve, sample_interval, -5., -35.),
+        c50: clarity(0.05),
+        c80: clarity(0.08),
+        d50: if total > 0. {
+            Some(early / total)
+        } else {
+            None
+        },
+        centre_time,
+    }
+}
+
+/// Analyzes an impulse response broadband and in all [`OCTAVE_BANDS`] below the Nyquist frequency.
+/// The first entry of the result is the broadband analysis.
+/// * `impulse_response` - The impulse response of the system.
+/// * `sample_interval` - The time between two samples in seconds.
+pub fn analyze_impulse_response(
+    impulse_response: &[f64],
+    sample_interval: f64,
+) -> Vec<BandAnalysis> {
+    let nyquist = 0.5 / sample_interval;
+
+    std::iter::once(None)
+        .chain(
+            OCTAVE_BANDS
+                .iter()
+                // the upper band edge has to be below the nyquist frequency
+                .filter(|frequency| **frequency * 2_f64.sqrt() < nyquist)
+                .map(|frequency| Some(*frequency)),
+        )
+        .map(|center_frequency| {
+            let filtered = match center_frequency {
+                Some(frequency) => octave_band_filter(impulse_response, frequency, sample_interval),
+                None => impulse_response.to_vec(),
+            };
+            let start = onset(&filtered);
+            BandAnalysis {
+                center_frequency,
+                decay_curve: schroeder_curve(&filtered[start..]),
+                parameters: room_parameters(&filtered[start..], sample_interval),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,151 @@
+use std::f64::consts::PI;
+
+use super::fft::fft;
+
+/// An eigenmode of a rectangular room.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoomMode {
+    /// frequency in Hz
+    pub frequency: f64,
+    /// amount of half wavelengths in x and y direction
+    pub order: [u32; 2],
+}
+
+/// A peak in a spectrum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectralPeak {
+    /// frequency in Hz, interpolated between the bins
+    pub frequency: f64,
+    /// level in dB
+    pub level: f64,
+    /// height above the surrounding spectrum in dB
+    pub prominence: f64,
+}
+
+/// Calculates the eigenfrequencies of a rectangular room with rigid walls up to `max_frequency`,
+/// sorted by frequency.
+/// * `width` - The size of the room in x direction in meters.
+/// * `height` - The size of the room in y direction in meters.
+/// * `speed` - The speed of sound in m/s.
+pub fn rectangular_room_modes(
+    width: f64,
+    height: f64,
+    speed: f64,
+    max_frequency: f64,
+) -> Vec<RoomMode> {
+    if width <= 0. || height <= 0. || speed <= 0. {
+        return vec![];
+    }
+
+    let max_x = (2. * width * max_frequency / speed) as u32;
+    let max_y = (2. * height * max_frequency / speed) as u32;
+
+    let mut modes = (0..=max_x)
+        .flat_map(|n_x| (0..=max_y).map(move |n_y| [n_x, n_y]))
+        .filter(|order| *order != [0, 0])
+        .map(|order| RoomMode {
+            frequency: speed / 2.
+                * ((order[0] as f64 / width).powi(2) + (order[1] as f64 / height).powi(2)).sqrt(),
+            order,
+        })
+        .filter(|mode| mode.frequency <= max_frequency)
+        .collect::<Vec<_>>();
+    modes.sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+    modes
+}
+
+/// Calculates the average power spectrum of multiple signals in dB.
+/// Every signal is Hann windowed and zero padded to the same power of two.
+/// Returns the frequencies in Hz and the levels in dB.
+/// * `signals` - The signals to average, e.g. impulse responses at different positions.
+/// * `sample_interval` - The time between two samples in seconds.
+pub fn average_spectrum(signals: &[Vec<f64>], sample_interval: f64) -> (Vec<f64>, Vec<f64>) {
+    let len = signals.iter().map(Vec::len).max().unwrap_or(0);
+    if len < 2 {
+        return (vec![], vec![]);
+    }
+    let size = len.next_power_of_two();
+
+    let mut power = vec![0.; size / 2];
+    for signal in signals {
+        let n = signal.len();
+        let mut re = signal
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x * 0.5 * (1. - (2. * PI * i as f64 / n as f64).cos()))
+            .collect::<Vec<_>>();
+        re.resize(size, 0.);
+        let mut im = vec![0.; size];
+        fft(&mut re, &mut im, false);
+
+        for (power, (re, im)) in power.iter_mut().zip(re.iter().zip(&im)) {
+            *power += (re * re + im * im) / signals.len() as f64;
+        }
+    }
+
+    let resolution = 1. / (size as f64 * sample_interval);
+    let frequencies = (0..power.len()).map(|k| k as f64 * resolution).collect();
+    let levels = power
+        .iter()
+        .map(|p| 10. * p.max(f64::MIN_POSITIVE).log10())
+        .collect();
+    (frequencies, levels)
+}
+
+/// Finds the local maxima of a spectrum that stand out by at least `min_prominence` dB.
+/// The prominence of a peak is its height above the higher of the two minima between the peak
+/// and the next higher value on either side.
+/// The frequency of a peak is refined with a parabolic interpolation.
+/// * `frequencies` - The frequencies of the bins in Hz, evenly spaced.
+/// * `levels` - The levels of the bins in dB.
+pub fn find_peaks(frequencies: &[f64], levels: &[f64], min_prominence: f64) -> Vec<SpectralPeak> {
+    if levels.len() < 3 || frequencies.len() != levels.len() {
+        return vec![];
+    }
+    let resolution = frequencies[1] - frequencies[0];
+
+    (1..levels.len() - 1)
+        .filter(|&i| levels[i] > levels[i - 1] && levels[i] >= levels[i + 1])
+        .filter_map(|i| {
+            let level = levels[i];
+            let left_min = levels[..i]
+                .iter()
+                .rev()
+                .take_while(|l| **l <= level)
+                .copied()
+                .fold(level, f64::min);
+            let right_min = levels[i + 1..]
+                .iter()
+                .take_while(|l| **l <= level)
+                .copied()
+                .fold(level, f64::min);
+            let prominence = level - left_min.max(right_min);
+            if prominence < min_prominence {
+                return None;
+            }
+
+            let (a, b, c) = (levels[i - 1], level, levels[i + 1]);
+            let denominator = a - 2. * b + c;
+            let offset = if denominator != 0. {
+                0.5 * (a - c) / denominator
+            } else {
+                0.
+            };
+
+            Some(SpectralPeak {
+                frequency: frequencies[i] + offset * resolution,
+                level: b - 0.25 * (a - c) * offset,
+                prominence,
+            })
+        })
+        .collect()
+}
+
+/// Returns the analytical mode with the frequency closest to `frequency`.
+pub fn nearest_mode(modes: &[RoomMode], frequency: f64) -> Option<&RoomMode> {
+    modes.iter().min_by(|a, b| {
+        (a.frequency - frequency)
+            .abs()
+            .total_cmp(&(b.frequency - frequency).abs())
+    })
+}
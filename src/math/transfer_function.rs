@@ -0,0 +1,189 @@
+use std::f64::consts::PI;
+
+use super::fft::fft;
+
+/// The transfer function between two signals and the coherence between them.
+/// All values are given for the frequencies in `frequencies`, excluding 0 Hz.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TransferFunction {
+    /// frequencies in Hz
+    pub frequencies: Vec<f64>,
+    /// magnitude of the transfer function in dB
+    pub magnitude: Vec<f64>,
+    /// unwrapped phase of the transfer function in degrees
+    pub phase: Vec<f64>,
+    /// group delay in seconds
+    pub group_delay: Vec<f64>,
+    /// magnitude squared coherence between 0 and 1
+    pub coherence: Vec<f64>,
+}
+
+/// Unwraps a phase (in radians) by removing jumps larger than π between neighbouring values.
+pub fn unwrap_phase(phase: &[f64]) -> Vec<f64> {
+    let mut offset = 0.;
+    let mut previous: Option<f64> = None;
+    phase
+        .iter()
+        .map(|value| {
+            if let Some(previous) = previous {
+                let difference = value - previous;
+                if difference > PI {
+                    offset -= 2. * PI * ((difference + PI) / (2. * PI)).floor();
+                } else if difference < -PI {
+                    offset += 2. * PI * ((-difference + PI) / (2. * PI)).floor();
+                }
+            }
+            previous = Some(*value);
+            value + offset
+        })
+        .collect()
+}
+
+/// Pairs the samples of two `[time, value]` records that were taken at the same times.
+/// Records can have gaps, e.g. while a microphone was disarmed, so only the most recent
+/// stretch without gaps in either record is returned.
+/// * `input` - The reference record.
+/// * `output` - The measured record.
+/// * `sample_interval` - The time between two samples in seconds.
+pub fn align_records(
+    input: &[[f64; 2]],
+    output: &[[f64; 2]],
+    sample_interval: f64,
+) -> (Vec<f64>, Vec<f64>) {
+    let tolerance = sample_interval / 2.;
+    let (mut aligned_input, mut aligned_output) = (vec![], vec![]);
+    let (mut i, mut o) = (input.len(), output.len());
+    let mut previous_time = None;
+
+    // walk both records backwards from the most recent sample
+    while i > 0 && o > 0 {
+        let (a, b) = (input[i - 1], output[o - 1]);
+        if a[0] > b[0] + tolerance {
+            i -= 1;
+        } else if b[0] > a[0] + tolerance {
+            o -= 1;
+        } else {
+            if previous_time.is_some_and(|previous: f64| previous - a[0] > sample_interval * 1.5) {
+                break;
+            }
+            aligned_input.push(a[1]);
+            aligned_output.push(b[1]);
+            previous_time = Some(a[0]);
+            i -= 1;
+            o -= 1;
+        }
+    }
+
+    aligned_input.reverse();
+    aligned_output.reverse();
+    (aligned_input, aligned_output)
+}
+
+/// Estimates the transfer function (H1 estimator) from `input` to `output` with Welch's method.
+/// Both signals are split into segments with 50 % overlap which are Hann windowed and averaged.
+/// Only the most recent `averages` segments are used.
+/// * `input` - The reference signal, e.g. the emitted signal of a source.
+/// * `output` - The measured signal, it has to be sampled at the same times as `input`.
+/// * `sample_interval` - The time between two samples in seconds.
+/// * `segment_size` - The amount of samples per segment, rounded up to a power of two.
+/// * `averages` - The maximum amount of segments to average.
+pub fn transfer_function(
+    input: &[f64],
+    output: &[f64],
+    sample_interval: f64,
+    segment_size: usize,
+    averages: usize,
+) -> TransferFunction {
+    let segment_size = segment_size.next_power_of_two();
+    let hop = segment_size / 2;
+    let len = input.len().min(output.len());
+    if len < segment_size || averages == 0 {
+        return TransferFunction::default();
+    }
+
+    // the most recent samples of both signals
+    let input = &input[input.len() - len..];
+    let output = &output[output.len() - len..];
+
+    let segments = ((len - segment_size) / hop + 1).min(averages);
+    let start = len - segment_size - (segments - 1) * hop;
+
+    let window = (0..segment_size)
+        .map(|n| 0.5 * (1. - (2. * PI * n as f64 / segment_size as f64).cos()))
+        .collect::<Vec<_>>();
+
+    let bins = segment_size / 2;
+    let mut g_xx = vec![0.; bins];
+    let mut g_yy = vec![0.; bins];
+    let mut g_xy = vec![(0., 0.); bins];
+
+    for segment in 0..segments {
+        let offset = start + segment * hop;
+        let spectrum = |signal: &[f64]| {
+            let mut re = signal[offset..offset + segment_size]
+                .iter()
+                .zip(&window)
+                .map(|(x, w)| x * w)
+                .collect::<Vec<_>>();
+            let mut im = vec![0.; segment_size];
+            fft(&mut re, &mut im, false);
+            (re, im)
+        };
+        let (x_re, x_im) = spectrum(input);
+        let (y_re, y_im) = spectrum(output);
+
+        let spectra = x_re.iter().zip(&x_im).zip(y_re.iter().zip(&y_im));
+        for (((xx, yy), xy), ((x_re, x_im), (y_re, y_im))) in g_xx
+            .iter_mut()
+            .zip(g_yy.iter_mut())
+            .zip(g_xy.iter_mut())
+            .zip(spectra)
+        {
+            *xx += x_re * x_re + x_im * x_im;
+            *yy += y_re * y_re + y_im * y_im;
+            // conj(X) * Y
+            xy.0 += x_re * y_re + x_im * y_im;
+            xy.1 += x_re * y_im - x_im * y_re;
+        }
+    }
+
+    let resolution = 1. / (segment_size as f64 * sample_interval);
+    let mut result = TransferFunction::default();
+    let mut wrapped_phase = vec![];
+
+    // skip the DC bin
+    for (k, ((xx, yy), (xy_re, xy_im))) in g_xx.iter().zip(&g_yy).zip(&g_xy).enumerate().skip(1) {
+        let cross = xy_re * xy_re + xy_im * xy_im;
+
+        result.frequencies.push(k as f64 * resolution);
+        result.magnitude.push(if *xx > 0. {
+            10. * (cross / (xx * xx)).log10()
+        } else {
+            f64::NEG_INFINITY
+        });
+        result.coherence.push(if *xx > 0. && *yy > 0. {
+            cross / (xx * yy)
+        } else {
+            0.
+        });
+        wrapped_phase.push(xy_im.atan2(*xy_re));
+    }
+
+    let phase = unwrap_phase(&wrapped_phase);
+    let angular_resolution = 2. * PI * resolution;
+    result.group_delay = (0..phase.len())
+        .map(|k| {
+            // central differences, one sided at the edges
+            let lower = k.saturating_sub(1);
+            let upper = (k + 1).min(phase.len() - 1);
+            if upper == lower {
+                0.
+            } else {
+                -(phase[upper] - phase[lower]) / ((upper - lower) as f64 * angular_resolution)
+            }
+        })
+        .collect();
+    result.phase = phase.iter().map(|p| p.to_degrees()).collect();
+
+    result
+}
@@ -0,0 +1,151 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::constants::PROPAGATION_SPEED;
+
+/// Reference pressure of the sound pressure level in Pa
+pub const REFERENCE_PRESSURE: f32 = 20e-6;
+
+/// Density of air at 20 °C in kg/m³
+pub const AIR_DENSITY: f32 = 1.204;
+
+/// How pressures and levels of the simulation are presented.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PressureUnits {
+    /// pressures are the raw sums of the pulses, levels are relative to a pressure of 1
+    #[default]
+    Raw,
+    /// a pressure of 1 on the grid is 1 Pa, levels are sound pressure levels
+    Pascal,
+}
+
+impl PressureUnits {
+    /// Suffix of pressure values, empty for raw values.
+    pub fn pressure_suffix(self) -> &'static str {
+        match self {
+            PressureUnits::Raw => "",
+            PressureUnits::Pascal => "Pa",
+        }
+    }
+
+    /// Suffix of level values.
+    pub fn level_suffix(self) -> &'static str {
+        match self {
+            PressureUnits::Raw => "dB",
+            PressureUnits::Pascal => "dB SPL",
+        }
+    }
+
+    /// Axis label and column name of pressure values.
+    pub fn pressure_label(self) -> &'static str {
+        match self {
+            PressureUnits::Raw => "Amplitude",
+            PressureUnits::Pascal => "Pressure (Pa)",
+        }
+    }
+
+    /// Appends the unit of pressure values to the name of a column.
+    pub fn pressure_column(self, name: &str) -> String {
+        match self {
+            PressureUnits::Raw => name.to_string(),
+            PressureUnits::Pascal => format!("{name} (Pa)"),
+        }
+    }
+
+    /// Has to be added to levels relative to a pressure of 1 to get levels in these units.
+    pub fn level_offset(self) -> f32 {
+        match self {
+            PressureUnits::Raw => 0.,
+            PressureUnits::Pascal => -20. * REFERENCE_PRESSURE.log10(),
+        }
+    }
+}
+
+impl fmt::Display for PressureUnits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PressureUnits::Raw => write!(f, "Raw"),
+            PressureUnits::Pascal => write!(f, "Pa / dB SPL"),
+        }
+    }
+}
+
+/// How the level of a source is entered when physical units are enabled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SourceLevelUnit {
+    /// sound pressure level at a distance of 1 m in dB re 20 µPa
+    #[default]
+    SplAt1m,
+    /// RMS pressure at a distance of 1 m in Pa
+    PressureAt1m,
+    /// sound power per meter of the line source in W/m
+    SoundPower,
+}
+
+impl SourceLevelUnit {
+    pub const ALL: [SourceLevelUnit; 3] = [
+        SourceLevelUnit::SplAt1m,
+        SourceLevelUnit::PressureAt1m,
+        SourceLevelUnit::SoundPower,
+    ];
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            SourceLevelUnit::SplAt1m => " dB",
+            SourceLevelUnit::PressureAt1m => " Pa",
+            SourceLevelUnit::SoundPower => " W/m",
+        }
+    }
+
+    /// Converts the RMS pressure at 1 m to a value in this unit.
+    pub fn level_of(self, pressure_at_1m: f32) -> f32 {
+        match self {
+            SourceLevelUnit::SplAt1m => sound_pressure_level(pressure_at_1m),
+            SourceLevelUnit::PressureAt1m => pressure_at_1m,
+            SourceLevelUnit::SoundPower => sound_power(pressure_at_1m, 1.),
+        }
+    }
+
+    /// Converts a value in this unit to the RMS pressure at 1 m.
+    pub fn to_pressure_at_1m(self, value: f32) -> f32 {
+        match self {
+            SourceLevelUnit::SplAt1m => pressure_from_level(value),
+            SourceLevelUnit::PressureAt1m => value,
+            SourceLevelUnit::SoundPower => pressure_from_sound_power(value, 1.),
+        }
+    }
+}
+
+impl fmt::Display for SourceLevelUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceLevelUnit::SplAt1m => write!(f, "SPL at 1 m"),
+            SourceLevelUnit::PressureAt1m => write!(f, "Pressure at 1 m"),
+            SourceLevelUnit::SoundPower => write!(f, "Sound Power"),
+        }
+    }
+}
+
+/// The sound pressure level of an RMS pressure in dB re 20 µPa.
+pub fn sound_pressure_level(pressure: f32) -> f32 {
+    20. * (pressure / REFERENCE_PRESSURE).log10()
+}
+
+/// The RMS pressure of a sound pressure level in Pa.
+pub fn pressure_from_level(level: f32) -> f32 {
+    REFERENCE_PRESSURE * 10_f32.powf(level / 20.)
+}
+
+/// The sound power per meter of a line source in W/m that causes the given RMS pressure
+/// at a distance in m. The simulation is two dimensional, so sources radiate cylindrical waves
+/// whose intensity `p² / ρc` falls with the circumference `2πr`.
+pub fn sound_power(pressure: f32, distance: f32) -> f32 {
+    2. * PI * distance * pressure * pressure / (AIR_DENSITY * PROPAGATION_SPEED)
+}
+
+/// The RMS pressure in Pa at a distance in m from a line source with the given sound power in W/m.
+pub fn pressure_from_sound_power(power: f32, distance: f32) -> f32 {
+    (power.max(0.) * AIR_DENSITY * PROPAGATION_SPEED / (2. * PI * distance)).sqrt()
+}
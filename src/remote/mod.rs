@@ -0,0 +1,4 @@
+pub mod osc;
+pub mod plugin;
+pub mod protocol;
+pub mod server;
@@ -0,0 +1,324 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::ops::RangeInclusive;
+use std::{fmt, io};
+
+use bevy::prelude::*;
+use rosc::{OscPacket, OscType};
+use serde::{Deserialize, Serialize};
+
+use crate::components::source::Source;
+use crate::events::Reset;
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::ui::state::UiState;
+
+pub const DEFAULT_OSC_PORT: u16 = 9000;
+
+/// Ports that can be selected in the preferences, lower ports are reserved for system services
+pub const OSC_PORTS: RangeInclusive<u16> = 1024..=65535;
+
+/// Largest OSC packet that is received, larger packets are truncated
+const MAX_PACKET_SIZE: usize = 4096;
+
+/// A parameter of a source that can be controlled with OSC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceParameter {
+    X,
+    Y,
+    Frequency,
+    Amplitude,
+    Phase,
+    StdDev,
+    StartFrequency,
+    EndFrequency,
+    Duration,
+}
+
+impl SourceParameter {
+    pub const ALL: [SourceParameter; 9] = [
+        SourceParameter::X,
+        SourceParameter::Y,
+        SourceParameter::Frequency,
+        SourceParameter::Amplitude,
+        SourceParameter::Phase,
+        SourceParameter::StdDev,
+        SourceParameter::StartFrequency,
+        SourceParameter::EndFrequency,
+        SourceParameter::Duration,
+    ];
+
+    /// Sets the parameter of a source. Parameters the source type does not have are ignored.
+    fn apply(self, source: &mut Source, value: f32) {
+        let name = match self {
+            SourceParameter::X => {
+                source.x = (value.round().max(0.) as u32).min(SIMULATION_WIDTH - 1);
+                return;
+            }
+            SourceParameter::Y => {
+                source.y = (value.round().max(0.) as u32).min(SIMULATION_HEIGHT - 1);
+                return;
+            }
+            SourceParameter::Frequency => "frequency",
+            SourceParameter::Amplitude => "amplitude",
+            SourceParameter::Phase => "phase",
+            SourceParameter::StdDev => "std_dev",
+            SourceParameter::StartFrequency => "start_frequency",
+            SourceParameter::EndFrequency => "end_frequency",
+            SourceParameter::Duration => "duration",
+        };
+        if let Some(parameter) = source.source_type.parameter_mut(name) {
+            *parameter = value;
+        }
+    }
+}
+
+impl fmt::Display for SourceParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceParameter::X => write!(f, "X"),
+            SourceParameter::Y => write!(f, "Y"),
+            SourceParameter::Frequency => write!(f, "Frequency"),
+            SourceParameter::Amplitude => write!(f, "Amplitude"),
+            SourceParameter::Phase => write!(f, "Phase"),
+            SourceParameter::StdDev => write!(f, "Standard Deviation"),
+            SourceParameter::StartFrequency => write!(f, "Start Frequency"),
+            SourceParameter::EndFrequency => write!(f, "End Frequency"),
+            SourceParameter::Duration => write!(f, "Duration"),
+        }
+    }
+}
+
+/// What an OSC address controls.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OscTarget {
+    /// starts the simulation when the value is positive
+    Start,
+    /// stops the simulation when the value is positive
+    Stop,
+    /// resets the simulation when the value is positive
+    Reset,
+    Source {
+        id: usize,
+        parameter: SourceParameter,
+    },
+}
+
+impl OscTarget {
+    pub const TRANSPORT: [OscTarget; 3] = [OscTarget::Start, OscTarget::Stop, OscTarget::Reset];
+}
+
+impl fmt::Display for OscTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OscTarget::Start => write!(f, "Start"),
+            OscTarget::Stop => write!(f, "Stop"),
+            OscTarget::Reset => write!(f, "Reset"),
+            OscTarget::Source { .. } => write!(f, "Source"),
+        }
+    }
+}
+
+/// Assigns an OSC address to a target.
+/// Controllers usually send values between 0 and 1, with scaling they are mapped to `min..max`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscMapping {
+    pub address: String,
+    pub target: OscTarget,
+    pub scaled: bool,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for OscMapping {
+    fn default() -> Self {
+        Self {
+            address: "/wavefront/frequency".to_string(),
+            target: OscTarget::Source {
+                id: 0,
+                parameter: SourceParameter::Frequency,
+            },
+            scaled: true,
+            min: 20.,
+            max: 2000.,
+        }
+    }
+}
+
+impl OscMapping {
+    pub fn value(&self, input: f32) -> f32 {
+        if self.scaled {
+            self.min + input * (self.max - self.min)
+        } else {
+            input
+        }
+    }
+}
+
+/// The settings of the OSC input that are saved with the scene.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscSettings {
+    pub enabled: bool,
+    pub port: u16,
+    pub mappings: Vec<OscMapping>,
+}
+
+/// A resource to store the settings and the socket of the OSC input.
+/// The socket is only open while the input is enabled in the preferences.
+#[derive(Resource)]
+pub struct OscControl {
+    pub enabled: bool,
+    pub port: u16,
+    pub mappings: Vec<OscMapping>,
+    socket: Option<(UdpSocket, u16)>,
+    /// why the socket could not be opened
+    pub error: Option<String>,
+    /// the last received message, to find out the addresses of a controller
+    pub last_message: Option<String>,
+}
+
+impl Default for OscControl {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_OSC_PORT,
+            mappings: Vec::new(),
+            socket: None,
+            error: None,
+            last_message: None,
+        }
+    }
+}
+
+impl OscControl {
+    pub fn settings(&self) -> OscSettings {
+        OscSettings {
+            enabled: self.enabled,
+            port: self.port,
+            mappings: self.mappings.clone(),
+        }
+    }
+
+    /// Replaces the settings, the socket is opened or closed by [`update_osc_socket`].
+    pub fn apply_settings(&mut self, settings: OscSettings) {
+        self.enabled = settings.enabled;
+        self.port = settings.port;
+        self.mappings = settings.mappings;
+    }
+
+    /// The port the socket is bound to, `None` if it is closed.
+    pub fn listening_port(&self) -> Option<u16> {
+        self.socket.as_ref().map(|(_, port)| *port)
+    }
+
+    /// All messages received since the last call as pairs of address and first numeric argument.
+    fn receive(&self) -> Vec<(String, Option<f32>)> {
+        let Some((socket, _)) = &self.socket else {
+            return Vec::new();
+        };
+
+        let mut messages = Vec::new();
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            match socket.recv(&mut buffer) {
+                Ok(size) => {
+                    if let Ok((_, packet)) = rosc::decoder::decode_udp(&buffer[..size]) {
+                        collect_messages(packet, &mut messages);
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("could not receive osc message: {err}");
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
+
+fn collect_messages(packet: OscPacket, messages: &mut Vec<(String, Option<f32>)>) {
+    match packet {
+        OscPacket::Message(message) => {
+            let value = message.args.iter().find_map(|arg| match *arg {
+                OscType::Float(value) => Some(value),
+                OscType::Double(value) => Some(value as f32),
+                OscType::Int(value) => Some(value as f32),
+                OscType::Long(value) => Some(value as f32),
+                OscType::Bool(value) => Some(if value { 1. } else { 0. }),
+                _ => None,
+            });
+            messages.push((message.addr, value));
+        }
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                collect_messages(packet, messages);
+            }
+        }
+    }
+}
+
+/// Opens or closes the socket when the input was enabled, disabled or its port changed.
+pub fn update_osc_socket(mut osc: ResMut<OscControl>) {
+    if !osc.is_changed() {
+        return;
+    }
+
+    let wanted_port = osc.enabled.then_some(osc.port);
+    if osc.listening_port() == wanted_port {
+        return;
+    }
+    osc.socket = None;
+    osc.error = None;
+
+    if let Some(port) = wanted_port {
+        // controllers are usually other devices, so all interfaces are used
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))
+            .and_then(|socket| socket.set_nonblocking(true).map(|_| socket));
+        match socket {
+            Ok(socket) => osc.socket = Some((socket, port)),
+            Err(err) => osc.error = Some(format!("Could not listen on port {port}: {err}")),
+        }
+    }
+}
+
+/// Applies the received OSC messages to the mapped targets.
+pub fn receive_osc_messages(
+    mut osc: ResMut<OscControl>,
+    mut ui_state: ResMut<UiState>,
+    mut sources: Query<&mut Source>,
+    mut reset_ev: EventWriter<Reset>,
+) {
+    if osc.socket.is_none() {
+        return;
+    }
+    let messages = osc.receive();
+
+    for (address, input) in messages {
+        osc.last_message = Some(match input {
+            Some(input) => format!("{address} {input}"),
+            None => address.clone(),
+        });
+
+        for mapping in osc
+            .mappings
+            .iter()
+            .filter(|mapping| mapping.address == address)
+        {
+            // messages without arguments are treated like button presses
+            let value = mapping.value(input.unwrap_or(1.));
+            match mapping.target {
+                OscTarget::Start if value > 0. => ui_state.is_running = true,
+                OscTarget::Stop if value > 0. => ui_state.is_running = false,
+                OscTarget::Reset if value > 0. => {
+                    reset_ev.send(Reset { force: true });
+                }
+                OscTarget::Source { id, parameter } => {
+                    if let Some(mut source) = sources.iter_mut().find(|source| source.id == id) {
+                        parameter.apply(&mut source, value);
+                        reset_ev.send(Reset::default());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
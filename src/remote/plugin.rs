@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use super::osc::{receive_osc_messages, update_osc_socket, OscControl};
+use super::protocol::respond;
+use super::server::RemoteServer;
+
+pub const DEFAULT_REMOTE_PORT: u16 = 7341;
+
+pub struct RemotePlugin;
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemoteControl>()
+            .init_resource::<OscControl>()
+            .add_systems(
+                Update,
+                (
+                    (update_remote_server, handle_remote_requests).chain(),
+                    (update_osc_socket, receive_osc_messages).chain(),
+                ),
+            );
+    }
+}
+
+/// A resource to store the settings and the state of the remote control server.
+/// The server only runs while it is enabled in the preferences.
+#[derive(Resource)]
+pub struct RemoteControl {
+    pub enabled: bool,
+    pub port: u16,
+    /// the directory that files are loaded from and screenshots are saved to,
+    /// clients cannot access files outside of it
+    pub working_directory: PathBuf,
+    server: Option<RemoteServer>,
+    /// why the server could not be started
+    pub error: Option<String>,
+}
+
+impl Default for RemoteControl {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_REMOTE_PORT,
+            working_directory: PathBuf::from("."),
+            server: None,
+            error: None,
+        }
+    }
+}
+
+impl RemoteControl {
+    /// The port the server is listening on, `None` if it is not running.
+    pub fn listening_port(&self) -> Option<u16> {
+        self.server.as_ref().map(|server| server.port())
+    }
+}
+
+/// Starts or stops the server when it was enabled, disabled or its port changed.
+fn update_remote_server(mut remote: ResMut<RemoteControl>) {
+    if !remote.is_changed() {
+        return;
+    }
+
+    let wanted_port = remote.enabled.then_some(remote.port);
+    if remote.listening_port() == wanted_port {
+        return;
+    }
+    // the old server is dropped before a new one is started on the same port
+    remote.server = None;
+    remote.error = None;
+
+    if let Some(port) = wanted_port {
+        match RemoteServer::start(port) {
+            Ok(server) => remote.server = Some(server),
+            Err(err) => remote.error = Some(format!("Could not listen on port {port}: {err}")),
+        }
+    }
+}
+
+/// Executes the commands received by the server.
+fn handle_remote_requests(world: &mut World) {
+    let requests = match &world.resource::<RemoteControl>().server {
+        Some(server) => server.requests(),
+        None => return,
+    };
+
+    for request in requests {
+        let response = respond(world, &request.line);
+        // the client may have disconnected in the meantime
+        let _ = request.response.send(response);
+    }
+}
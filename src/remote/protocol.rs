@@ -0,0 +1,472 @@
+//! The command protocol of the remote control server.
+//!
+//! Clients connect to `127.0.0.1:<port>` over TCP and send one JSON object per line.
+//! Every command is answered with one line, either `{"ok": true, "result": ...}`
+//! or `{"ok": false, "error": "..."}`. Positions are given in cells of the simulation area.
+//!
+//! | Command | Result |
+//! | --- | --- |
+//! | `{"command": "status"}` | `{"running", "time", "delta_l", "delta_t"}` |
+//! | `{"command": "start"}`, `{"command": "stop"}` | starts or stops the simulation |
+//! | `{"command": "reset"}` | resets the pressure field and the records |
+//! | `{"command": "new"}` | clears the scene and the settings |
+//! | `{"command": "load", "path": "scene.json"}` | loads a scene or checkpoint file |
+//! | `{"command": "save", "path": "scene.json", "checkpoint": false}` | saves the scene, or a checkpoint of the simulation if `checkpoint` is set |
+//! | `{"command": "scene"}` | `{"sources", "microphones", "rect_walls", "circ_walls"}` |
+//! | `{"command": "add_source", "x": 100, "y": 200, "kind": "sin", "parameters": {"frequency": 500}}` | the id of the source |
+//! | `{"command": "add_microphone", "x": 100, "y": 200}` | the id of the microphone |
+//! | `{"command": "add_rect_wall", "x0": 0, "y0": 0, "x1": 10, "y1": 10, "hollow": false, "reflection_factor": 1}` | the id of the wall |
+//! | `{"command": "add_circ_wall", "x": 100, "y": 100, "radius": 20, "hollow": true, "reflection_factor": 1}` | the id of the wall |
+//! | `{"command": "move", "object": "source", "id": 0, "x": 300, "y": 300}` | moves an object (walls by their center, they are kept inside the simulation area) |
+//! | `{"command": "remove", "object": "microphone", "id": 0}` | removes an object |
+//! | `{"command": "set_source", "id": 0, "kind": "gauss", "parameters": {"amplitude": 5}}` | changes the type or the parameters of a source |
+//! | `{"command": "mic_record", "id": 0}` | the record of a microphone as `[time, pressure]` pairs |
+//! | `{"command": "screenshot", "path": "field.png"}` | saves the simulation area as PNG |
+//!
+//! Paths are relative to the working directory of the remote control and cannot leave it.
+//! Saved files and screenshots never overwrite existing files.
+//!
+//! Objects are `source`, `microphone`, `rect_wall` and `circ_wall`. The source kinds and their
+//! parameters are the same as in scripts, `kind`, `parameters`, `hollow` and `reflection_factor`
+//! are optional.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Component as PathComponent, Path, PathBuf};
+
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::components::microphone::Microphone;
+use crate::components::source::{Source, SourceType};
+use crate::components::wall::{CircWall, RectWall, Wall};
+use crate::events::{LoadFile, New, Reset, SceneParams, UpdateWalls};
+use crate::math::constants::{PROPAGATION_SPEED, SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::remote::plugin::RemoteControl;
+use crate::render::gradient::Gradient;
+use crate::render::screenshot::grid_image;
+use crate::simulation::field_map::FieldMap;
+use crate::simulation::grid::Grid;
+use crate::simulation::plugin::ComponentIDs;
+use crate::simulation::steady_state::SteadyStateMap;
+use crate::simulation::validation;
+use crate::ui::loading::validate_file;
+use crate::ui::state::{SimTime, UiState};
+
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    Status,
+    Start,
+    Stop,
+    Reset,
+    New,
+    Load {
+        path: String,
+    },
+    Save {
+        path: String,
+        #[serde(default)]
+        checkpoint: bool,
+    },
+    Scene,
+    AddSource {
+        x: u32,
+        y: u32,
+        #[serde(default = "default_kind")]
+        kind: String,
+        #[serde(default)]
+        parameters: HashMap<String, f32>,
+    },
+    AddMicrophone {
+        x: u32,
+        y: u32,
+    },
+    AddRectWall {
+        x0: u32,
+        y0: u32,
+        x1: u32,
+        y1: u32,
+        #[serde(default)]
+        hollow: bool,
+        #[serde(default = "default_reflection_factor")]
+        reflection_factor: f32,
+    },
+    AddCircWall {
+        x: u32,
+        y: u32,
+        radius: u32,
+        #[serde(default)]
+        hollow: bool,
+        #[serde(default = "default_reflection_factor")]
+        reflection_factor: f32,
+    },
+    Move {
+        object: Object,
+        id: usize,
+        x: u32,
+        y: u32,
+    },
+    Remove {
+        object: Object,
+        id: usize,
+    },
+    SetSource {
+        id: usize,
+        kind: Option<String>,
+        #[serde(default)]
+        parameters: HashMap<String, f32>,
+    },
+    MicRecord {
+        id: usize,
+    },
+    Screenshot {
+        path: String,
+    },
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Object {
+    Source,
+    Microphone,
+    RectWall,
+    CircWall,
+}
+
+fn default_kind() -> String {
+    "sin".to_string()
+}
+
+fn default_reflection_factor() -> f32 {
+    1.
+}
+
+/// Parses and executes a command and returns the response line.
+pub fn respond(world: &mut World, line: &str) -> String {
+    let result = serde_json::from_str::<Command>(line)
+        .map_err(|err| format!("Invalid command: {err}"))
+        .and_then(|command| execute(world, command));
+
+    match result {
+        Ok(result) => json!({ "ok": true, "result": result }),
+        Err(error) => json!({ "ok": false, "error": error }),
+    }
+    .to_string()
+}
+
+/// Executes a command on the world of the app.
+/// Changes to the scene are handled like changes in the ui, they reset the simulation
+/// if `reset_on_change` is enabled.
+pub fn execute(world: &mut World, command: Command) -> Result<Value, String> {
+    match command {
+        Command::Status => {
+            let ui_state = world.resource::<UiState>();
+            Ok(json!({
+                "running": ui_state.is_running,
+                "time": world.resource::<SimTime>().time_since_start,
+                "delta_l": ui_state.delta_l,
+                "delta_t": ui_state.delta_l / PROPAGATION_SPEED,
+            }))
+        }
+        Command::Start => {
+            world.resource_mut::<UiState>().is_running = true;
+            Ok(Value::Null)
+        }
+        Command::Stop => {
+            world.resource_mut::<UiState>().is_running = false;
+            Ok(Value::Null)
+        }
+        Command::Reset => {
+            world.send_event(Reset { force: true });
+            Ok(Value::Null)
+        }
+        Command::New => {
+            world.send_event(New);
+            Ok(Value::Null)
+        }
+        Command::Load { path } => {
+            let file = working_path(world, &path)?;
+            let contents =
+                std::fs::read(file).map_err(|err| format!("Could not read {path}: {err}"))?;
+            validate_file(&contents).map_err(|errors| errors.join("\n"))?;
+            world.send_event(LoadFile { contents });
+            Ok(Value::Null)
+        }
+        Command::Save { path, checkpoint } => {
+            let mut scene = SystemState::<SceneParams>::new(world);
+            let data = scene
+                .get(world)
+                .serialize(checkpoint)
+                .map_err(|err| format!("Could not serialize the scene: {err}"))?;
+            let file_path = working_path(world, &path)?;
+            let mut file = create_new(&file_path, &path)?;
+            file.write_all(&data).map_err(|err| {
+                let _ = std::fs::remove_file(&file_path);
+                format!("Could not save {path}: {err}")
+            })?;
+            Ok(Value::Null)
+        }
+        Command::Scene => Ok(json!({
+            "sources": sorted::<Source>(world, |source| source.id),
+            "microphones": sorted::<Microphone>(world, |mic| mic.id),
+            "rect_walls": sorted::<RectWall>(world, |wall| wall.id),
+            "circ_walls": sorted::<CircWall>(world, |wall| wall.id),
+        })),
+        Command::AddSource {
+            x,
+            y,
+            kind,
+            parameters,
+        } => {
+            check_position(x, y)?;
+            let mut source_type = source_type(&kind)?;
+            set_parameters(&mut source_type, &parameters)?;
+
+            let id = world.resource_mut::<ComponentIDs>().get_new_source_id();
+            world.spawn(Source::new(x, y, source_type, id));
+            world.send_event(Reset::default());
+            Ok(json!(id))
+        }
+        Command::AddMicrophone { x, y } => {
+            check_position(x, y)?;
+            let id = world.resource_mut::<ComponentIDs>().get_new_mic_id();
+            world.spawn(Microphone::new(x, y, id));
+            world.send_event(Reset::default());
+            Ok(json!(id))
+        }
+        Command::AddRectWall {
+            x0,
+            y0,
+            x1,
+            y1,
+            hollow,
+            reflection_factor,
+        } => {
+            check_position(x0, y0)?;
+            check_position(x1, y1)?;
+            check_reflection_factor(reflection_factor)?;
+            let id = world.resource_mut::<ComponentIDs>().get_new_wall_id();
+            world.spawn(RectWall::new(
+                x0.min(x1),
+                y0.min(y1),
+                x0.max(x1),
+                y0.max(y1),
+                hollow,
+                reflection_factor,
+                id,
+            ));
+            walls_changed(world);
+            Ok(json!(id))
+        }
+        Command::AddCircWall {
+            x,
+            y,
+            radius,
+            hollow,
+            reflection_factor,
+        } => {
+            check_position(x, y)?;
+            check_reflection_factor(reflection_factor)?;
+            validation::radius(radius.into()).map_err(|err| err.to_string())?;
+            let id = world.resource_mut::<ComponentIDs>().get_new_wall_id();
+            world.spawn(CircWall::new(x, y, radius, hollow, reflection_factor, id));
+            walls_changed(world);
+            Ok(json!(id))
+        }
+        Command::Move { object, id, x, y } => {
+            check_position(x, y)?;
+            match object {
+                Object::Source => {
+                    let mut source = component_mut::<Source>(world, id, |source| source.id)?;
+                    source.x = x;
+                    source.y = y;
+                }
+                Object::Microphone => {
+                    let mut mic = component_mut::<Microphone>(world, id, |mic| mic.id)?;
+                    mic.x = x;
+                    mic.y = y;
+                }
+                Object::RectWall => {
+                    component_mut::<RectWall>(world, id, |wall| wall.id)?.set_center(x, y);
+                }
+                Object::CircWall => {
+                    let mut wall = component_mut::<CircWall>(world, id, |wall| wall.id)?;
+                    // like rectangular walls, the moved wall stays inside the simulation area
+                    let x = clamp_to_radius(x, wall.radius, SIMULATION_WIDTH);
+                    let y = clamp_to_radius(y, wall.radius, SIMULATION_HEIGHT);
+                    wall.set_center(x, y);
+                }
+            }
+            match object {
+                Object::RectWall | Object::CircWall => walls_changed(world),
+                Object::Source | Object::Microphone => {
+                    world.send_event(Reset::default());
+                }
+            }
+            Ok(Value::Null)
+        }
+        Command::Remove { object, id } => {
+            let entity = match object {
+                Object::Source => entity_with_id::<Source>(world, id, |source| source.id),
+                Object::Microphone => entity_with_id::<Microphone>(world, id, |mic| mic.id),
+                Object::RectWall => entity_with_id::<RectWall>(world, id, |wall| wall.id),
+                Object::CircWall => entity_with_id::<CircWall>(world, id, |wall| wall.id),
+            }?;
+            world.despawn(entity);
+            match object {
+                Object::RectWall | Object::CircWall => walls_changed(world),
+                Object::Source | Object::Microphone => {
+                    world.send_event(Reset::default());
+                }
+            }
+            Ok(Value::Null)
+        }
+        Command::SetSource {
+            id,
+            kind,
+            parameters,
+        } => {
+            let mut source_type =
+                component_mut::<Source>(world, id, |source| source.id)?.source_type;
+            if let Some(kind) = kind {
+                source_type = self::source_type(&kind)?;
+            }
+            set_parameters(&mut source_type, &parameters)?;
+
+            component_mut::<Source>(world, id, |source| source.id)?.source_type = source_type;
+            world.send_event(Reset::default());
+            Ok(Value::Null)
+        }
+        Command::MicRecord { id } => {
+            let record = component_mut::<Microphone>(world, id, |mic| mic.id)?
+                .record
+                .clone();
+            Ok(json!(record))
+        }
+        Command::Screenshot { path } => {
+            let file_path = working_path(world, &path)?;
+            let file = create_new(&file_path, &path)?;
+            let image = grid_image(
+                world.resource::<UiState>(),
+                world.resource::<Grid>(),
+                world.resource::<Gradient>(),
+                world.resource::<FieldMap>(),
+                world.resource::<SteadyStateMap>(),
+            );
+            image
+                .write_to(&mut BufWriter::new(file), image::ImageFormat::Png)
+                .map_err(|err| {
+                    let _ = std::fs::remove_file(&file_path);
+                    format!("Could not save {path}: {err}")
+                })?;
+            Ok(Value::Null)
+        }
+    }
+}
+
+/// Resolves a path of a client in the working directory of the remote control.
+/// Absolute paths and paths that leave the working directory are rejected.
+fn working_path(world: &World, path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, PathComponent::Normal(_) | PathComponent::CurDir))
+    {
+        return Err(format!(
+            "Invalid path: {path}, expected a path relative to the working directory"
+        ));
+    }
+    Ok(world
+        .resource::<RemoteControl>()
+        .working_directory
+        .join(relative))
+}
+
+/// Creates a file that does not exist yet, clients must not be able to replace files of the user.
+fn create_new(file_path: &Path, path: &str) -> Result<File, String> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(file_path)
+        .map_err(|err| format!("Could not create {path}: {err}"))
+}
+
+/// Clamps the coordinate of the center of a circle so the circle fits into `0..size`,
+/// circles larger than the simulation area are centered.
+fn clamp_to_radius(coordinate: u32, radius: u32, size: u32) -> u32 {
+    if 2 * radius < size {
+        coordinate.clamp(radius, size - 1 - radius)
+    } else {
+        size / 2
+    }
+}
+
+fn check_position(x: u32, y: u32) -> Result<(), String> {
+    validation::position(x.into(), y.into())
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+fn check_reflection_factor(reflection_factor: f32) -> Result<(), String> {
+    validation::reflection_factor(reflection_factor)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+fn source_type(kind: &str) -> Result<SourceType, String> {
+    validation::source_type(kind).map_err(|err| err.to_string())
+}
+
+fn set_parameters(
+    source_type: &mut SourceType,
+    parameters: &HashMap<String, f32>,
+) -> Result<(), String> {
+    for (name, value) in parameters {
+        validation::set_parameter(source_type, name, *value).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// Recalculates the walls and resets the simulation if `reset_on_change` is enabled.
+fn walls_changed(world: &mut World) {
+    world.send_event(UpdateWalls);
+    world.send_event(Reset::default());
+}
+
+/// All components of a type as JSON, sorted by their id.
+fn sorted<T: Component + serde::Serialize>(world: &mut World, id: fn(&T) -> usize) -> Vec<Value> {
+    let mut components = world.query::<&T>().iter(world).collect::<Vec<_>>();
+    components.sort_by_key(|component| id(component));
+    components
+        .into_iter()
+        .map(|component| json!(component))
+        .collect()
+}
+
+fn entity_with_id<T: Component>(
+    world: &mut World,
+    id: usize,
+    id_of: fn(&T) -> usize,
+) -> Result<Entity, String> {
+    world
+        .query::<(Entity, &T)>()
+        .iter(world)
+        .find(|(_, component)| id_of(component) == id)
+        .map(|(entity, _)| entity)
+        .ok_or_else(|| format!("There is no object with id {id}"))
+}
+
+fn component_mut<T: Component>(
+    world: &mut World,
+    id: usize,
+    id_of: fn(&T) -> usize,
+) -> Result<Mut<'_, T>, String> {
+    let entity = entity_with_id(world, id, id_of)?;
+    Ok(world
+        .get_mut::<T>(entity)
+        .expect("the entity has the component"))
+}
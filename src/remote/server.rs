@@ -0,0 +1,123 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use bevy::log::warn;
+
+/// Time between two checks for new connections
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A command received by the server, waiting to be executed by the app.
+pub struct Request {
+    /// the command as sent by the client, a single line of JSON
+    pub line: String,
+    /// the response is sent back to the client that sent the command
+    pub response: Sender<String>,
+}
+
+/// A TCP server on localhost that receives newline delimited commands.
+/// Every connection is handled by its own thread, the commands are collected with
+/// [`RemoteServer::requests`]. The server stops when it is dropped.
+pub struct RemoteServer {
+    port: u16,
+    requests: Mutex<Receiver<Request>>,
+    running: Arc<AtomicBool>,
+}
+
+impl RemoteServer {
+    /// Starts listening on `127.0.0.1:port`.
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        // the listener is polled, so the thread notices when the server is stopped
+        listener.set_nonblocking(true)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_running = running.clone();
+        thread::spawn(move || accept_connections(listener, sender, thread_running));
+
+        Ok(Self {
+            port,
+            requests: Mutex::new(receiver),
+            running,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// All commands received since the last call.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests
+            .lock()
+            .expect("the receiver is only used by the app")
+            .try_iter()
+            .collect()
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+fn accept_connections(listener: TcpListener, sender: Sender<Request>, running: Arc<AtomicBool>) {
+    while running.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let sender = sender.clone();
+                thread::spawn(move || handle_connection(stream, sender));
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_INTERVAL),
+            Err(err) => {
+                warn!("could not accept remote control connection: {err}");
+                thread::sleep(ACCEPT_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Forwards the commands of a client to the app and writes the responses back.
+/// The connection is closed when the client disconnects or the server is stopped.
+fn handle_connection(stream: TcpStream, sender: Sender<Request>) {
+    // accepted streams may inherit the non-blocking mode of the listener
+    if stream.set_nonblocking(false).is_err() {
+        return;
+    }
+    let Ok(reader) = stream.try_clone() else {
+        return;
+    };
+    let mut writer = stream;
+
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response_sender, response_receiver) = mpsc::channel();
+        let request = Request {
+            line,
+            response: response_sender,
+        };
+        // both fail if the server was stopped in the meantime
+        if sender.send(request).is_err() {
+            break;
+        }
+        let Ok(response) = response_receiver.recv() else {
+            break;
+        };
+        if writeln!(writer, "{response}").is_err() {
+            break;
+        }
+    }
+}
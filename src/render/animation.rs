@@ -0,0 +1,317 @@
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use bevy::prelude::*;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{self, FilterType};
+use image::{Delay, DynamicImage, Frame, ImageResult, RgbImage};
+
+use super::annotations::{annotate_image, Annotations, Overlays};
+use super::gradient::Gradient;
+use super::screenshot::grid_image;
+use crate::components::microphone::Microphone;
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::components::source::Source;
+use crate::math::constants::SIMULATION_WIDTH;
+use crate::simulation::field_map::FieldMap;
+use crate::simulation::grid::Grid;
+use crate::simulation::steady_state::SteadyStateMap;
+use crate::ui::state::{SimTime, UiState};
+
+/// Amount of captured frames that can wait for the writer thread.
+/// Capturing blocks once the writer falls this far behind.
+const FRAME_QUEUE_LENGTH: usize = 32;
+
+/// The file format of an animation.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum AnimationFormat {
+    /// numbered PNG files in a directory
+    #[default]
+    PngSequence,
+    /// a single animated GIF
+    Gif,
+}
+
+impl fmt::Display for AnimationFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationFormat::PngSequence => write!(f, "PNG Sequence"),
+            AnimationFormat::Gif => write!(f, "Animated GIF"),
+        }
+    }
+}
+
+/// Records every n-th simulation step as a frame of an animation.
+/// Frames are rendered from the simulation state instead of the screen,
+/// so the animation is smooth even if the app cannot render every step.
+/// They are encoded and written on a separate thread.
+#[derive(Resource)]
+pub struct AnimationRecorder {
+    pub format: AnimationFormat,
+    /// directory of a PNG sequence or file of a GIF
+    pub path: PathBuf,
+    /// every n-th simulation step is captured
+    pub step_interval: u32,
+    /// width and height of the simulation area in the frames in pixels
+    pub resolution: u32,
+    /// playback rate of the GIF in frames per second
+    pub frame_rate: u32,
+    /// draw the outlines of the sources, microphones, probes and walls
+    pub show_overlays: bool,
+    pub show_color_bar: bool,
+    pub show_time: bool,
+    /// the recording stops automatically after this amount of frames, if set
+    pub max_frames: Option<usize>,
+    /// the last error that stopped the recording
+    pub error: Option<String>,
+    is_recording: bool,
+    /// sends frames to the writer thread while recording
+    sender: Option<SyncSender<RgbImage>>,
+    /// the writer thread, it finishes the queued frames after the recording stopped
+    writer: Option<JoinHandle<ImageResult<()>>>,
+    frames: usize,
+    steps: u32,
+}
+
+impl Default for AnimationRecorder {
+    fn default() -> Self {
+        Self {
+            format: AnimationFormat::PngSequence,
+            path: PathBuf::from("animation"),
+            step_interval: 10,
+            resolution: SIMULATION_WIDTH,
+            frame_rate: 25,
+            show_overlays: true,
+            show_color_bar: true,
+            show_time: true,
+            max_frames: None,
+            error: None,
+            is_recording: false,
+            sender: None,
+            writer: None,
+            frames: 0,
+            steps: 0,
+        }
+    }
+}
+
+impl AnimationRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    /// Amount of frames captured so far
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Whether the writer thread is still writing queued frames after the recording stopped.
+    pub fn is_writing(&self) -> bool {
+        !self.is_recording && self.writer.is_some()
+    }
+
+    /// Creates the output directory or file and starts capturing.
+    /// Waits for the frames of a previous recording to be written.
+    pub fn start(&mut self) -> ImageResult<()> {
+        self.stop();
+        self.join_writer();
+        self.error = None;
+        self.step_interval = self.step_interval.max(1);
+        self.resolution = self.resolution.max(1);
+
+        let output = match self.format {
+            AnimationFormat::PngSequence => {
+                std::fs::create_dir_all(&self.path)?;
+                Output::PngSequence(self.path.clone())
+            }
+            AnimationFormat::Gif => {
+                let file = BufWriter::new(File::create(&self.path)?);
+                // speed 10 is the recommended tradeoff between quantization quality and speed
+                let mut encoder = GifEncoder::new_with_speed(file, 10);
+                encoder.set_repeat(Repeat::Infinite)?;
+                Output::Gif(
+                    encoder,
+                    Delay::from_numer_denom_ms(1000, self.frame_rate.max(1)),
+                )
+            }
+        };
+        let (sender, receiver) = mpsc::sync_channel(FRAME_QUEUE_LENGTH);
+        self.sender = Some(sender);
+        self.writer = Some(thread::spawn(move || write_frames(output, receiver)));
+        self.frames = 0;
+        self.steps = 0;
+        self.is_recording = true;
+        Ok(())
+    }
+
+    /// Advances the recorder by one simulation step.
+    /// Returns whether a frame should be captured at this step.
+    pub fn next_step(&mut self) -> bool {
+        if !self.is_recording {
+            return false;
+        }
+        self.steps += 1;
+        (self.steps - 1).is_multiple_of(self.step_interval)
+    }
+
+    /// Queues a frame for the writer thread. Stops the recording once the maximum amount
+    /// of frames is reached. Returns the error of the writer thread if it stopped.
+    pub fn write_frame(&mut self, frame: RgbImage) -> ImageResult<()> {
+        let Some(sender) = self.sender.as_ref() else {
+            return Ok(());
+        };
+        if sender.send(frame).is_err() {
+            // the writer thread only stops early because of an error
+            self.stop();
+            return match self.writer.take().map(JoinHandle::join) {
+                Some(Ok(result)) => result,
+                _ => Ok(()),
+            };
+        }
+        self.frames += 1;
+
+        if self.max_frames.is_some_and(|max| self.frames >= max) {
+            self.stop();
+        }
+        Ok(())
+    }
+
+    /// Stops the recording. The writer thread finishes the queued frames in the background,
+    /// the GIF is finished when its encoder is dropped.
+    pub fn stop(&mut self) {
+        self.sender = None;
+        self.is_recording = false;
+    }
+
+    /// Stores the error of the writer thread once it finished.
+    pub fn poll_writer(&mut self) {
+        if self.writer.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.join_writer();
+        }
+    }
+
+    /// Waits for the writer thread and stores its error.
+    fn join_writer(&mut self) {
+        let Some(writer) = self.writer.take() else {
+            return;
+        };
+        let result = match writer.join() {
+            Ok(result) => result.map_err(|err| err.to_string()),
+            Err(_) => Err("The animation writer panicked".to_string()),
+        };
+        if let Err(err) = result {
+            self.stop();
+            self.error = Some(err);
+        }
+    }
+
+    /// Renders a frame of the current simulation state with the selected decorations.
+    pub fn render_frame(
+        &self,
+        ui_state: &UiState,
+        grid: &Grid,
+        gradient: &Gradient,
+        field_map: &FieldMap,
+        steady_state: &SteadyStateMap,
+        time: f32,
+        overlays: &Overlays,
+    ) -> RgbImage {
+        let image = grid_image(ui_state, grid, gradient, field_map, steady_state);
+        let image = if self.resolution != image.width() {
+            imageops::resize(
+                &image,
+                self.resolution,
+                self.resolution,
+                FilterType::Nearest,
+            )
+        } else {
+            image
+        };
+
+        annotate_image(
+            &image,
+            Annotations {
+                outlines: self.show_overlays,
+                color_bar: self.show_color_bar,
+                scale_bar: false,
+                time: self.show_time,
+            },
+            overlays,
+            gradient,
+            ui_state.color_scale(),
+            ui_state.delta_l,
+            time,
+        )
+    }
+}
+
+/// Where the writer thread writes the frames to.
+enum Output {
+    PngSequence(PathBuf),
+    Gif(GifEncoder<BufWriter<File>>, Delay),
+}
+
+/// Writes the frames of the receiver until the recording stops.
+fn write_frames(mut output: Output, receiver: Receiver<RgbImage>) -> ImageResult<()> {
+    for (index, frame) in receiver.into_iter().enumerate() {
+        match &mut output {
+            Output::PngSequence(directory) => {
+                frame.save(directory.join(format!("frame_{index:05}.png")))?;
+            }
+            Output::Gif(encoder, delay) => {
+                encoder.encode_frame(Frame::from_parts(
+                    DynamicImage::ImageRgb8(frame).into_rgba8(),
+                    0,
+                    0,
+                    *delay,
+                ))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A system used to capture the frames of an animation while the simulation is running
+pub fn animation_system(
+    mut recorder: ResMut<AnimationRecorder>,
+    grid: Res<Grid>,
+    gradient: Res<Gradient>,
+    ui_state: Res<UiState>,
+    field_map: Res<FieldMap>,
+    steady_state: Res<SteadyStateMap>,
+    sim_time: Res<SimTime>,
+    sources: Query<&Source>,
+    microphones: Query<&Microphone>,
+    line_probes: Query<&LineProbe>,
+    area_probes: Query<&AreaProbe>,
+) {
+    recorder.poll_writer();
+    if !ui_state.is_running || !recorder.next_step() {
+        return;
+    }
+
+    let overlays = Overlays::new(
+        sources.iter(),
+        microphones.iter(),
+        line_probes.iter(),
+        area_probes.iter(),
+    )
+    .with_walls(&grid, ui_state.boundary_width);
+    let frame = recorder.render_frame(
+        &ui_state,
+        &grid,
+        &gradient,
+        &field_map,
+        &steady_state,
+        sim_time.time_since_start,
+        &overlays,
+    );
+    if let Err(err) = recorder.write_frame(frame) {
+        recorder.error = Some(err.to_string());
+        recorder.stop();
+    }
+}
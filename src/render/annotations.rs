@@ -0,0 +1,526 @@
+use std::fmt::Write;
+
+use bevy::math::UVec2;
+use image::{imageops, Rgb, RgbImage};
+
+use super::gradient::Gradient;
+use super::raster::{
+    draw_circle, draw_color_bar, draw_label, draw_line, draw_text, fill_rect, text_width,
+    GLYPH_HEIGHT,
+};
+use crate::components::microphone::Microphone;
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::components::source::Source;
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::transformations::coords_to_index;
+use crate::simulation::grid::Grid;
+
+const SOURCE_COLOR: [u8; 3] = [15, 194, 192];
+const MICROPHONE_COLOR: [u8; 3] = [255, 0, 0];
+const PROBE_COLOR: [u8; 3] = [255, 200, 60];
+const WALL_OUTLINE_COLOR: [u8; 3] = [255, 255, 255];
+
+/// The annotations that are drawn on exported images.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Annotations {
+    /// outlines of the sources, microphones, probes and walls
+    pub outlines: bool,
+    pub color_bar: bool,
+    pub scale_bar: bool,
+    pub time: bool,
+}
+
+/// Positions of the components that are drawn on top of exported images, in grid coordinates.
+#[derive(Debug, Default, Clone)]
+pub struct Overlays {
+    pub sources: Vec<UVec2>,
+    pub microphones: Vec<UVec2>,
+    pub line_probes: Vec<[UVec2; 2]>,
+    /// top left and bottom right corner
+    pub area_probes: Vec<[UVec2; 2]>,
+    /// edges between wall and air cells as start and end corner
+    pub wall_edges: Vec<[UVec2; 2]>,
+}
+
+impl Overlays {
+    pub fn new<'a>(
+        sources: impl Iterator<Item = &'a Source>,
+        microphones: impl Iterator<Item = &'a Microphone>,
+        line_probes: impl Iterator<Item = &'a LineProbe>,
+        area_probes: impl Iterator<Item = &'a AreaProbe>,
+    ) -> Self {
+        Self {
+            sources: sources
+                .map(|source| UVec2::new(source.x, source.y))
+                .collect(),
+            microphones: microphones.map(|mic| UVec2::new(mic.x, mic.y)).collect(),
+            line_probes: line_probes.map(|probe| [probe.start, probe.end]).collect(),
+            area_probes: area_probes
+                .map(|probe| {
+                    let (min, max) = probe.bounds();
+                    [min, max + UVec2::ONE]
+                })
+                .collect(),
+            wall_edges: vec![],
+        }
+    }
+
+    /// Adds the outlines of all walls. Neighboring edges are merged into one line.
+    pub fn with_walls(mut self, grid: &Grid, boundary_width: u32) -> Self {
+        let is_wall = |x: i64, y: i64| {
+            x >= 0
+                && y >= 0
+                && x < SIMULATION_WIDTH as i64
+                && y < SIMULATION_HEIGHT as i64
+                && grid.wall_cache[coords_to_index(
+                    x as u32 + boundary_width,
+                    y as u32 + boundary_width,
+                    boundary_width,
+                )]
+                .is_wall
+        };
+
+        // horizontal edges above every row of cells
+        for y in 0..=SIMULATION_HEIGHT as i64 {
+            let mut start = None;
+            for x in 0..=SIMULATION_WIDTH as i64 {
+                let is_edge = x < SIMULATION_WIDTH as i64 && is_wall(x, y - 1) != is_wall(x, y);
+                match (is_edge, start) {
+                    (true, None) => start = Some(x),
+                    (false, Some(x0)) => {
+                        self.wall_edges.push([
+                            UVec2::new(x0 as u32, y as u32),
+                            UVec2::new(x as u32, y as u32),
+                        ]);
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        // vertical edges left of every column of cells
+        for x in 0..=SIMULATION_WIDTH as i64 {
+            let mut start = None;
+            for y in 0..=SIMULATION_HEIGHT as i64 {
+                let is_edge = y < SIMULATION_HEIGHT as i64 && is_wall(x - 1, y) != is_wall(x, y);
+                match (is_edge, start) {
+                    (true, None) => start = Some(y),
+                    (false, Some(y0)) => {
+                        self.wall_edges.push([
+                            UVec2::new(x as u32, y0 as u32),
+                            UVec2::new(x as u32, y as u32),
+                        ]);
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        self
+    }
+
+    /// Draws the components in the colors of their gizmos.
+    /// * `scale` - The size of a cell in pixels.
+    /// * `line_scale` - The width of the lines in pixels.
+    pub fn draw(&self, image: &mut RgbImage, scale: f32, line_scale: u32) {
+        let corner = |pos: UVec2| [pos.x as f32 * scale, pos.y as f32 * scale];
+        let center = |pos: UVec2| [(pos.x as f32 + 0.5) * scale, (pos.y as f32 + 0.5) * scale];
+
+        for [start, end] in &self.wall_edges {
+            draw_line(
+                image,
+                corner(*start),
+                corner(*end),
+                line_scale,
+                Rgb(WALL_OUTLINE_COLOR),
+            );
+        }
+        for [start, end] in &self.line_probes {
+            draw_line(
+                image,
+                center(*start),
+                center(*end),
+                2 * line_scale,
+                Rgb(PROBE_COLOR),
+            );
+        }
+        for [min, max] in &self.area_probes {
+            let [x0, y0] = corner(*min);
+            let [x1, y1] = corner(*max);
+            for (start, end) in [
+                ([x0, y0], [x1, y0]),
+                ([x1, y0], [x1, y1]),
+                ([x1, y1], [x0, y1]),
+                ([x0, y1], [x0, y0]),
+            ] {
+                draw_line(image, start, end, 2 * line_scale, Rgb(PROBE_COLOR));
+            }
+        }
+        for source in &self.sources {
+            draw_circle(
+                image,
+                center(*source),
+                5. * line_scale as f32,
+                None,
+                Rgb(SOURCE_COLOR),
+            );
+        }
+        for mic in &self.microphones {
+            draw_circle(
+                image,
+                center(*mic),
+                5. * line_scale as f32,
+                None,
+                Rgb(MICROPHONE_COLOR),
+            );
+            draw_circle(
+                image,
+                center(*mic),
+                6. * line_scale as f32,
+                Some(line_scale as f32),
+                Rgb([0, 0, 0]),
+            );
+        }
+    }
+
+    /// Writes the components as SVG elements.
+    /// * `scale` - The size of a cell in pixels.
+    /// * `line_scale` - The width of the lines in pixels.
+    fn write_svg(&self, svg: &mut String, scale: f32, line_scale: f32) {
+        let corner = |pos: UVec2| (pos.x as f32 * scale, pos.y as f32 * scale);
+        let center = |pos: UVec2| ((pos.x as f32 + 0.5) * scale, (pos.y as f32 + 0.5) * scale);
+
+        if !self.wall_edges.is_empty() {
+            let mut path = String::new();
+            for [start, end] in &self.wall_edges {
+                let (x0, y0) = corner(*start);
+                let (x1, y1) = corner(*end);
+                let _ = write!(path, "M{x0} {y0}L{x1} {y1}");
+            }
+            let _ = writeln!(
+                svg,
+                r#"<path d="{path}" fill="none" stroke="{}" stroke-width="{line_scale}"/>"#,
+                hex(WALL_OUTLINE_COLOR)
+            );
+        }
+        for [start, end] in &self.line_probes {
+            let (x0, y0) = center(*start);
+            let (x1, y1) = center(*end);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x0}" y1="{y0}" x2="{x1}" y2="{y1}" stroke="{}" stroke-width="{}"/>"#,
+                hex(PROBE_COLOR),
+                2. * line_scale
+            );
+        }
+        for [min, max] in &self.area_probes {
+            let (x0, y0) = corner(*min);
+            let (x1, y1) = corner(*max);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{x0}" y="{y0}" width="{}" height="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                x1 - x0,
+                y1 - y0,
+                hex(PROBE_COLOR),
+                2. * line_scale
+            );
+        }
+        for source in &self.sources {
+            let (x, y) = center(*source);
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{x}" cy="{y}" r="{}" fill="{}"/>"#,
+                5. * line_scale,
+                hex(SOURCE_COLOR)
+            );
+        }
+        for mic in &self.microphones {
+            let (x, y) = center(*mic);
+            let _ = writeln!(
+                svg,
+                r##"<circle cx="{x}" cy="{y}" r="{}" fill="{}" stroke="#000000" stroke-width="{line_scale}"/>"##,
+                5.5 * line_scale,
+                hex(MICROPHONE_COLOR)
+            );
+        }
+    }
+}
+
+/// Positions and sizes of the annotations in pixels, shared by PNG and SVG exports.
+struct Layout {
+    /// width and height of the simulation area
+    size: u32,
+    /// size of a font pixel, the text is about 2% of the image high
+    text_scale: u32,
+    margin: u32,
+    /// width of the area right of the simulation that holds the color bar
+    color_bar_width: u32,
+}
+
+impl Layout {
+    fn new(size: u32, color_bar: bool) -> Self {
+        let text_scale = (size / (GLYPH_HEIGHT * 50)).max(1);
+        Self {
+            size,
+            text_scale,
+            margin: 4 * text_scale,
+            color_bar_width: if color_bar {
+                GLYPH_HEIGHT * text_scale * 12
+            } else {
+                0
+            },
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.size + self.color_bar_width
+    }
+
+    /// x, y, width and height of the color bar
+    fn color_bar(&self) -> [u32; 4] {
+        [
+            self.size + self.margin,
+            self.margin,
+            3 * self.margin,
+            self.size / 3,
+        ]
+    }
+
+    /// Length of the scale bar in pixels and its label.
+    fn scale_bar(&self, delta_l: f32) -> (u32, String) {
+        let cell = self.size as f32 / SIMULATION_WIDTH as f32;
+        let (meters, label) = scale_bar_length(SIMULATION_WIDTH as f32 * delta_l / 5.);
+        ((meters / delta_l * cell).round() as u32, label)
+    }
+}
+
+/// Rounds a length in meters down to 1, 2 or 5 times a power of ten and formats it.
+fn scale_bar_length(max_length: f32) -> (f32, String) {
+    let power = 10_f32.powf(max_length.log10().floor());
+    let factor = [5., 2., 1.]
+        .into_iter()
+        .find(|factor| factor * power <= max_length)
+        .unwrap_or(1.);
+    let length = factor * power;
+    let label = if length >= 1. {
+        format!("{} m", length)
+    } else if length >= 0.01 {
+        format!("{} cm", (length * 100.).round())
+    } else {
+        format!("{} mm", (length * 1000.).round())
+    };
+    (length, label)
+}
+
+/// Formats a simulation time with a suitable unit.
+pub fn format_time(time: f32) -> String {
+    if time < 1. {
+        format!("t = {:.3} ms", time * 1000.)
+    } else {
+        format!("t = {:.4} s", time)
+    }
+}
+
+/// Draws the annotations on an image of the simulation area.
+/// The image is placed on a larger canvas if the color bar is shown.
+/// * `image` - The simulation area, already scaled to its final size.
+/// * `color_scale` - The values at both ends of the color scale and their unit.
+pub fn annotate_image(
+    image: &RgbImage,
+    annotations: Annotations,
+    overlays: &Overlays,
+    gradient: &Gradient,
+    color_scale: (f32, f32, &str),
+    delta_l: f32,
+    time: f32,
+) -> RgbImage {
+    let layout = Layout::new(image.width(), annotations.color_bar);
+    let mut canvas = RgbImage::new(layout.width(), layout.size);
+    imageops::replace(&mut canvas, image, 0, 0);
+
+    if annotations.outlines {
+        overlays.draw(
+            &mut canvas,
+            layout.size as f32 / SIMULATION_WIDTH as f32,
+            layout.text_scale,
+        );
+    }
+
+    if annotations.color_bar {
+        let (min, max, unit) = color_scale;
+        draw_color_bar(
+            &mut canvas,
+            gradient,
+            layout.color_bar(),
+            min,
+            max,
+            unit,
+            layout.text_scale,
+        );
+    }
+
+    if annotations.scale_bar {
+        let (length, label) = layout.scale_bar(delta_l);
+        let x = layout.margin as i32;
+        let bar_height = 2 * layout.text_scale;
+        let y = (layout.size - layout.margin - bar_height) as i32;
+        fill_rect(
+            &mut canvas,
+            x - layout.text_scale as i32,
+            y - (GLYPH_HEIGHT + 4) as i32 * layout.text_scale as i32,
+            length.max(text_width(&label, layout.text_scale)) + 2 * layout.text_scale,
+            (GLYPH_HEIGHT + 5) * layout.text_scale + bar_height,
+            Rgb([0, 0, 0]),
+        );
+        fill_rect(&mut canvas, x, y, length, bar_height, Rgb([255, 255, 255]));
+        draw_text(
+            &mut canvas,
+            x,
+            y - (GLYPH_HEIGHT + 3) as i32 * layout.text_scale as i32,
+            &label,
+            layout.text_scale,
+            Rgb([255, 255, 255]),
+        );
+    }
+
+    if annotations.time {
+        draw_label(
+            &mut canvas,
+            layout.margin as i32,
+            layout.margin as i32,
+            &format_time(time),
+            layout.text_scale,
+        );
+    }
+
+    canvas
+}
+
+/// Creates an SVG with the simulation area as an embedded PNG and the annotations as vectors.
+/// * `png` - The simulation area with one pixel per cell, encoded as PNG.
+/// * `size` - The width and height of the simulation area in the SVG.
+/// * `color_scale` - The values at both ends of the color scale and their unit.
+pub fn annotated_svg(
+    png: &[u8],
+    size: u32,
+    annotations: Annotations,
+    overlays: &Overlays,
+    gradient: &Gradient,
+    color_scale: (f32, f32, &str),
+    delta_l: f32,
+    time: f32,
+) -> String {
+    let layout = Layout::new(size, annotations.color_bar);
+    let font_size = (GLYPH_HEIGHT + 3) * layout.text_scale;
+    let width = layout.width();
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{width}" height="{size}" viewBox="0 0 {width} {size}" font-family="sans-serif" font-size="{font_size}">"#
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="{width}" height="{size}" fill="#000000"/>"##
+    );
+    let _ = writeln!(
+        svg,
+        r#"<image width="{size}" height="{size}" preserveAspectRatio="none" style="image-rendering:pixelated" xlink:href="data:image/png;base64,{}"/>"#,
+        base64(png)
+    );
+
+    if annotations.outlines {
+        overlays.write_svg(
+            &mut svg,
+            size as f32 / SIMULATION_WIDTH as f32,
+            layout.text_scale as f32,
+        );
+    }
+
+    if annotations.color_bar {
+        let (min, max, unit) = color_scale;
+        let [x, y, bar_width, bar_height] = layout.color_bar();
+        let _ = writeln!(
+            svg,
+            r#"<defs><linearGradient id="color_bar" x1="0" y1="1" x2="0" y2="0">"#
+        );
+        const STOPS: usize = 16;
+        for stop in 0..=STOPS {
+            let t = stop as f32 / STOPS as f32;
+            let color = gradient
+                .at(min + t * (max - min), min, max)
+                .map(|c| ((c as f32 / 255.).powf(1. / 2.2) * 255.) as u8);
+            let _ = writeln!(svg, r#"<stop offset="{t}" stop-color="{}"/>"#, hex(color));
+        }
+        let _ = writeln!(svg, "</linearGradient></defs>");
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{x}" y="{y}" width="{bar_width}" height="{bar_height}" fill="url(#color_bar)" stroke="#ffffff"/>"##
+        );
+        let text_x = x + bar_width + layout.margin;
+        let _ = writeln!(
+            svg,
+            r##"<text x="{text_x}" y="{y}" dominant-baseline="hanging" fill="#ffffff">{max:.1} {unit}</text>"##
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{text_x}" y="{}" fill="#ffffff">{min:.1} {unit}</text>"##,
+            y + bar_height
+        );
+    }
+
+    if annotations.scale_bar {
+        let (length, label) = layout.scale_bar(delta_l);
+        let bar_height = 2 * layout.text_scale;
+        let x = layout.margin;
+        let y = size - layout.margin - bar_height;
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{x}" y="{y}" width="{length}" height="{bar_height}" fill="#ffffff" stroke="#000000"/>"##
+        );
+        let _ = writeln!(
+            svg,
+            r##"<text x="{x}" y="{}" fill="#ffffff" stroke="#000000" stroke-width="{}" paint-order="stroke">{label}</text>"##,
+            y - layout.text_scale,
+            layout.text_scale
+        );
+    }
+
+    if annotations.time {
+        let _ = writeln!(
+            svg,
+            r##"<text x="{0}" y="{0}" dominant-baseline="hanging" fill="#ffffff" stroke="#000000" stroke-width="{1}" paint-order="stroke">{2}</text>"##,
+            layout.margin,
+            layout.text_scale,
+            format_time(time)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Formats a color as a hexadecimal SVG color.
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Encodes bytes as standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = chunk.iter().enumerate().fold(0u32, |value, (i, byte)| {
+            value | ((*byte as u32) << (16 - 8 * i))
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((value >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -11,8 +11,48 @@ use crate::components::states::Move;
 use crate::components::wall::{CircWall, RectWall, WResize, Wall};
 use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
 use crate::math::transformations::{coords_to_index, map_range};
+use crate::simulation::field_map::FieldMap;
 use crate::simulation::grid::Grid;
-use crate::ui::state::{FftMicrophone, FftScaling, UiState};
+use crate::simulation::steady_state::SteadyStateMap;
+use crate::ui::state::{FftMicrophone, FftScaling, RenderMode, UiState, VectorOverlay};
+
+/// Returns the color of a cell that is not a wall in the current render mode
+pub fn cell_color(
+    index: usize,
+    grid: &Grid,
+    gradient: &Gradient,
+    ui_state: &UiState,
+    field_map: &FieldMap,
+    steady_state: &SteadyStateMap,
+) -> [u8; 3] {
+    match ui_state.render_mode {
+        RenderMode::Pressure => gradient.at(
+            grid.pressure[index],
+            ui_state.min_gradient,
+            ui_state.max_gradient,
+        ),
+        RenderMode::Magnitude => gradient.at(
+            steady_state
+                .selected_bin()
+                .map_or(f32::NEG_INFINITY, |bin| bin.rms_level(index))
+                + ui_state.pressure_units.level_offset(),
+            ui_state.min_level,
+            ui_state.max_level,
+        ),
+        RenderMode::Phase => gradient.at(
+            steady_state
+                .selected_bin()
+                .map_or(0., |bin| bin.phase(index)),
+            -180.,
+            180.,
+        ),
+        mode => gradient.at(
+            field_map.level(index, mode) + ui_state.pressure_units.level_offset(),
+            ui_state.min_level,
+            ui_state.max_level,
+        ),
+    }
+}
 
 pub fn draw_pixels(
     pixel_buffers: QueryPixelBuffer,
@@ -21,6 +61,8 @@ pub fn draw_pixels(
     ui_state: Res<UiState>,
     fft_microphone: Res<FftMicrophone>,
     microphones: Query<&Microphone>,
+    field_map: Res<FieldMap>,
+    steady_state: Res<SteadyStateMap>,
 ) {
     let (query, mut images) = pixel_buffers.split();
     let mut items = query.iter();
@@ -59,9 +101,14 @@ pub fn draw_pixels(
             };
         }
 
-        let p = grid.pressure[current_index];
-
-        let [r, g, b] = gradient.at(p, ui_state.min_gradient, ui_state.max_gradient);
+        let [r, g, b] = cell_color(
+            current_index,
+            &grid,
+            &gradient,
+            &ui_state,
+            &field_map,
+            &steady_state,
+        );
 
         Pixel { r, g, b, a: 255 }
     });
@@ -84,7 +131,7 @@ pub fn draw_pixels(
             let new_spectrum = crate::math::fft::calc_mic_spectrum(
                 mic,
                 FftScaling::Normalized,
-                grid.delta_t,
+                ui_state.recording_policy.sample_interval(grid.delta_t),
                 ui_state.fft_window_size,
             );
 
@@ -130,12 +177,19 @@ pub fn draw_overlays(
     pixel_buffers: QueryPixelBuffer,
     rect_walls_overlay: RectWallsResizeOrMove,
     circ_walls_overlay: CircWallsResizeOrMove,
+    grid: Res<Grid>,
+    ui_state: Res<UiState>,
+    field_map: Res<FieldMap>,
 ) {
     let (query, mut images) = pixel_buffers.split();
     let mut frame = images.frame(query.iter().next().expect("one pixel buffer"));
 
     let raw_pixles = frame.raw_mut();
 
+    if ui_state.vector_overlay != VectorOverlay::None && !ui_state.render_abc_area {
+        draw_vector_field(raw_pixles, &grid, &ui_state, &field_map);
+    }
+
     for wall in rect_walls_overlay.iter() {
         for x in wall.rect.min.x..=wall.rect.max.x {
             for y in wall.rect.min.y..=wall.rect.max.y {
@@ -281,3 +335,86 @@ pub fn draw_overlays(
         }
     }
 }
+
+/// Draws arrows of the particle velocity or the active intensity on a coarse grid.
+/// The arrows are scaled relative to the longest arrow.
+fn draw_vector_field(
+    raw_pixels: &mut [Pixel],
+    grid: &Grid,
+    ui_state: &UiState,
+    field_map: &FieldMap,
+) {
+    let spacing = ui_state.vector_spacing.max(4);
+
+    let mut vectors = vec![];
+    for y in (spacing / 2..SIMULATION_HEIGHT).step_by(spacing as usize) {
+        for x in (spacing / 2..SIMULATION_WIDTH).step_by(spacing as usize) {
+            let index = coords_to_index(
+                x + ui_state.boundary_width,
+                y + ui_state.boundary_width,
+                ui_state.boundary_width,
+            );
+            if index >= grid.wall_cache.len() || grid.wall_cache[index].is_wall {
+                continue;
+            }
+            let vector = match ui_state.vector_overlay {
+                VectorOverlay::Velocity => grid.velocity(index),
+                VectorOverlay::Intensity => match field_map.intensity.get(index) {
+                    Some(intensity) => *intensity,
+                    None => continue,
+                },
+                VectorOverlay::None => return,
+            };
+            vectors.push((x, y, vector));
+        }
+    }
+
+    let longest = vectors
+        .iter()
+        .map(|(_, _, [v_x, v_y])| v_x.hypot(*v_y))
+        .fold(0., f32::max);
+    if longest <= f32::EPSILON {
+        return;
+    }
+
+    let white = Pixel {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 255,
+    };
+    let max_length = spacing as f32 * 0.9;
+
+    for (x, y, [v_x, v_y]) in vectors {
+        let length = v_x.hypot(v_y) / longest * max_length;
+        // very short arrows would only be a dot
+        if length < 2. {
+            continue;
+        }
+        let direction = Vec2::new(v_x, v_y).normalize();
+        let tail = Vec2::new(x as f32, y as f32) - direction * length / 2.;
+        let head = tail + direction * length;
+        let head_size = (length / 3.).min(4.);
+
+        draw_line(raw_pixels, tail, head, white);
+        for side in [-1., 1.] {
+            let wing = head - direction * head_size + direction.perp() * side * head_size / 2.;
+            draw_line(raw_pixels, wing, head, white);
+        }
+    }
+}
+
+/// Draws a line between two points in grid coordinates, pixels outside of the simulation are skipped.
+fn draw_line(raw_pixels: &mut [Pixel], from: Vec2, to: Vec2, pixel: Pixel) {
+    let steps = (to - from).abs().max_element().ceil().max(1.) as u32;
+    for step in 0..=steps {
+        let point = from.lerp(to, step as f32 / steps as f32).round();
+        if point.x >= 0.
+            && point.y >= 0.
+            && (point.x as u32) < SIMULATION_WIDTH
+            && (point.y as u32) < SIMULATION_HEIGHT
+        {
+            raw_pixels[(point.x as u32 + point.y as u32 * SIMULATION_WIDTH) as usize] = pixel;
+        }
+    }
+}
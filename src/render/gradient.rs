@@ -29,6 +29,49 @@ impl Gradient {
 
         map[idx]
     }
+
+    /// Draws a vertical color bar of the gradient with `min` at the bottom and `max` at the top.
+    /// The values of both ends are written next to the bar.
+    pub fn draw_color_bar(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        min: f32,
+        max: f32,
+        unit: &str,
+    ) {
+        const STEPS: usize = 64;
+        let step_height = rect.height() / STEPS as f32;
+
+        for step in 0..STEPS {
+            let value = map_range(0., STEPS as f32 - 1., max, min, step as f32);
+            let [r, g, b] = self.at(value, min, max);
+            // inverse gamma correction to match the brightness/contrast of the simulation
+            let [r, g, b] = [r, g, b].map(|c| ((c as f32 / 255.).powf(1. / 2.2) * 255.) as u8);
+            painter.rect_filled(
+                egui::Rect::from_min_size(
+                    egui::pos2(rect.min.x, rect.min.y + step as f32 * step_height),
+                    egui::vec2(rect.width(), step_height + 0.5),
+                ),
+                0.,
+                egui::Color32::from_rgb(r, g, b),
+            );
+        }
+        painter.rect_stroke(rect, 0., egui::Stroke::new(1., egui::Color32::WHITE));
+
+        for (value, pos, align) in [
+            (max, rect.left_top(), egui::Align2::RIGHT_TOP),
+            (min, rect.left_bottom(), egui::Align2::RIGHT_BOTTOM),
+        ] {
+            painter.text(
+                pos - egui::vec2(5., 0.),
+                align,
+                format!("{value:.1} {unit}"),
+                egui::FontId::monospace(12.),
+                egui::Color32::WHITE,
+            );
+        }
+    }
 }
 
 // https://gist.github.com/mikhailov-work/6a308c20e494d9e0ccc29036b28faa7a
@@ -1,4 +1,7 @@
+pub mod animation;
+pub mod annotations;
 pub mod draw;
 pub mod gradient;
 pub mod plugin;
+pub mod raster;
 pub mod screenshot;
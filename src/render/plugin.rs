@@ -2,9 +2,11 @@ use bevy::prelude::*;
 use bevy_pixel_buffer::builder::PixelBufferBuilder;
 use bevy_pixel_buffer::pixel_buffer::PixelBufferSize;
 
+use super::animation::{animation_system, AnimationRecorder};
 use super::draw::{draw_overlays, draw_pixels};
 use super::gradient::Gradient;
 use crate::math::constants::*;
+use crate::simulation::systems::steady_state_system;
 use crate::ui::state::SimTime;
 
 pub struct RenderPlugin;
@@ -14,8 +16,11 @@ impl Plugin for RenderPlugin {
         app.init_resource::<Gradient>()
             .init_resource::<SimTime>()
             .init_resource::<Gradient>()
+            .init_resource::<AnimationRecorder>()
             .add_systems(Startup, (setup_buffers,))
-            .add_systems(Update, (draw_pixels, draw_overlays).chain());
+            .add_systems(Update, (draw_pixels, draw_overlays).chain())
+            // frames are captured after all simulation systems of a step
+            .add_systems(FixedUpdate, animation_system.after(steady_state_system));
     }
 }
 
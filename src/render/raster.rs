@@ -0,0 +1,289 @@
+use image::{Rgb, RgbImage};
+
+use super::gradient::Gradient;
+use crate::math::transformations::map_range;
+
+/// Width of a glyph of the built-in font in pixels
+pub const GLYPH_WIDTH: u32 = 5;
+/// Height of a glyph of the built-in font in pixels
+pub const GLYPH_HEIGHT: u32 = 7;
+
+/// Returns the rows of a glyph of the built-in 5×7 font, most significant bit on the left.
+/// Characters without a glyph are drawn as blanks.
+#[rustfmt::skip]
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        '.' => [0, 0, 0, 0, 0, 0b01100, 0b01100],
+        ',' => [0, 0, 0, 0, 0b01100, 0b00100, 0b01000],
+        '-' => [0, 0, 0, 0b11111, 0, 0, 0],
+        '+' => [0, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0],
+        ':' => [0, 0b01100, 0b01100, 0, 0b01100, 0b01100, 0],
+        '=' => [0, 0, 0b11111, 0, 0b11111, 0, 0],
+        '/' => [0, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '%' => [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+        '°' => [0b01100, 0b10010, 0b10010, 0b01100, 0, 0, 0],
+        '×' => [0, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        'a' => [0, 0, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111],
+        'b' => [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110],
+        'c' => [0, 0, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110],
+        'd' => [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111],
+        'e' => [0, 0, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110],
+        'f' => [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000],
+        'g' => [0, 0b01111, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110],
+        'h' => [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001],
+        'i' => [0b00100, 0, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'j' => [0b00010, 0, 0b00110, 0b00010, 0b00010, 0b10010, 0b01100],
+        'k' => [0b10000, 0b10000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010],
+        'l' => [0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'm' => [0, 0, 0b11010, 0b10101, 0b10101, 0b10001, 0b10001],
+        'n' => [0, 0, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001],
+        'o' => [0, 0, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110],
+        'p' => [0, 0, 0b11110, 0b10001, 0b11110, 0b10000, 0b10000],
+        'q' => [0, 0, 0b01101, 0b10011, 0b01111, 0b00001, 0b00001],
+        'r' => [0, 0, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000],
+        's' => [0, 0, 0b01110, 0b10000, 0b01110, 0b00001, 0b11110],
+        't' => [0b01000, 0b01000, 0b11100, 0b01000, 0b01000, 0b01001, 0b00110],
+        'u' => [0, 0, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101],
+        'v' => [0, 0, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'w' => [0, 0, 0b10001, 0b10001, 0b10101, 0b10101, 0b01010],
+        'x' => [0, 0, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001],
+        'y' => [0, 0, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110],
+        'z' => [0, 0, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111],
+        'μ' => [0, 0, 0b10001, 0b10001, 0b10011, 0b11101, 0b10000],
+        _ => [0; 7],
+    }
+}
+
+/// Sets a pixel if it is inside of the image.
+fn put_pixel(image: &mut RgbImage, x: i32, y: i32, color: Rgb<u8>) {
+    if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+/// Fills a rectangle, clipped to the image.
+pub fn fill_rect(image: &mut RgbImage, x: i32, y: i32, width: u32, height: u32, color: Rgb<u8>) {
+    for py in y..y + height as i32 {
+        for px in x..x + width as i32 {
+            put_pixel(image, px, py, color);
+        }
+    }
+}
+
+/// Draws the outline of a rectangle with the given line width inside of the rectangle.
+pub fn stroke_rect(
+    image: &mut RgbImage,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    line_width: u32,
+    color: Rgb<u8>,
+) {
+    let line_width = line_width.min(width).min(height);
+    fill_rect(image, x, y, width, line_width, color);
+    fill_rect(
+        image,
+        x,
+        y + (height - line_width) as i32,
+        width,
+        line_width,
+        color,
+    );
+    fill_rect(image, x, y, line_width, height, color);
+    fill_rect(
+        image,
+        x + (width - line_width) as i32,
+        y,
+        line_width,
+        height,
+        color,
+    );
+}
+
+/// Draws a line with square ends.
+pub fn draw_line(
+    image: &mut RgbImage,
+    start: [f32; 2],
+    end: [f32; 2],
+    line_width: u32,
+    color: Rgb<u8>,
+) {
+    let steps = (end[0] - start[0])
+        .abs()
+        .max((end[1] - start[1]).abs())
+        .ceil() as u32;
+    let offset = line_width as f32 / 2.;
+    for step in 0..=steps {
+        let t = if steps == 0 {
+            0.
+        } else {
+            step as f32 / steps as f32
+        };
+        let x = start[0] + (end[0] - start[0]) * t - offset;
+        let y = start[1] + (end[1] - start[1]) * t - offset;
+        fill_rect(
+            image,
+            x.round() as i32,
+            y.round() as i32,
+            line_width,
+            line_width,
+            color,
+        );
+    }
+}
+
+/// Draws a circle, filled or as a ring with the given line width.
+pub fn draw_circle(
+    image: &mut RgbImage,
+    center: [f32; 2],
+    radius: f32,
+    line_width: Option<f32>,
+    color: Rgb<u8>,
+) {
+    let inner = line_width.map_or(0., |width| (radius - width).max(0.));
+    for y in (center[1] - radius).floor() as i32..=(center[1] + radius).ceil() as i32 {
+        for x in (center[0] - radius).floor() as i32..=(center[0] + radius).ceil() as i32 {
+            let distance = ((x as f32 + 0.5 - center[0]).powi(2)
+                + (y as f32 + 0.5 - center[1]).powi(2))
+            .sqrt();
+            if distance <= radius && distance >= inner {
+                put_pixel(image, x, y, color);
+            }
+        }
+    }
+}
+
+/// Width of a text in pixels when drawn with [`draw_text`].
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    let chars = text.chars().count() as u32;
+    (chars * (GLYPH_WIDTH + 1)).saturating_sub(1) * scale
+}
+
+/// Draws a text with the built-in font. `x` and `y` are the top left corner of the text.
+/// * `scale` - The size of a font pixel in image pixels.
+pub fn draw_text(image: &mut RgbImage, x: i32, y: i32, text: &str, scale: u32, color: Rgb<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let left = x + (i as u32 * (GLYPH_WIDTH + 1) * scale) as i32;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                    fill_rect(
+                        image,
+                        left + (column * scale) as i32,
+                        y + (row as u32 * scale) as i32,
+                        scale,
+                        scale,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Draws a text on a dark box so it is readable on every gradient.
+pub fn draw_label(image: &mut RgbImage, x: i32, y: i32, text: &str, scale: u32) {
+    let padding = 2 * scale;
+    fill_rect(
+        image,
+        x,
+        y,
+        text_width(text, scale) + 2 * padding,
+        GLYPH_HEIGHT * scale + 2 * padding,
+        Rgb([0, 0, 0]),
+    );
+    draw_text(
+        image,
+        x + padding as i32,
+        y + padding as i32,
+        text,
+        scale,
+        Rgb([255, 255, 255]),
+    );
+}
+
+/// Draws a vertical color bar of the gradient with `min` at the bottom and `max` at the top,
+/// like [`Gradient::draw_color_bar`]. The values of both ends are written to the right of the bar.
+pub fn draw_color_bar(
+    image: &mut RgbImage,
+    gradient: &Gradient,
+    [x, y, width, height]: [u32; 4],
+    min: f32,
+    max: f32,
+    unit: &str,
+    scale: u32,
+) {
+    for row in 0..height {
+        let value = map_range(0., (height - 1).max(1) as f32, max, min, row as f32);
+        let color = gradient
+            .at(value, min, max)
+            .map(|c| ((c as f32 / 255.).powf(1. / 2.2) * 255.) as u8);
+        fill_rect(image, x as i32, (y + row) as i32, width, 1, Rgb(color));
+    }
+    stroke_rect(
+        image,
+        x as i32,
+        y as i32,
+        width,
+        height,
+        1,
+        Rgb([255, 255, 255]),
+    );
+
+    let text_x = (x + width + 3 * scale) as i32;
+    draw_text(
+        image,
+        text_x,
+        y as i32,
+        &format!("{max:.1} {unit}"),
+        scale,
+        Rgb([255, 255, 255]),
+    );
+    draw_text(
+        image,
+        text_x,
+        (y + height - GLYPH_HEIGHT * scale) as i32,
+        &format!("{min:.1} {unit}"),
+        scale,
+        Rgb([255, 255, 255]),
+    );
+}
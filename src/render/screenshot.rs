@@ -1,19 +1,28 @@
 use bevy::ecs::system::Commands;
 use bevy_file_dialog::FileDialogExt;
+use image::imageops::{self, FilterType};
+use image::RgbImage;
 
+use super::annotations::{annotate_image, annotated_svg, Annotations, Overlays};
+use super::draw::cell_color;
 use super::gradient::Gradient;
 use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
 use crate::math::transformations::coords_to_index;
+use crate::simulation::field_map::FieldMap;
 use crate::simulation::grid::Grid;
+use crate::simulation::steady_state::SteadyStateMap;
 use crate::ui::loading::SaveFileContents;
-use crate::ui::state::UiState;
+use crate::ui::state::{ScreenshotFormat, ScreenshotSettings, UiState};
 
-pub fn screenshot_grid(
+/// Renders the simulation area in the current render mode like it is shown in the app,
+/// one pixel per cell.
+pub fn grid_image(
     ui_state: &UiState,
     grid: &Grid,
     gradient: &Gradient,
-    commands: &mut Commands,
-) {
+    field_map: &FieldMap,
+    steady_state: &SteadyStateMap,
+) -> RgbImage {
     let mut pixels: Vec<u8> = Vec::new();
 
     for y in ui_state.boundary_width..(SIMULATION_WIDTH + ui_state.boundary_width) {
@@ -28,9 +37,14 @@ pub fn screenshot_grid(
                 pixels.push((reflection_factor * 255.) as u8);
                 pixels.push((reflection_factor * 255.) as u8);
             } else {
-                let pressure = grid.pressure[current_index];
-
-                let [r, g, b] = gradient.at(pressure, ui_state.min_gradient, ui_state.max_gradient);
+                let [r, g, b] = cell_color(
+                    current_index,
+                    grid,
+                    gradient,
+                    ui_state,
+                    field_map,
+                    steady_state,
+                );
 
                 // inverse gamma correction to match the brightness/contrast of the simulation
                 pixels.push(((r as f32 / 255.).powf(1. / 2.2) * 255.) as u8);
@@ -40,20 +54,111 @@ pub fn screenshot_grid(
         }
     }
 
+    RgbImage::from_raw(SIMULATION_WIDTH, SIMULATION_HEIGHT, pixels).expect("could not create image")
+}
+
+/// Exports the simulation area with the annotations of the screenshot settings as PNG or SVG.
+/// * `time` - The simulation time in seconds.
+pub fn export_screenshot(
+    settings: &ScreenshotSettings,
+    ui_state: &UiState,
+    grid: &Grid,
+    gradient: &Gradient,
+    field_map: &FieldMap,
+    steady_state: &SteadyStateMap,
+    overlays: &Overlays,
+    time: f32,
+    commands: &mut Commands,
+) {
+    let image = grid_image(ui_state, grid, gradient, field_map, steady_state);
+    let size = SIMULATION_WIDTH * settings.scale.max(1);
+    let annotations = Annotations {
+        outlines: settings.outlines,
+        color_bar: settings.color_bar,
+        scale_bar: settings.scale_bar,
+        time: settings.time,
+    };
+
+    let (data, extension) = match settings.format {
+        ScreenshotFormat::Png => {
+            let image = imageops::resize(&image, size, size, FilterType::Nearest);
+            let image = annotate_image(
+                &image,
+                annotations,
+                overlays,
+                gradient,
+                ui_state.color_scale(),
+                ui_state.delta_l,
+                time,
+            );
+            (encode_png(&image), "png")
+        }
+        ScreenshotFormat::Svg => {
+            let svg = annotated_svg(
+                &encode_png(&image),
+                size,
+                annotations,
+                overlays,
+                gradient,
+                ui_state.color_scale(),
+                ui_state.delta_l,
+                time,
+            );
+            (svg.into_bytes(), "svg")
+        }
+    };
+
+    commands
+        .dialog()
+        .add_filter(settings.format.to_string(), &[extension])
+        .set_file_name(format!("screenshot.{extension}"))
+        .set_directory("./")
+        .set_title("Select a file to save to")
+        .save_file::<SaveFileContents>(data);
+}
+
+fn encode_png(image: &RgbImage) -> Vec<u8> {
     let mut data = Vec::new();
     let encoder = image::codecs::png::PngEncoder::new(&mut data);
 
-    let image = image::RgbImage::from_raw(SIMULATION_WIDTH, SIMULATION_HEIGHT, pixels)
-        .expect("could not create image");
-
     image
         .write_with_encoder(encoder)
         .expect("could not write image");
+    data
+}
+
+pub fn export_field_map(ui_state: &UiState, field_map: &FieldMap, commands: &mut Commands) {
+    let data = field_map.to_csv(
+        ui_state.render_mode,
+        ui_state.boundary_width,
+        ui_state.delta_l,
+        ui_state.pressure_units,
+    );
+
+    commands
+        .dialog()
+        .add_filter("CSV", &["csv"])
+        .set_file_name("field_map.csv")
+        .set_directory("./")
+        .set_title("Select a file to save to")
+        .save_file::<SaveFileContents>(data);
+}
+
+pub fn export_steady_state(
+    ui_state: &UiState,
+    steady_state: &SteadyStateMap,
+    commands: &mut Commands,
+) {
+    let data = steady_state.to_csv(
+        ui_state.boundary_width,
+        ui_state.delta_l,
+        ui_state.pressure_units,
+    );
 
     commands
         .dialog()
-        .add_filter("PNG", &["png"])
-        .set_file_name("screenshot.png")
+        .add_filter("CSV", &["csv"])
+        .set_file_name("steady_state.csv")
         .set_directory("./")
         .set_title("Select a file to save to")
         .save_file::<SaveFileContents>(data);
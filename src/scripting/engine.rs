@@ -0,0 +1,404 @@
+//! Bindings of the simulation for [Rhai](https://rhai.rs) scripts.
+//!
+//! Scripts build scenes and drive a [`HeadlessSimulation`] with these functions:
+//!
+//! | Function | Description |
+//! | --- | --- |
+//! | `clear()` | removes all sources, microphones and walls and resets the simulation |
+//! | `reset()` | resets the pressure field, the time and the records |
+//! | `add_source(x, y)` | adds a 1 kHz sine source, returns its id |
+//! | `add_source(x, y, #{ type: "sin", frequency: 500.0 })` | adds a source, see below |
+//! | `add_mic(x, y)` | adds a microphone, returns its id |
+//! | `add_rect_wall(x0, y0, x1, y1)` | adds a solid, fully reflecting rectangular wall |
+//! | `add_rect_wall(x0, y0, x1, y1, hollow, reflection_factor)` | adds a rectangular wall |
+//! | `add_circ_wall(x, y, radius)` | adds a solid, fully reflecting circular wall |
+//! | `add_circ_wall(x, y, radius, hollow, reflection_factor)` | adds a circular wall |
+//! | `set(name, value)`, `get(name)` | changes or reads a setting, see below |
+//! | `step()`, `step(n)` | advances the simulation by one or `n` time steps |
+//! | `time()`, `delta_t()` | the simulation time and the time step in seconds |
+//! | `mic_record(id)`, `mic_times(id)` | the recorded pressure values and their times |
+//! | `pressure(x, y)` | the current pressure at a position |
+//! | `start_field_recording(path)` | records the pressure of the simulation area to a `.npy` file |
+//! | `start_field_recording(path, x0, y0, x1, y1, spatial_decimation, time_decimation)` | records every n-th cell of a region every n-th step |
+//! | `stop_field_recording()` | finishes the recording and writes its metadata to a `.json` file |
+//!
+//! Positions are given in cells of the simulation area.
+//! The `type` of a source is one of `"sin"`, `"gauss"`, `"noise"`, `"impulse"` and `"sweep"`,
+//! the other properties are the parameters of the source type (`frequency`, `amplitude`, `phase`,
+//! `std_dev`, `start_frequency`, `end_frequency`, `duration`), missing ones keep their defaults.
+//! The settings are `delta_l`, `boundary_width`, `framerate`, `is_recording`,
+//! `reset_on_change`, `min_gradient` and `max_gradient`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use bevy::math::UVec2;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Position, FLOAT, INT};
+
+use crate::components::source::SourceType;
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::simulation::headless::HeadlessSimulation;
+use crate::simulation::validation::{self, InputError};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Amount of time steps `step(n)` runs before it checks whether the script was cancelled.
+const STEPS_PER_CANCEL_CHECK: usize = 64;
+
+/// Runs a script on the given simulation. Everything the script prints is passed to `output`.
+/// Returns the simulation with all changes made by the script,
+/// even if the script stopped with an error.
+///
+/// Setting `cancel` stops the script at its next operation or within a few time steps of `step(n)`.
+/// Scripts cannot catch this, the result is a "Script terminated" error.
+pub fn run_script(
+    script: &str,
+    simulation: HeadlessSimulation,
+    output: impl Fn(&str) + 'static,
+    cancel: Arc<AtomicBool>,
+) -> (HeadlessSimulation, Result<(), String>) {
+    let simulation = Rc::new(RefCell::new(simulation));
+    let output = Rc::new(output);
+
+    let result = {
+        let engine = create_engine(&simulation, &output, &cancel);
+        engine.run(script).map_err(|err| err.to_string())
+    };
+
+    // the engine and its functions are dropped, so the simulation is not shared anymore
+    let simulation = Rc::try_unwrap(simulation)
+        .ok()
+        .expect("the engine is dropped")
+        .into_inner();
+    (simulation, result)
+}
+
+fn create_engine(
+    simulation: &Rc<RefCell<HeadlessSimulation>>,
+    output: &Rc<impl Fn(&str) + 'static>,
+    cancel: &Arc<AtomicBool>,
+) -> Engine {
+    let mut engine = Engine::new();
+
+    let cancelled = cancel.clone();
+    engine.on_progress(move |_| cancelled.load(Ordering::Relaxed).then_some(Dynamic::UNIT));
+
+    let print = output.clone();
+    engine.on_print(move |text| print(text));
+    let debug = output.clone();
+    engine.on_debug(move |text, _, position| debug(&format!("{position}: {text}")));
+
+    let sim = simulation.clone();
+    engine.register_fn("clear", move || sim.borrow_mut().clear());
+    let sim = simulation.clone();
+    engine.register_fn("reset", move || sim.borrow_mut().reset());
+
+    let sim = simulation.clone();
+    engine.register_fn("add_source", move |x: INT, y: INT| -> ScriptResult<INT> {
+        let position = position(x, y)?;
+        Ok(sim
+            .borrow_mut()
+            .add_source(position.x, position.y, SourceType::default_sin()) as INT)
+    });
+    let sim = simulation.clone();
+    engine.register_fn(
+        "add_source",
+        move |x: INT, y: INT, properties: Map| -> ScriptResult<INT> {
+            let position = position(x, y)?;
+            let source_type = source_type(&properties)?;
+            Ok(sim
+                .borrow_mut()
+                .add_source(position.x, position.y, source_type) as INT)
+        },
+    );
+
+    let sim = simulation.clone();
+    engine.register_fn("add_mic", move |x: INT, y: INT| -> ScriptResult<INT> {
+        let position = position(x, y)?;
+        Ok(sim.borrow_mut().add_microphone(position.x, position.y) as INT)
+    });
+
+    let sim = simulation.clone();
+    engine.register_fn(
+        "add_rect_wall",
+        move |x0: INT, y0: INT, x1: INT, y1: INT| -> ScriptResult<INT> {
+            let (min, max) = corners(x0, y0, x1, y1)?;
+            Ok(sim.borrow_mut().add_rect_wall(min, max, false, 1.) as INT)
+        },
+    );
+    let sim = simulation.clone();
+    engine.register_fn(
+        "add_rect_wall",
+        move |x0: INT,
+              y0: INT,
+              x1: INT,
+              y1: INT,
+              hollow: bool,
+              reflection_factor: FLOAT|
+              -> ScriptResult<INT> {
+            let (min, max) = corners(x0, y0, x1, y1)?;
+            let reflection_factor = reflection(reflection_factor)?;
+            Ok(sim
+                .borrow_mut()
+                .add_rect_wall(min, max, hollow, reflection_factor) as INT)
+        },
+    );
+
+    let sim = simulation.clone();
+    engine.register_fn(
+        "add_circ_wall",
+        move |x: INT, y: INT, radius: INT| -> ScriptResult<INT> {
+            let center = position(x, y)?;
+            let radius = radius_of(radius)?;
+            Ok(sim.borrow_mut().add_circ_wall(center, radius, false, 1.) as INT)
+        },
+    );
+    let sim = simulation.clone();
+    engine.register_fn(
+        "add_circ_wall",
+        move |x: INT,
+              y: INT,
+              radius: INT,
+              hollow: bool,
+              reflection_factor: FLOAT|
+              -> ScriptResult<INT> {
+            let center = position(x, y)?;
+            let radius = radius_of(radius)?;
+            let reflection_factor = reflection(reflection_factor)?;
+            Ok(sim
+                .borrow_mut()
+                .add_circ_wall(center, radius, hollow, reflection_factor) as INT)
+        },
+    );
+
+    let sim = simulation.clone();
+    engine.register_fn(
+        "set",
+        move |name: &str, value: Dynamic| -> ScriptResult<()> {
+            set_setting(&mut sim.borrow_mut(), name, value)
+        },
+    );
+    let sim = simulation.clone();
+    engine.register_fn("get", move |name: &str| -> ScriptResult<Dynamic> {
+        get_setting(&sim.borrow(), name)
+    });
+
+    let sim = simulation.clone();
+    engine.register_fn("step", move || sim.borrow_mut().step(1));
+    let sim = simulation.clone();
+    let cancelled = cancel.clone();
+    engine.register_fn("step", move |steps: INT| -> ScriptResult<()> {
+        if steps < 0 {
+            return Err(format!("Cannot step backwards ({steps} steps)").into());
+        }
+        // a single call can run for minutes, so it stops like the engine when cancelled
+        let mut remaining = steps as usize;
+        loop {
+            if cancelled.load(Ordering::Relaxed) {
+                return Err(EvalAltResult::ErrorTerminated(Dynamic::UNIT, Position::NONE).into());
+            }
+            let chunk = remaining.min(STEPS_PER_CANCEL_CHECK);
+            sim.borrow_mut().step(chunk);
+            remaining -= chunk;
+            if remaining == 0 {
+                return Ok(());
+            }
+        }
+    });
+
+    let sim = simulation.clone();
+    engine.register_fn("time", move || sim.borrow().time() as FLOAT);
+    let sim = simulation.clone();
+    engine.register_fn("delta_t", move || sim.borrow().delta_t() as FLOAT);
+
+    let sim = simulation.clone();
+    engine.register_fn("mic_record", move |id: INT| -> ScriptResult<Array> {
+        let record = mic_record(&mut sim.borrow_mut(), id)?;
+        Ok(record
+            .iter()
+            .map(|&[_, value]| Dynamic::from_float(value))
+            .collect())
+    });
+    let sim = simulation.clone();
+    engine.register_fn("mic_times", move |id: INT| -> ScriptResult<Array> {
+        let record = mic_record(&mut sim.borrow_mut(), id)?;
+        Ok(record
+            .iter()
+            .map(|&[time, _]| Dynamic::from_float(time))
+            .collect())
+    });
+
+    let sim = simulation.clone();
+    engine.register_fn("pressure", move |x: INT, y: INT| -> ScriptResult<FLOAT> {
+        let position = position(x, y)?;
+        let pressure = sim.borrow().pressure(position.x, position.y);
+        Ok(pressure.expect("the position is inside the simulation area") as FLOAT)
+    });
+
+    let sim = simulation.clone();
+    engine.register_fn(
+        "start_field_recording",
+        move |path: &str| -> ScriptResult<()> {
+            sim.borrow_mut()
+                .start_field_recording(
+                    path,
+                    UVec2::ZERO,
+                    UVec2::new(SIMULATION_WIDTH - 1, SIMULATION_HEIGHT - 1),
+                    1,
+                    1,
+                )
+                .map_err(|err| format!("Could not record to {path}: {err}").into())
+        },
+    );
+    let sim = simulation.clone();
+    engine.register_fn(
+        "start_field_recording",
+        move |path: &str,
+              x0: INT,
+              y0: INT,
+              x1: INT,
+              y1: INT,
+              spatial_decimation: INT,
+              time_decimation: INT|
+              -> ScriptResult<()> {
+            let (min, max) = corners(x0, y0, x1, y1)?;
+            let spatial_decimation = decimation(spatial_decimation)?;
+            let time_decimation = decimation(time_decimation)?;
+            sim.borrow_mut()
+                .start_field_recording(path, min, max, spatial_decimation, time_decimation)
+                .map_err(|err| format!("Could not record to {path}: {err}").into())
+        },
+    );
+    let sim = simulation.clone();
+    engine.register_fn("stop_field_recording", move || -> ScriptResult<()> {
+        sim.borrow_mut()
+            .stop_field_recording()
+            .map_err(|err| format!("Could not finish the field recording: {err}").into())
+    });
+
+    engine
+}
+
+/// Converts a rejected input into an error of the script.
+fn input_error(err: InputError) -> Box<EvalAltResult> {
+    err.to_string().into()
+}
+
+fn position(x: INT, y: INT) -> ScriptResult<UVec2> {
+    validation::position(x, y).map_err(input_error)
+}
+
+fn corners(x0: INT, y0: INT, x1: INT, y1: INT) -> ScriptResult<(UVec2, UVec2)> {
+    validation::corners(x0, y0, x1, y1).map_err(input_error)
+}
+
+fn radius_of(radius: INT) -> ScriptResult<u32> {
+    validation::radius(radius).map_err(input_error)
+}
+
+fn decimation(decimation: INT) -> ScriptResult<u32> {
+    validation::decimation(decimation).map_err(input_error)
+}
+
+fn reflection(reflection_factor: FLOAT) -> ScriptResult<f32> {
+    validation::reflection_factor(reflection_factor as f32).map_err(input_error)
+}
+
+/// Reads a number that may be written as an integer or a float.
+fn number(value: &Dynamic) -> Option<f32> {
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|value| value as FLOAT))
+        .map(|value| value as f32)
+}
+
+/// Creates a source type from a map of properties.
+fn source_type(properties: &Map) -> ScriptResult<SourceType> {
+    let name = match properties.get("type") {
+        None => "sin".to_string(),
+        Some(value) => value
+            .clone()
+            .into_string()
+            .map_err(|_| "Property type has to be a string")?,
+    };
+    let mut source_type = validation::source_type(&name).map_err(input_error)?;
+
+    for (property, value) in properties
+        .iter()
+        .filter(|(property, _)| *property != "type")
+    {
+        let value =
+            number(value).ok_or_else(|| format!("Property {property} has to be a number"))?;
+        validation::set_parameter(&mut source_type, property, value).map_err(input_error)?;
+    }
+    Ok(source_type)
+}
+
+fn set_setting(
+    simulation: &mut HeadlessSimulation,
+    name: &str,
+    value: Dynamic,
+) -> ScriptResult<()> {
+    let invalid = || -> Box<EvalAltResult> { format!("Invalid value for {name}: {value}").into() };
+
+    match name {
+        "delta_l" => {
+            let delta_l = number(&value)
+                .filter(|delta_l| *delta_l > 0.)
+                .ok_or_else(invalid)?;
+            simulation.ui_state_mut().delta_l = delta_l;
+        }
+        "boundary_width" => {
+            let boundary_width = value
+                .as_int()
+                .ok()
+                .filter(|width| (2..=200).contains(width))
+                .ok_or_else(invalid)?;
+            simulation.set_boundary_width(boundary_width as u32);
+        }
+        "framerate" => {
+            let framerate = number(&value)
+                .filter(|framerate| (1. ..=500.).contains(framerate))
+                .ok_or_else(invalid)?;
+            simulation.ui_state_mut().framerate = framerate as f64;
+        }
+        "is_recording" => {
+            simulation.ui_state_mut().is_recording = value.as_bool().map_err(|_| invalid())?;
+        }
+        "reset_on_change" => {
+            simulation.ui_state_mut().reset_on_change = value.as_bool().map_err(|_| invalid())?;
+        }
+        "min_gradient" => {
+            simulation.ui_state_mut().min_gradient = number(&value).ok_or_else(invalid)?;
+        }
+        "max_gradient" => {
+            simulation.ui_state_mut().max_gradient = number(&value).ok_or_else(invalid)?;
+        }
+        _ => return Err(format!("Unknown setting: {name}").into()),
+    }
+    Ok(())
+}
+
+fn get_setting(simulation: &HeadlessSimulation, name: &str) -> ScriptResult<Dynamic> {
+    let ui_state = simulation.ui_state();
+    Ok(match name {
+        "delta_l" => Dynamic::from_float(ui_state.delta_l as FLOAT),
+        "boundary_width" => Dynamic::from_int(ui_state.boundary_width as INT),
+        "framerate" => Dynamic::from_float(ui_state.framerate as FLOAT),
+        "is_recording" => Dynamic::from_bool(ui_state.is_recording),
+        "reset_on_change" => Dynamic::from_bool(ui_state.reset_on_change),
+        "min_gradient" => Dynamic::from_float(ui_state.min_gradient as FLOAT),
+        "max_gradient" => Dynamic::from_float(ui_state.max_gradient as FLOAT),
+        _ => return Err(format!("Unknown setting: {name}").into()),
+    })
+}
+
+fn mic_record(simulation: &mut HeadlessSimulation, id: INT) -> ScriptResult<Vec<[f64; 2]>> {
+    usize::try_from(id)
+        .ok()
+        .and_then(|id| simulation.mic_record(id))
+        .ok_or_else(|| format!("There is no microphone with id {id}").into())
+}
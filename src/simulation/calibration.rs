@@ -0,0 +1,196 @@
+use std::f32::consts::{PI, SQRT_2};
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+
+use super::headless::HeadlessSimulation;
+use crate::components::source::{Source, SourceType};
+use crate::math::constants::{PROPAGATION_SPEED, SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::units::SourceLevelUnit;
+
+/// Length of the measured impulse response in time steps
+const IMPULSE_RESPONSE_LENGTH: usize = 4096;
+
+/// Largest distance between the source and the microphone of the measurement in cells.
+/// Both are placed in the middle of the simulation area, away from the absorbing boundary.
+const MAX_DISTANCE: u32 = SIMULATION_WIDTH / 2 - 50;
+
+/// Amount of frequencies at which the level of a sweep is evaluated
+const SWEEP_FREQUENCIES: usize = 64;
+
+/// Relates the amplitude of sources to the pressure they cause at a distance of 1 m,
+/// assuming a pressure of 1 on the grid is 1 Pa.
+///
+/// The free field impulse response between a source and a microphone is measured once with a
+/// [`HeadlessSimulation`]. In time steps it only depends on the distance in cells,
+/// so the measurement is reused until `delta_l` changes the amount of cells in a meter.
+/// The measurement runs in the background, [`SourceCalibration::poll`] stores its result.
+#[derive(Debug, Default, Resource)]
+pub struct SourceCalibration {
+    /// distance of the microphone in cells and the measured impulse response
+    response: Option<(u32, Vec<f32>)>,
+    /// the running measurement
+    task: Option<Task<(u32, Vec<f32>)>>,
+    /// how the level of sources is entered in the ui
+    pub level_unit: SourceLevelUnit,
+}
+
+impl SourceCalibration {
+    /// The distance of the measurement in cells, 1 m if it fits into the simulation area.
+    pub fn distance(delta_l: f32) -> u32 {
+        ((1. / delta_l).round() as u32).clamp(1, MAX_DISTANCE)
+    }
+
+    pub fn is_measured(&self, delta_l: f32) -> bool {
+        self.response
+            .as_ref()
+            .is_some_and(|(distance, _)| *distance == Self::distance(delta_l))
+    }
+
+    /// Whether a measurement is running in the background.
+    pub fn is_measuring(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Starts measuring the impulse response for `delta_l` on the [`AsyncComputeTaskPool`]
+    /// unless it is already known or being measured.
+    /// The measurement runs a simulation of a few thousand steps.
+    pub fn measure(&mut self, delta_l: f32) {
+        if self.is_measured(delta_l) || self.is_measuring() {
+            return;
+        }
+        let distance = Self::distance(delta_l);
+        self.task = Some(
+            AsyncComputeTaskPool::get()
+                .spawn(async move { (distance, impulse_response(distance)) }),
+        );
+    }
+
+    /// Stores the result of the running measurement once it is finished.
+    pub fn poll(&mut self) {
+        let Some(task) = self.task.as_mut() else {
+            return;
+        };
+        if let Some(response) = block_on(poll_once(task)) {
+            self.response = Some(response);
+            self.task = None;
+        }
+    }
+
+    /// The RMS pressure at 1 m per unit of amplitude of a source, `None` if the response for `delta_l`
+    /// was not measured yet or the source has no steady level (impulses).
+    pub fn pressure_per_amplitude(&self, source_type: &SourceType, delta_l: f32) -> Option<f32> {
+        let (distance, response) = self
+            .response
+            .as_ref()
+            .filter(|(distance, _)| *distance == Self::distance(delta_l))?;
+        let delta_t = delta_l / PROPAGATION_SPEED;
+
+        let mut unit_source = *source_type;
+        *unit_source.parameter_mut("amplitude")? = 1.;
+
+        let rms = match unit_source {
+            SourceType::Sin { frequency, .. } => magnitude(response, frequency * delta_t) / SQRT_2,
+            // every sample is independent, so the variances of the delayed samples add up
+            SourceType::WhiteNoise { .. } => response.iter().map(|h| h * h).sum::<f32>().sqrt(),
+            SourceType::Sweep {
+                start_frequency,
+                end_frequency,
+                ..
+            } => {
+                if end_frequency <= start_frequency {
+                    return Some(0.);
+                }
+                // an exponential sweep spends the same time in every octave
+                let ratio = end_frequency / start_frequency;
+                let mean_square = (0..SWEEP_FREQUENCIES)
+                    .map(|i| {
+                        let position = (i as f32 + 0.5) / SWEEP_FREQUENCIES as f32;
+                        let frequency = start_frequency * ratio.powf(position);
+                        magnitude(response, frequency * delta_t).powi(2) / 2.
+                    })
+                    .sum::<f32>()
+                    / SWEEP_FREQUENCIES as f32;
+                mean_square.sqrt()
+            }
+            SourceType::Gauss { .. } => {
+                let source = Source::new(0, 0, unit_source, 0);
+                convolved_rms(response, |step| source.calc(step as f32 * delta_t))
+            }
+            SourceType::Impulse { .. } => return None,
+        };
+
+        // cylindrical waves decay with 1/√r in the far field
+        Some(rms * (*distance as f32 * delta_l).sqrt())
+    }
+
+    /// The level of a source at 1 m in [`SourceCalibration::level_unit`].
+    pub fn source_level(&self, source_type: &SourceType, delta_l: f32) -> Option<f32> {
+        let pressure_per_amplitude = self.pressure_per_amplitude(source_type, delta_l)?;
+        let mut source_type = *source_type;
+        let amplitude = *source_type.parameter_mut("amplitude")?;
+        Some(self.level_unit.level_of(pressure_per_amplitude * amplitude))
+    }
+
+    /// Changes the amplitude of a source so it reaches the level at 1 m.
+    /// Returns false if the source cannot be calibrated.
+    pub fn set_source_level(&self, source_type: &mut SourceType, level: f32, delta_l: f32) -> bool {
+        let Some(pressure_per_amplitude) = self
+            .pressure_per_amplitude(source_type, delta_l)
+            .filter(|pressure| *pressure > 0.)
+        else {
+            return false;
+        };
+        let Some(amplitude) = source_type.parameter_mut("amplitude") else {
+            return false;
+        };
+        *amplitude = self.level_unit.to_pressure_at_1m(level) / pressure_per_amplitude;
+        true
+    }
+}
+
+/// Simulates an impulse in the free field and records it `distance` cells away.
+fn impulse_response(distance: u32) -> Vec<f32> {
+    let mut simulation = HeadlessSimulation::new();
+    let (x, y) = (SIMULATION_WIDTH / 2, SIMULATION_HEIGHT / 2);
+    simulation.add_source(x, y, SourceType::Impulse { amplitude: 1. });
+    let mic = simulation.add_microphone(x + distance, y);
+    simulation.step(IMPULSE_RESPONSE_LENGTH);
+
+    simulation
+        .mic_record(mic)
+        .unwrap_or_default()
+        .iter()
+        .map(|[_, pressure]| *pressure as f32)
+        .collect()
+}
+
+/// The magnitude of the transfer function at a frequency relative to the sample rate.
+fn magnitude(response: &[f32], normalized_frequency: f32) -> f32 {
+    let omega = 2. * PI * normalized_frequency;
+    let (re, im) = response
+        .iter()
+        .enumerate()
+        .fold((0., 0.), |(re, im), (n, h)| {
+            let phase = omega * n as f32;
+            (re + h * phase.cos(), im - h * phase.sin())
+        });
+    f32::hypot(re, im)
+}
+
+/// The RMS value of a signal filtered by the response, after the response has settled.
+fn convolved_rms(response: &[f32], signal: impl Fn(usize) -> f32) -> f32 {
+    let samples = (0..2 * response.len()).map(signal).collect::<Vec<_>>();
+    let mean_square = (response.len()..samples.len())
+        .map(|n| {
+            let output = response
+                .iter()
+                .enumerate()
+                .map(|(k, h)| h * samples[n - k])
+                .sum::<f32>();
+            output * output
+        })
+        .sum::<f32>()
+        / response.len() as f32;
+    mean_square.sqrt()
+}
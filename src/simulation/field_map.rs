@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use super::grid::Grid;
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::transformations::coords_to_index;
+use crate::math::units::PressureUnits;
+use crate::ui::state::RenderMode;
+
+/// Per cell statistics of the pressure field that are accumulated over time.
+/// They are only accumulated while a field map is shown and start over when one is shown again.
+/// All levels are given in dB relative to a pressure of 1.
+#[derive(Debug, Default, Resource)]
+pub struct FieldMap {
+    /// mean square pressure per cell over the last complete averaging window
+    pub mean_square: Vec<f32>,
+    /// sum of the squared pressure in the current averaging window of the RMS level
+    pub window_energy: Vec<f32>,
+    /// amount of samples summed up in `window_energy`
+    pub window_samples: usize,
+    /// time since the start of the current averaging window in seconds
+    pub window_elapsed: f32,
+    /// whether `mean_square` holds a complete window
+    pub window_complete: bool,
+    /// sum of the squared pressure since the start of the averaging window
+    pub energy: Vec<f32>,
+    /// highest absolute pressure since the last reset
+    pub peak: Vec<f32>,
+    /// exponentially time weighted active intensity (pressure times particle velocity) per cell
+    pub intensity: Vec<[f32; 2]>,
+    /// amount of samples summed up in `energy`
+    pub samples: usize,
+    /// time since the last reset in seconds
+    pub elapsed: f32,
+}
+
+impl FieldMap {
+    pub fn clear(&mut self) {
+        self.clear_levels();
+        self.intensity.clear();
+    }
+
+    /// Clears the statistics of the levels but keeps the intensity.
+    pub fn clear_levels(&mut self) {
+        self.mean_square.clear();
+        self.window_energy.clear();
+        self.window_samples = 0;
+        self.window_elapsed = 0.;
+        self.window_complete = false;
+        self.energy.clear();
+        self.peak.clear();
+        self.samples = 0;
+        self.elapsed = 0.;
+    }
+
+    /// Adds the current pressure of every cell to the statistics.
+    /// * `averaging_time` - The length of the RMS and Leq windows in seconds.
+    pub fn accumulate(&mut self, pressure: &[f32], delta_t: f32, averaging_time: f32) {
+        if self.peak.len() != pressure.len() {
+            // the grid size changed or the statistics were cleared
+            self.clear_levels();
+            self.mean_square = vec![0.; pressure.len()];
+            self.window_energy = vec![0.; pressure.len()];
+            self.energy = vec![0.; pressure.len()];
+            self.peak = vec![0.; pressure.len()];
+        }
+
+        self.window_energy
+            .par_iter_mut()
+            .zip(self.peak.par_iter_mut())
+            .enumerate()
+            .for_each(|(index, (window_energy, peak))| {
+                let p = pressure[index];
+                *window_energy += p * p;
+                *peak = peak.max(p.abs());
+            });
+        self.window_samples += 1;
+        self.window_elapsed += delta_t;
+
+        // the RMS level shows the last complete window and starts the next one
+        if self.window_elapsed >= averaging_time {
+            let samples = self.window_samples as f32;
+            self.mean_square
+                .par_iter_mut()
+                .zip(self.window_energy.par_iter_mut())
+                .for_each(|(mean_square, window_energy)| {
+                    *mean_square = *window_energy / samples;
+                    *window_energy = 0.;
+                });
+            self.window_samples = 0;
+            self.window_elapsed = 0.;
+            self.window_complete = true;
+        }
+
+        // the Leq holds its value once the window is over
+        if self.elapsed < averaging_time {
+            self.energy
+                .par_iter_mut()
+                .enumerate()
+                .for_each(|(index, energy)| *energy += pressure[index] * pressure[index]);
+            self.samples += 1;
+        }
+
+        self.elapsed += delta_t;
+    }
+
+    /// Adds the current intensity of every cell to the time averaged active intensity.
+    /// * `averaging_time` - The time constant of the average in seconds.
+    pub fn accumulate_intensity(&mut self, grid: &Grid, averaging_time: f32) {
+        if self.intensity.len() != grid.pressure.len() {
+            self.intensity = vec![[0.; 2]; grid.pressure.len()];
+        }
+
+        let alpha = 1. - (-grid.delta_t / averaging_time.max(grid.delta_t)).exp();
+        self.intensity
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(index, intensity)| {
+                let p = grid.pressure[index];
+                let [u_x, u_y] = grid.velocity(index);
+                intensity[0] += alpha * (p * u_x - intensity[0]);
+                intensity[1] += alpha * (p * u_y - intensity[1]);
+            });
+    }
+
+    /// Returns the level of a cell in dB, `f32::NEG_INFINITY` if nothing was accumulated yet.
+    pub fn level(&self, index: usize, mode: RenderMode) -> f32 {
+        let value = match mode {
+            RenderMode::Rms if self.window_complete => self.mean_square.get(index).copied(),
+            // the first window is shown while it fills up
+            RenderMode::Rms if self.window_samples > 0 => self
+                .window_energy
+                .get(index)
+                .map(|energy| energy / self.window_samples as f32),
+            RenderMode::Peak => self.peak.get(index).map(|peak| peak * peak),
+            RenderMode::Leq if self.samples > 0 => self
+                .energy
+                .get(index)
+                .map(|energy| energy / self.samples as f32),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => 10. * value.log10(),
+            None => f32::NEG_INFINITY,
+        }
+    }
+
+    /// Returns the levels of the visible simulation area (without the absorbing boundary) as CSV.
+    /// * `delta_l` - The size of a cell in meters, used to write the cell positions.
+    /// * `units` - The units the levels are written in.
+    pub fn to_csv(
+        &self,
+        mode: RenderMode,
+        boundary_width: u32,
+        delta_l: f32,
+        units: PressureUnits,
+    ) -> Vec<u8> {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        wtr.write_record([
+            "x",
+            "y",
+            "x (m)",
+            "y (m)",
+            &format!("{} ({})", mode, units.level_suffix()),
+        ])
+        .unwrap();
+        for y in 0..SIMULATION_HEIGHT {
+            for x in 0..SIMULATION_WIDTH {
+                let index = coords_to_index(x + boundary_width, y + boundary_width, boundary_width);
+                wtr.write_record(&[
+                    x.to_string(),
+                    y.to_string(),
+                    (x as f32 * delta_l).to_string(),
+                    (y as f32 * delta_l).to_string(),
+                    (self.level(index, mode) + units.level_offset()).to_string(),
+                ])
+                .unwrap();
+            }
+        }
+        wtr.into_inner().unwrap()
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::export::npy::NpyWriter;
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::transformations::coords_to_index;
+
+/// Metadata that is written next to a field recording as JSON.
+#[derive(Debug, Serialize)]
+struct FieldRecordingMetadata {
+    /// name of the `.npy` file with the pressure
+    data: String,
+    /// shape of the recording: frames, height, width
+    shape: [usize; 3],
+    /// distance between two recorded cells in meters
+    delta_l: f32,
+    /// time between two recorded frames in seconds
+    delta_t: f32,
+    /// time step of the simulation in seconds
+    simulation_delta_t: f32,
+    /// simulation cell size in meters
+    simulation_delta_l: f32,
+    /// top left recorded cell in grid coordinates
+    origin: [u32; 2],
+    spatial_decimation: u32,
+    time_decimation: u32,
+    /// simulation time of the first frame in seconds
+    start_time: f32,
+}
+
+/// Records the pressure of the simulated region frame by frame to a NumPy `.npy` file.
+///
+/// The `.npy` header holds the dimensions of the recording as the shape
+/// `(frames, height, width)` of the array. NumPy rejects headers with other keys,
+/// so the cell size, the time step and the recorded region are written to a JSON sidecar
+/// with the same name and the extension `.json` (see [`metadata_path`]) when the recording stops:
+///
+/// ```python
+/// import json, numpy as np
+/// field = np.load("field_recording.npy", mmap_mode="r")
+/// metadata = json.load(open("field_recording.json"))
+/// x = (metadata["origin"][0] + np.arange(field.shape[2]) * metadata["spatial_decimation"]) * metadata["simulation_delta_l"]
+/// t = metadata["start_time"] + np.arange(field.shape[0]) * metadata["delta_t"]
+/// ```
+///
+/// The recorder can be used without the UI, see
+/// [`HeadlessSimulation::start_field_recording`](super::headless::HeadlessSimulation::start_field_recording),
+/// or directly: call [`FieldRecorder::start`], then [`FieldRecorder::record`] after every
+/// simulation step and [`FieldRecorder::stop`] to finish the file.
+#[derive(Debug, Resource)]
+pub struct FieldRecorder {
+    pub path: PathBuf,
+    /// first recorded cell in grid coordinates (inclusive)
+    pub min: UVec2,
+    /// last recorded cell in grid coordinates (inclusive)
+    pub max: UVec2,
+    /// only every n-th cell in x and y direction is recorded
+    pub spatial_decimation: u32,
+    /// only every n-th simulation step is recorded
+    pub time_decimation: u32,
+    /// the recording stops automatically after this duration in seconds, if set
+    pub max_duration: Option<f32>,
+    /// the last error that stopped the recording
+    pub error: Option<String>,
+    writer: Option<NpyWriter<BufWriter<File>>>,
+    metadata: Option<FieldRecordingMetadata>,
+    steps: u32,
+}
+
+impl Default for FieldRecorder {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("field_recording.npy"),
+            min: UVec2::ZERO,
+            max: UVec2::new(SIMULATION_WIDTH - 1, SIMULATION_HEIGHT - 1),
+            spatial_decimation: 1,
+            time_decimation: 1,
+            max_duration: None,
+            error: None,
+            writer: None,
+            metadata: None,
+            steps: 0,
+        }
+    }
+}
+
+impl FieldRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// The recorded cells in x and y direction
+    pub fn frame_size(&self) -> UVec2 {
+        let min = self.min.min(self.max);
+        let max = self.min.max(self.max);
+        let step = self.spatial_decimation.max(1);
+        (max - min) / step + UVec2::ONE
+    }
+
+    /// Size of a single frame in bytes
+    pub fn frame_bytes(&self) -> usize {
+        let size = self.frame_size();
+        (size.x * size.y) as usize * std::mem::size_of::<f32>()
+    }
+
+    /// Amount of frames recorded so far
+    pub fn frames(&self) -> usize {
+        self.writer.as_ref().map_or(0, NpyWriter::frames)
+    }
+
+    /// Size of the recording in bytes
+    pub fn bytes(&self) -> usize {
+        self.writer.as_ref().map_or(0, NpyWriter::bytes)
+    }
+
+    /// Recorded simulation time in seconds
+    pub fn recorded_duration(&self) -> f32 {
+        self.metadata
+            .as_ref()
+            .map_or(0., |metadata| self.frames() as f32 * metadata.delta_t)
+    }
+
+    /// Bytes written per second of simulated time
+    pub fn bytes_per_second(&self, delta_t: f32) -> f32 {
+        self.frame_bytes() as f32 / (delta_t * self.time_decimation.max(1) as f32)
+    }
+
+    /// Creates the file at `path` and starts recording. A running recording is finished first.
+    /// * `time` - The current simulation time in seconds.
+    pub fn start(&mut self, delta_l: f32, delta_t: f32, time: f32) -> io::Result<()> {
+        self.stop()?;
+        self.error = None;
+        self.min = self.min.min(self.max);
+        self.max = self
+            .min
+            .max(self.max)
+            .min(UVec2::new(SIMULATION_WIDTH - 1, SIMULATION_HEIGHT - 1));
+        self.spatial_decimation = self.spatial_decimation.max(1);
+        self.time_decimation = self.time_decimation.max(1);
+
+        let size = self.frame_size();
+        let file = BufWriter::new(File::create(&self.path)?);
+        self.writer = Some(NpyWriter::new(file, &[size.y as usize, size.x as usize])?);
+        self.metadata = Some(FieldRecordingMetadata {
+            data: self
+                .path
+                .file_name()
+                .map_or(String::new(), |name| name.to_string_lossy().into_owned()),
+            shape: [0, size.y as usize, size.x as usize],
+            delta_l: delta_l * self.spatial_decimation as f32,
+            delta_t: delta_t * self.time_decimation as f32,
+            simulation_delta_t: delta_t,
+            simulation_delta_l: delta_l,
+            origin: [self.min.x, self.min.y],
+            spatial_decimation: self.spatial_decimation,
+            time_decimation: self.time_decimation,
+            start_time: time,
+        });
+        self.steps = 0;
+        Ok(())
+    }
+
+    /// Records a frame of the pressure if the step is not skipped by the time decimation.
+    /// Stops the recording once the maximum duration is reached.
+    pub fn record(&mut self, pressure: &[f32], boundary_width: u32) -> io::Result<()> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+
+        self.steps += 1;
+        if !(self.steps - 1).is_multiple_of(self.time_decimation) {
+            return Ok(());
+        }
+
+        let step = self.spatial_decimation as usize;
+        let frame = (self.min.y..=self.max.y)
+            .step_by(step)
+            .flat_map(|y| {
+                (self.min.x..=self.max.x).step_by(step).map(move |x| {
+                    pressure
+                        [coords_to_index(x + boundary_width, y + boundary_width, boundary_width)]
+                })
+            })
+            .collect::<Vec<_>>();
+        writer.write_frame(&frame)?;
+
+        if self
+            .max_duration
+            .is_some_and(|duration| self.recorded_duration() >= duration)
+        {
+            self.stop()?;
+        }
+        Ok(())
+    }
+
+    /// Finishes the `.npy` file and writes the metadata. Does nothing if not recording.
+    pub fn stop(&mut self) -> io::Result<()> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let frames = writer.frames();
+        writer.finish()?;
+
+        if let Some(mut metadata) = self.metadata.take() {
+            metadata.shape[0] = frames;
+            let json = serde_json::to_vec_pretty(&metadata).map_err(io::Error::other)?;
+            std::fs::write(metadata_path(&self.path), json)?;
+        }
+        Ok(())
+    }
+}
+
+/// Path of the JSON metadata of the recording at `path`.
+pub fn metadata_path(path: &Path) -> PathBuf {
+    path.with_extension("json")
+}
@@ -1,4 +1,5 @@
-use std::f32::consts::TAU;
+use std::f32::consts::{SQRT_2, TAU};
+use std::path::Path;
 
 use bevy::prelude::*;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
@@ -18,7 +19,7 @@ pub struct Cell {
     pub right: f32,
 }
 
-#[derive(Debug, Resource)]
+#[derive(Debug, Clone, Resource)]
 pub struct Grid {
     /// Grid cells
     pub cur_cells: Vec<Cell>,
@@ -74,6 +75,24 @@ impl Grid {
         self.delta_t = delta_l / PROPAGATION_SPEED;
     }
 
+    /// Speed of sound on the grid in m/s.
+    /// Waves in a two dimensional TLM mesh travel 1/√2 cells per time step.
+    pub fn wave_speed(&self, delta_l: f32) -> f32 {
+        delta_l / (self.delta_t * SQRT_2)
+    }
+
+    /// Sets the pressure of a cell to `amplitude` by writing equal pulses into all of its ports
+    pub fn inject_impulse(&mut self, index: usize, amplitude: f32) {
+        let pulse = amplitude / 2.;
+        self.cur_cells[index] = Cell {
+            bottom: pulse,
+            left: pulse,
+            top: pulse,
+            right: pulse,
+        };
+        self.pressure[index] = amplitude;
+    }
+
     pub fn reset_cells(&mut self, boundary_width: u32) {
         self.cur_cells = vec![
             Cell::default();
@@ -124,6 +143,13 @@ impl Grid {
             });
     }
 
+    /// Particle velocity of a cell derived from the scattered pulses (x to the right, y downwards).
+    /// The velocity is given in units of pressure divided by the line impedance.
+    pub fn velocity(&self, index: usize) -> [f32; 2] {
+        let cell = self.cur_cells[index];
+        [cell.right - cell.left, cell.bottom - cell.top]
+    }
+
     pub fn update_walls(
         &mut self,
         rect_walls: &Query<&RectWall>,
@@ -305,14 +331,18 @@ impl Grid {
     }
 
     /// Write source outputs into cell reflection pulses
+    ///
+    /// Returns the id and output of every source
     pub fn apply_sources(
         &mut self,
         time_since_start: f32,
         sources: &Query<&Source>,
         boundary_width: u32,
-    ) {
+    ) -> Vec<(usize, f32)> {
+        let mut outputs = vec![];
         for source in sources.iter() {
             let calc = source.calc(time_since_start);
+            outputs.push((source.id, calc));
             let source_pos = coords_to_index(
                 source.x + boundary_width,
                 source.y + boundary_width,
@@ -323,28 +353,37 @@ impl Grid {
             self.next_cells[source_pos].top = calc;
             self.next_cells[source_pos].right = calc;
         }
+        outputs
     }
 
-    /// If plots are enabled, write cell pressure values into microphones
+    /// If recording is enabled, write cell pressure values into armed microphones
     pub fn apply_microphones(
         &self,
         mut microphones: Query<&mut Microphone>,
         ui_state: &UiState,
+        stream_directory: &Path,
         time_since_start: f64,
     ) {
-        if ui_state.show_plots {
+        if ui_state.is_recording {
             for mut mic in microphones.iter_mut() {
+                if !mic.armed {
+                    continue;
+                }
+
                 let x = mic.x;
                 let y = mic.y;
 
-                mic.record.push([
+                mic.record_sample(
                     time_since_start,
                     self.pressure[coords_to_index(
                         x + ui_state.boundary_width,
                         y + ui_state.boundary_width,
                         ui_state.boundary_width,
                     )] as f64,
-                ]);
+                    ui_state.recording_policy,
+                    self.delta_t,
+                    stream_directory,
+                );
             }
         }
     }
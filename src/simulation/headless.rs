@@ -0,0 +1,438 @@
+use std::io;
+use std::path::PathBuf;
+
+use bevy::ecs::schedule::ExecutorKind;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+use super::field_recorder::FieldRecorder;
+use super::grid::Grid;
+use super::plugin::ComponentIDs;
+use super::systems::{apply_system, calc_system, field_recorder_system, update_system};
+use crate::components::microphone::{Microphone, RecordingPolicy, StreamDirectory};
+use crate::components::source::{Source, SourceRecords, SourceType};
+use crate::components::wall::{CircWall, RectWall};
+use crate::math::constants::{PROPAGATION_SPEED, SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::transformations::coords_to_index;
+use crate::ui::state::{SimTime, UiState};
+
+/// A simulation without a window, used by scripts and language bindings.
+///
+/// It owns a separate [`World`] and steps it with the same systems the app runs in `FixedUpdate`,
+/// so results are identical to the interactive simulation. Only the grid, the sources,
+/// the microphones and the field recorder are simulated,
+/// the other analysis tools of the app are not available.
+pub struct HeadlessSimulation {
+    world: World,
+    schedule: Schedule,
+    walls_changed: bool,
+}
+
+impl Default for HeadlessSimulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for HeadlessSimulation {
+    fn drop(&mut self) {
+        // a recording that is not stopped would leave a file without metadata behind
+        if let Some(mut recorder) = self.world.get_resource_mut::<FieldRecorder>() {
+            if let Err(err) = recorder.stop() {
+                warn!("Could not finish the field recording: {err}");
+            }
+        }
+    }
+}
+
+impl HeadlessSimulation {
+    /// Creates an empty simulation with the default settings.
+    /// Microphones keep their whole record.
+    pub fn new() -> Self {
+        let ui_state = UiState {
+            is_running: true,
+            is_recording: true,
+            recording_policy: RecordingPolicy::Unbounded,
+            ..default()
+        };
+        let mut grid = Grid::default();
+        grid.reset_cells(ui_state.boundary_width);
+        grid.reset_walls(ui_state.boundary_width);
+        grid.cache_boundaries(ui_state.boundary_width);
+
+        let mut world = World::new();
+        world.insert_resource(grid);
+        world.insert_resource(ui_state);
+        world.init_resource::<SimTime>();
+        world.init_resource::<SourceRecords>();
+        world.init_resource::<StreamDirectory>();
+        world.init_resource::<ComponentIDs>();
+        world.init_resource::<FieldRecorder>();
+
+        let mut schedule = Schedule::default();
+        // the grid is parallelized internally, the systems run one after another anyway
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        schedule.add_systems(
+            (
+                calc_system,
+                apply_system,
+                update_system,
+                field_recorder_system,
+            )
+                .chain(),
+        );
+
+        Self {
+            world,
+            schedule,
+            walls_changed: false,
+        }
+    }
+
+    /// Copies the scene and the state of the simulation from the world of the app.
+    pub fn from_world(world: &mut World) -> Self {
+        let mut simulation = Self::new();
+
+        let grid = world.resource::<Grid>().clone();
+        let ui_state = UiState {
+            is_running: true,
+            ..*world.resource::<UiState>()
+        };
+        let time_since_start = world.resource::<SimTime>().time_since_start;
+        let ids = *world.resource::<ComponentIDs>();
+
+        let target = &mut simulation.world;
+        target.insert_resource(grid);
+        target.insert_resource(ui_state);
+        target.resource_mut::<SimTime>().time_since_start = time_since_start;
+        target.insert_resource(ids);
+
+        for source in world.query::<&Source>().iter(world) {
+            target.spawn(*source);
+        }
+        for mic in world.query::<&Microphone>().iter(world) {
+            target.spawn(mic.clone());
+        }
+        for wall in world.query::<&RectWall>().iter(world) {
+            target.spawn(*wall);
+        }
+        for wall in world.query::<&CircWall>().iter(world) {
+            target.spawn(*wall);
+        }
+
+        simulation
+    }
+
+    /// Replaces the scene and the state of the simulation in the world of the app.
+    /// Whether the app is running is not changed.
+    pub fn apply_to(mut self, world: &mut World) {
+        self.update_walls();
+
+        let entities = world
+            .query_filtered::<Entity, Or<(
+                With<Source>,
+                With<Microphone>,
+                With<RectWall>,
+                With<CircWall>,
+            )>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        for entity in entities {
+            world.despawn(entity);
+        }
+
+        for source in self.sources() {
+            world.spawn(source);
+        }
+        for mic in self.microphones() {
+            world.spawn(mic);
+        }
+        for wall in self.rect_walls() {
+            world.spawn(wall);
+        }
+        for wall in self.circ_walls() {
+            world.spawn(wall);
+        }
+
+        let is_running = world.resource::<UiState>().is_running;
+        world.insert_resource(UiState {
+            is_running,
+            ..*self.ui_state()
+        });
+        world.resource_mut::<SimTime>().time_since_start = self.time();
+        world.insert_resource(*self.world.resource::<ComponentIDs>());
+        world.insert_resource(self.world.remove_resource::<Grid>().expect("grid exists"));
+    }
+
+    pub fn ui_state(&self) -> &UiState {
+        self.world.resource::<UiState>()
+    }
+
+    /// The settings of the simulation. Use [`HeadlessSimulation::set_boundary_width`]
+    /// to change the boundary width, it requires the grid to be resized.
+    pub fn ui_state_mut(&mut self) -> Mut<'_, UiState> {
+        self.world.resource_mut::<UiState>()
+    }
+
+    pub fn grid(&self) -> &Grid {
+        self.world.resource::<Grid>()
+    }
+
+    /// Simulation time in seconds
+    pub fn time(&self) -> f32 {
+        self.world.resource::<SimTime>().time_since_start
+    }
+
+    /// Time between two steps in seconds
+    pub fn delta_t(&self) -> f32 {
+        self.ui_state().delta_l / PROPAGATION_SPEED
+    }
+
+    /// Changes the width of the absorbing boundary. This resets the simulation.
+    pub fn set_boundary_width(&mut self, boundary_width: u32) {
+        self.ui_state_mut().boundary_width = boundary_width;
+        let mut grid = self.world.resource_mut::<Grid>();
+        grid.reset_walls(boundary_width);
+        grid.cache_boundaries(boundary_width);
+        self.walls_changed = true;
+        self.reset();
+    }
+
+    /// Adds a source and returns its id.
+    pub fn add_source(&mut self, x: u32, y: u32, source_type: SourceType) -> usize {
+        let id = self
+            .world
+            .resource_mut::<ComponentIDs>()
+            .get_new_source_id();
+        self.world.spawn(Source::new(x, y, source_type, id));
+        id
+    }
+
+    /// Adds a microphone and returns its id.
+    pub fn add_microphone(&mut self, x: u32, y: u32) -> usize {
+        let id = self.world.resource_mut::<ComponentIDs>().get_new_mic_id();
+        self.world.spawn(Microphone::new(x, y, id));
+        id
+    }
+
+    /// Adds a rectangular wall between the two corners (inclusive) and returns its id.
+    pub fn add_rect_wall(
+        &mut self,
+        min: UVec2,
+        max: UVec2,
+        is_hollow: bool,
+        reflection_factor: f32,
+    ) -> usize {
+        let id = self.world.resource_mut::<ComponentIDs>().get_new_wall_id();
+        self.world.spawn(RectWall::new(
+            min.x,
+            min.y,
+            max.x,
+            max.y,
+            is_hollow,
+            reflection_factor,
+            id,
+        ));
+        self.walls_changed = true;
+        id
+    }
+
+    /// Adds a circular wall and returns its id.
+    pub fn add_circ_wall(
+        &mut self,
+        center: UVec2,
+        radius: u32,
+        is_hollow: bool,
+        reflection_factor: f32,
+    ) -> usize {
+        let id = self.world.resource_mut::<ComponentIDs>().get_new_wall_id();
+        self.world.spawn(CircWall::new(
+            center.x,
+            center.y,
+            radius,
+            is_hollow,
+            reflection_factor,
+            id,
+        ));
+        self.walls_changed = true;
+        id
+    }
+
+    /// Removes all sources, microphones and walls and resets the simulation.
+    pub fn clear(&mut self) {
+        let entities = self
+            .world
+            .iter_entities()
+            .map(|entity| entity.id())
+            .collect::<Vec<_>>();
+        for entity in entities {
+            self.world.despawn(entity);
+        }
+        self.world.resource_mut::<ComponentIDs>().reset();
+        self.walls_changed = true;
+        self.reset();
+    }
+
+    /// Resets the pressure field, the simulation time and all records.
+    pub fn reset(&mut self) {
+        let boundary_width = self.ui_state().boundary_width;
+        self.world
+            .resource_mut::<Grid>()
+            .reset_cells(boundary_width);
+        self.world.resource_mut::<SimTime>().time_since_start = 0.;
+        self.world.resource_mut::<SourceRecords>().clear();
+        for mut mic in self
+            .world
+            .query::<&mut Microphone>()
+            .iter_mut(&mut self.world)
+        {
+            mic.clear();
+        }
+    }
+
+    /// Advances the simulation by `steps` time steps.
+    pub fn step(&mut self, steps: usize) {
+        self.update_walls();
+        // the systems only update delta t after the first step
+        let delta_l = self.ui_state().delta_l;
+        self.world.resource_mut::<Grid>().update_delta_t(delta_l);
+        for _ in 0..steps {
+            self.schedule.run(&mut self.world);
+        }
+    }
+
+    /// Starts recording the pressure of the cells between `min` and `max` (inclusive)
+    /// to a NumPy `.npy` file at `path`, see [`FieldRecorder`].
+    /// Only every `spatial_decimation`-th cell of every `time_decimation`-th step is recorded.
+    /// A running recording is finished first.
+    pub fn start_field_recording(
+        &mut self,
+        path: impl Into<PathBuf>,
+        min: UVec2,
+        max: UVec2,
+        spatial_decimation: u32,
+        time_decimation: u32,
+    ) -> io::Result<()> {
+        let delta_l = self.ui_state().delta_l;
+        let delta_t = self.delta_t();
+        let time = self.time();
+
+        let mut recorder = self.world.resource_mut::<FieldRecorder>();
+        recorder.path = path.into();
+        recorder.min = min;
+        recorder.max = max;
+        recorder.spatial_decimation = spatial_decimation;
+        recorder.time_decimation = time_decimation;
+        recorder.max_duration = None;
+        recorder.start(delta_l, delta_t, time)
+    }
+
+    /// Finishes the field recording and writes its metadata.
+    /// Returns the error that stopped the recording early, if there was one.
+    pub fn stop_field_recording(&mut self) -> io::Result<()> {
+        let mut recorder = self.world.resource_mut::<FieldRecorder>();
+        if let Some(error) = recorder.error.take() {
+            return Err(io::Error::other(error));
+        }
+        recorder.stop()
+    }
+
+    pub fn is_field_recording(&self) -> bool {
+        self.world.resource::<FieldRecorder>().is_recording()
+    }
+
+    pub fn sources(&mut self) -> Vec<Source> {
+        let mut sources = self
+            .world
+            .query::<&Source>()
+            .iter(&self.world)
+            .copied()
+            .collect::<Vec<_>>();
+        sources.sort_by_key(|source| source.id);
+        sources
+    }
+
+    pub fn microphones(&mut self) -> Vec<Microphone> {
+        let mut mics = self
+            .world
+            .query::<&Microphone>()
+            .iter(&self.world)
+            .cloned()
+            .collect::<Vec<_>>();
+        mics.sort_by_key(|mic| mic.id);
+        mics
+    }
+
+    pub fn rect_walls(&mut self) -> Vec<RectWall> {
+        let mut walls = self
+            .world
+            .query::<&RectWall>()
+            .iter(&self.world)
+            .copied()
+            .collect::<Vec<_>>();
+        walls.sort_by_key(|wall| wall.id);
+        walls
+    }
+
+    pub fn circ_walls(&mut self) -> Vec<CircWall> {
+        let mut walls = self
+            .world
+            .query::<&CircWall>()
+            .iter(&self.world)
+            .copied()
+            .collect::<Vec<_>>();
+        walls.sort_by_key(|wall| wall.id);
+        walls
+    }
+
+    /// The record of the microphone with the given id as `[time, pressure]` pairs.
+    pub fn mic_record(&mut self, id: usize) -> Option<Vec<[f64; 2]>> {
+        self.world
+            .query::<&Microphone>()
+            .iter(&self.world)
+            .find(|mic| mic.id == id)
+            .map(|mic| mic.record.clone())
+    }
+
+    /// The pressure at a position of the simulation area,
+    /// `None` if the position is outside of it.
+    pub fn pressure(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= SIMULATION_WIDTH || y >= SIMULATION_HEIGHT {
+            return None;
+        }
+        let boundary_width = self.ui_state().boundary_width;
+        Some(
+            self.grid().pressure
+                [coords_to_index(x + boundary_width, y + boundary_width, boundary_width)],
+        )
+    }
+
+    /// The pressure of the simulation area without the boundary, row by row.
+    pub fn pressure_field(&self) -> Vec<f32> {
+        let boundary_width = self.ui_state().boundary_width;
+        let grid = self.grid();
+        let mut field = Vec::with_capacity((SIMULATION_WIDTH * SIMULATION_HEIGHT) as usize);
+        for y in 0..SIMULATION_HEIGHT {
+            let start = coords_to_index(boundary_width, y + boundary_width, boundary_width);
+            field.extend_from_slice(&grid.pressure[start..start + SIMULATION_WIDTH as usize]);
+        }
+        field
+    }
+
+    /// Recalculates the wall cells of the grid if walls were added since the last step.
+    fn update_walls(&mut self) {
+        if self.walls_changed {
+            self.world.run_system_once(update_walls_system);
+            self.walls_changed = false;
+        }
+    }
+}
+
+fn update_walls_system(
+    mut grid: ResMut<Grid>,
+    ui_state: Res<UiState>,
+    rect_walls: Query<&RectWall>,
+    circ_walls: Query<&CircWall>,
+) {
+    grid.update_walls(&rect_walls, &circ_walls, ui_state.boundary_width);
+}
@@ -1,3 +1,11 @@
+pub mod calibration;
+pub mod field_map;
+pub mod field_recorder;
 pub mod grid;
+pub mod headless;
+pub mod mode_finder;
 pub mod plugin;
+pub mod steady_state;
 pub mod systems;
+pub mod templates;
+pub mod validation;
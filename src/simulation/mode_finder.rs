@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+use super::grid::Grid;
+use crate::math::rect::WRect;
+use crate::math::room_modes::{average_spectrum, find_peaks, SpectralPeak};
+use crate::math::transformations::coords_to_index;
+
+/// Amount of cells that are recorded to find the room modes
+const SAMPLE_CELLS: usize = 16;
+
+/// Finds the eigenfrequencies of a closed room by exciting it with an impulse
+/// and detecting the peaks in the averaged spectra of several cells.
+#[derive(Debug, Resource)]
+pub struct ModeFinder {
+    /// id of the hollow [`RectWall`](crate::components::wall::RectWall) that encloses the room
+    pub room: Option<usize>,
+    /// recording duration in seconds, the frequency resolution is its inverse
+    pub duration: f32,
+    /// highest frequency to search for modes in Hz
+    pub max_frequency: f32,
+    /// minimum height of a peak above the surrounding spectrum in dB
+    pub min_prominence: f32,
+    pub is_measuring: bool,
+    /// an impulse is injected into the excitation cell at the next simulation step
+    pub pending_impulse: bool,
+    /// the cell that is excited, in grid coordinates without the boundary
+    pub excitation_cell: UVec2,
+    /// the recorded cells, in grid coordinates without the boundary
+    pub sample_cells: Vec<UVec2>,
+    pub records: Vec<Vec<f64>>,
+    /// the detected modes of the last measurement
+    pub modes: Vec<SpectralPeak>,
+}
+
+impl Default for ModeFinder {
+    fn default() -> Self {
+        Self {
+            room: None,
+            duration: 0.2,
+            max_frequency: 500.,
+            min_prominence: 10.,
+            is_measuring: false,
+            pending_impulse: false,
+            excitation_cell: UVec2::ZERO,
+            sample_cells: vec![],
+            records: vec![],
+            modes: vec![],
+        }
+    }
+}
+
+impl ModeFinder {
+    /// Prepares a measurement in the interior of `room`. The simulation has to be reset afterwards.
+    /// The room is excited close to a corner, where all modes have a pressure maximum,
+    /// and recorded at cells that are spread with a Halton sequence to avoid nodal lines.
+    pub fn start(&mut self, room: &WRect) {
+        let min = room.min + UVec2::ONE;
+        let size = (room.max - room.min).max(UVec2::splat(2)) - UVec2::ONE;
+
+        self.excitation_cell = min + (size / 10).min(UVec2::splat(2));
+        self.sample_cells = (1..=SAMPLE_CELLS)
+            .map(|i| {
+                let x = halton(i, 2) * size.x as f32;
+                let y = halton(i, 3) * size.y as f32;
+                min + UVec2::new(x as u32, y as u32).min(size - UVec2::ONE)
+            })
+            .collect();
+        self.records = vec![vec![]; self.sample_cells.len()];
+        self.modes.clear();
+        self.is_measuring = true;
+        self.pending_impulse = true;
+    }
+
+    /// Excites the room again without recording, e.g. to render a mode shape.
+    pub fn excite(&mut self) {
+        self.pending_impulse = true;
+    }
+
+    /// Recorded time in seconds
+    pub fn recorded_duration(&self, delta_t: f32) -> f32 {
+        self.records.first().map_or(0, Vec::len) as f32 * delta_t
+    }
+
+    /// Injects a pending impulse and records the sample cells.
+    /// Once the duration is reached the modes are detected.
+    pub fn update(&mut self, grid: &mut Grid, boundary_width: u32) {
+        let index = |cell: UVec2| {
+            coords_to_index(
+                cell.x + boundary_width,
+                cell.y + boundary_width,
+                boundary_width,
+            )
+        };
+
+        if self.pending_impulse {
+            grid.inject_impulse(index(self.excitation_cell), 1.);
+            self.pending_impulse = false;
+        }
+
+        if !self.is_measuring {
+            return;
+        }
+
+        for (record, cell) in self.records.iter_mut().zip(&self.sample_cells) {
+            record.push(grid.pressure[index(*cell)] as f64);
+        }
+
+        if self.recorded_duration(grid.delta_t) >= self.duration {
+            self.is_measuring = false;
+            let (frequencies, levels) = average_spectrum(&self.records, grid.delta_t as f64);
+            self.modes = find_peaks(&frequencies, &levels, self.min_prominence as f64)
+                .into_iter()
+                .filter(|peak| peak.frequency <= self.max_frequency as f64)
+                .collect();
+        }
+    }
+}
+
+/// Element `index` of the Halton sequence with the given base, between 0 and 1.
+fn halton(mut index: usize, base: usize) -> f32 {
+    let mut fraction = 1.;
+    let mut result = 0.;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
@@ -1,9 +1,19 @@
-use bevy::app::{App, FixedUpdate, Plugin};
+use bevy::app::{App, FixedUpdate, Plugin, Update};
 use bevy::ecs::schedule::IntoSystemConfigs;
 use bevy::ecs::system::Resource;
 
+use super::calibration::SourceCalibration;
+use super::field_map::FieldMap;
+use super::field_recorder::FieldRecorder;
 use super::grid::Grid;
-use super::systems::{apply_system, calc_system, update_system};
+use super::mode_finder::ModeFinder;
+use super::steady_state::SteadyStateMap;
+use super::systems::{
+    apply_system, calc_system, calibration_system, field_map_system, field_recorder_system,
+    flush_system, mode_finder_system, probe_system, steady_state_system, update_system,
+};
+use crate::components::microphone::StreamDirectory;
+use crate::components::source::SourceRecords;
 use crate::math::constants::INIT_BOUNDARY_WIDTH;
 
 pub struct GridPlugin;
@@ -15,10 +25,28 @@ impl Plugin for GridPlugin {
 
         app.insert_resource(grid)
             .init_resource::<ComponentIDs>()
+            .init_resource::<SourceRecords>()
+            .init_resource::<StreamDirectory>()
+            .init_resource::<FieldMap>()
+            .init_resource::<SourceCalibration>()
+            .init_resource::<SteadyStateMap>()
+            .init_resource::<ModeFinder>()
+            .init_resource::<FieldRecorder>()
             .add_systems(
                 FixedUpdate,
-                (calc_system, apply_system, update_system).chain(),
-            );
+                (
+                    calc_system,
+                    apply_system,
+                    update_system,
+                    mode_finder_system,
+                    probe_system,
+                    field_recorder_system,
+                    field_map_system,
+                    steady_state_system,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, (flush_system, calibration_system));
 
         #[cfg(debug_assertions)]
         {
@@ -43,6 +71,7 @@ pub struct ComponentIDs {
     current_mic_id: usize,
     current_source_id: usize,
     current_wall_id: usize,
+    current_probe_id: usize,
 }
 
 impl ComponentIDs {
@@ -67,6 +96,13 @@ impl ComponentIDs {
         current
     }
 
+    /// Get a new **valid** id for a probe
+    pub fn get_new_probe_id(&mut self) -> usize {
+        let current = self.current_probe_id;
+        self.current_probe_id += 1;
+        current
+    }
+
     /// Decrements the current wall id
     pub fn decrement_wall_ids(&mut self) {
         self.current_wall_id -= 1;
@@ -77,5 +113,6 @@ impl ComponentIDs {
         self.current_mic_id = 0;
         self.current_source_id = 0;
         self.current_wall_id = 0;
+        self.current_probe_id = 0;
     }
 }
@@ -0,0 +1,232 @@
+use std::f64::consts::TAU;
+
+use bevy::prelude::*;
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::transformations::coords_to_index;
+use crate::math::units::PressureUnits;
+
+/// Relative change between two blocks below which a frequency bin counts as converged (-40 dB).
+pub const CONVERGENCE_THRESHOLD: f64 = 0.01;
+
+/// A single bin DFT of every cell, calculated block wise with the Goertzel algorithm.
+#[derive(Debug, Clone, Default)]
+pub struct FrequencyBin {
+    /// frequency in Hz
+    pub frequency: f32,
+    /// complex amplitude (real and imaginary part) of every cell from the last complete block.
+    /// Its magnitude is the peak amplitude of the sinusoid, not its RMS value.
+    pub amplitude: Vec<[f64; 2]>,
+    /// energy weighted relative change of the amplitudes between the last two blocks
+    pub change: Option<f64>,
+    /// amount of completed blocks
+    pub blocks: usize,
+    s1: Vec<f64>,
+    s2: Vec<f64>,
+    /// samples in the current block
+    position: usize,
+    /// simulation time at the start of the current block
+    block_start: f64,
+}
+
+impl FrequencyBin {
+    pub fn new(frequency: f32) -> Self {
+        Self {
+            frequency,
+            ..Default::default()
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.frequency);
+    }
+
+    /// Whether the amplitudes of the last two blocks differ by less than [`CONVERGENCE_THRESHOLD`].
+    pub fn is_converged(&self) -> bool {
+        self.change
+            .is_some_and(|change| change < CONVERGENCE_THRESHOLD)
+    }
+
+    /// Amount of samples per block, a whole number of periods that is at least `averaging_time` long.
+    pub fn block_size(&self, delta_t: f32, averaging_time: f32) -> usize {
+        let frequency = self.frequency as f64;
+        let periods = (averaging_time as f64 * frequency).ceil().max(1.);
+        ((periods / frequency / delta_t as f64).round() as usize).max(1)
+    }
+
+    /// Adds the pressure of every cell at `time` (in seconds) to the current block.
+    pub fn update(&mut self, pressure: &[f32], time: f64, delta_t: f32, averaging_time: f32) {
+        if self.s1.len() != pressure.len() {
+            // the grid size changed
+            self.clear();
+            self.s1 = vec![0.; pressure.len()];
+            self.s2 = vec![0.; pressure.len()];
+        }
+
+        if self.position == 0 {
+            self.block_start = time;
+        }
+
+        let omega = TAU * self.frequency as f64 * delta_t as f64;
+        let coefficient = 2. * omega.cos();
+        self.s1
+            .par_iter_mut()
+            .zip(self.s2.par_iter_mut())
+            .enumerate()
+            .for_each(|(index, (s1, s2))| {
+                let s = pressure[index] as f64 + coefficient * *s1 - *s2;
+                *s2 = *s1;
+                *s1 = s;
+            });
+        self.position += 1;
+
+        let block_size = self.block_size(delta_t, averaging_time);
+        if self.position >= block_size {
+            self.finish_block(omega);
+        }
+    }
+
+    /// Calculates the complex amplitudes of the current block and starts a new one.
+    fn finish_block(&mut self, omega: f64) {
+        let n = self.position as f64;
+        // X = e^(-j omega (N - 1)) (s1 - e^(-j omega) s2),
+        // additionally rotated to refer the phase to the start of the simulation
+        let rotation = -omega * (n - 1.) - TAU * self.frequency as f64 * self.block_start;
+        let (sin_r, cos_r) = rotation.sin_cos();
+        let (sin_o, cos_o) = omega.sin_cos();
+
+        let amplitude = self
+            .s1
+            .iter()
+            .zip(&self.s2)
+            .map(|(s1, s2)| {
+                let re = s1 - cos_o * s2;
+                let im = sin_o * s2;
+                // scaled to the peak amplitude of a sinusoid
+                [
+                    2. * (re * cos_r - im * sin_r) / n,
+                    2. * (re * sin_r + im * cos_r) / n,
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        if self.amplitude.len() == amplitude.len() {
+            let (difference, energy) = amplitude.iter().zip(&self.amplitude).fold(
+                (0., 0.),
+                |(difference, energy), (new, old)| {
+                    (
+                        difference + (new[0] - old[0]).powi(2) + (new[1] - old[1]).powi(2),
+                        energy + new[0].powi(2) + new[1].powi(2),
+                    )
+                },
+            );
+            self.change = if energy > 0. {
+                Some((difference / energy).sqrt())
+            } else {
+                None
+            };
+        }
+
+        self.amplitude = amplitude;
+        self.blocks += 1;
+        self.s1.iter_mut().for_each(|s| *s = 0.);
+        self.s2.iter_mut().for_each(|s| *s = 0.);
+        self.position = 0;
+    }
+
+    /// Returns the peak amplitude of a cell in dB relative to a peak of 1,
+    /// `f32::NEG_INFINITY` if no block was completed yet.
+    pub fn level(&self, index: usize) -> f32 {
+        match self.amplitude.get(index) {
+            Some([re, im]) => (20. * re.hypot(*im).log10()) as f32,
+            None => f32::NEG_INFINITY,
+        }
+    }
+
+    /// Returns the RMS value of the sinusoid of a cell in dB relative to an RMS value of 1,
+    /// which is the [`level`](Self::level) lowered by 3.01 dB.
+    /// Only this level can be converted to a sound pressure level.
+    pub fn rms_level(&self, index: usize) -> f32 {
+        self.level(index) - 10. * 2f32.log10()
+    }
+
+    /// Returns the phase of a cell in degrees.
+    pub fn phase(&self, index: usize) -> f32 {
+        match self.amplitude.get(index) {
+            Some([re, im]) => im.atan2(*re).to_degrees() as f32,
+            None => 0.,
+        }
+    }
+}
+
+/// Running single bin DFTs of the pressure field for the steady state response to tonal sources.
+#[derive(Debug, Resource)]
+pub struct SteadyStateMap {
+    pub bins: Vec<FrequencyBin>,
+    /// index of the bin that is rendered
+    pub selected: usize,
+}
+
+impl Default for SteadyStateMap {
+    fn default() -> Self {
+        Self {
+            bins: vec![FrequencyBin::new(1000.)],
+            selected: 0,
+        }
+    }
+}
+
+impl SteadyStateMap {
+    pub fn clear(&mut self) {
+        self.bins.iter_mut().for_each(FrequencyBin::clear);
+    }
+
+    pub fn selected_bin(&self) -> Option<&FrequencyBin> {
+        self.bins.get(self.selected)
+    }
+
+    /// Adds the pressure of every cell at `time` (in seconds) to all frequency bins.
+    pub fn accumulate(&mut self, pressure: &[f32], time: f64, delta_t: f32, averaging_time: f32) {
+        for bin in self.bins.iter_mut() {
+            bin.update(pressure, time, delta_t, averaging_time);
+        }
+    }
+
+    /// Returns the complex amplitudes of all bins in the visible simulation area as CSV.
+    /// * `delta_l` - The size of a cell in meters, used to write the cell positions.
+    /// * `units` - The units the amplitudes are labelled with.
+    pub fn to_csv(&self, boundary_width: u32, delta_l: f32, units: PressureUnits) -> Vec<u8> {
+        let mut wtr = csv::Writer::from_writer(vec![]);
+        let mut header = vec![
+            "x".to_string(),
+            "y".to_string(),
+            "x (m)".to_string(),
+            "y (m)".to_string(),
+        ];
+        for bin in &self.bins {
+            header.push(units.pressure_column(&format!("re {} Hz", bin.frequency)));
+            header.push(units.pressure_column(&format!("im {} Hz", bin.frequency)));
+        }
+        wtr.write_record(&header).unwrap();
+
+        for y in 0..SIMULATION_HEIGHT {
+            for x in 0..SIMULATION_WIDTH {
+                let index = coords_to_index(x + boundary_width, y + boundary_width, boundary_width);
+                let mut record = vec![
+                    x.to_string(),
+                    y.to_string(),
+                    (x as f32 * delta_l).to_string(),
+                    (y as f32 * delta_l).to_string(),
+                ];
+                for bin in &self.bins {
+                    let [re, im] = bin.amplitude.get(index).copied().unwrap_or_default();
+                    record.push(re.to_string());
+                    record.push(im.to_string());
+                }
+                wtr.write_record(&record).unwrap();
+            }
+        }
+        wtr.into_inner().unwrap()
+    }
+}
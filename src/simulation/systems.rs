@@ -1,9 +1,17 @@
 use bevy::prelude::*;
 
+use super::calibration::SourceCalibration;
+use super::field_map::FieldMap;
+use super::field_recorder::FieldRecorder;
 use super::grid::Grid;
-use crate::components::microphone::Microphone;
-use crate::components::source::Source;
-use crate::ui::state::{SimTime, UiState};
+use super::mode_finder::ModeFinder;
+use super::steady_state::SteadyStateMap;
+use crate::components::microphone::{Microphone, StreamDirectory};
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::components::source::{Source, SourceRecords};
+use crate::ui::state::{
+    SimTime, TransferFunctionSettings, TransferReference, UiState, VectorOverlay,
+};
 
 /// A system used to calculate reflection pulses per cell
 pub fn calc_system(mut grid: ResMut<Grid>, ui_state: Res<UiState>) {
@@ -12,19 +20,55 @@ pub fn calc_system(mut grid: ResMut<Grid>, ui_state: Res<UiState>) {
     }
 }
 
-/// A system used to insert source reflection pulses into cells
+/// A system used to insert source reflection pulses into cells,
+///
+/// record the emitted signal of the source selected as reference of the transfer function
 ///
 /// and write pressure values into microphones
 pub fn apply_system(
     mut grid: ResMut<Grid>,
     sources: Query<&Source>,
+    mut source_records: ResMut<SourceRecords>,
+    transfer_function: Option<Res<TransferFunctionSettings>>,
     microphones: Query<&mut Microphone>,
     sim_time: Res<SimTime>,
     ui_state: Res<UiState>,
+    stream_directory: Res<StreamDirectory>,
 ) {
+    // the transfer function tab is the only user of the signals, headless simulations have none
+    let reference = transfer_function
+        .as_ref()
+        .and_then(|settings| match settings.reference {
+            Some(TransferReference::Source(id)) => Some(id),
+            _ => None,
+        });
+    if transfer_function.is_some_and(|settings| settings.is_changed()) {
+        source_records.retain(reference);
+    }
+
     if ui_state.is_running {
-        grid.apply_sources(sim_time.time_since_start, &sources, ui_state.boundary_width);
-        grid.apply_microphones(microphones, &ui_state, sim_time.time_since_start as f64);
+        let outputs =
+            grid.apply_sources(sim_time.time_since_start, &sources, ui_state.boundary_width);
+        if ui_state.is_recording {
+            for (id, output) in outputs {
+                if Some(id) != reference {
+                    continue;
+                }
+                source_records.record(
+                    id,
+                    sim_time.time_since_start as f64,
+                    output as f64,
+                    ui_state.recording_policy,
+                    grid.delta_t,
+                );
+            }
+        }
+        grid.apply_microphones(
+            microphones,
+            &ui_state,
+            &stream_directory.0,
+            sim_time.time_since_start as f64,
+        );
     }
 }
 
@@ -44,3 +88,116 @@ pub fn update_system(
         sim_time.time_since_start += grid.delta_t;
     }
 }
+
+/// A system used to excite and record the room during a room mode measurement
+pub fn mode_finder_system(
+    mut mode_finder: ResMut<ModeFinder>,
+    mut grid: ResMut<Grid>,
+    ui_state: Res<UiState>,
+) {
+    if ui_state.is_running {
+        mode_finder.update(&mut grid, ui_state.boundary_width);
+    }
+}
+
+/// A system used to sample the cells of line probes
+/// and record the values of area probes if recording is enabled
+pub fn probe_system(
+    mut line_probes: Query<&mut LineProbe>,
+    mut area_probes: Query<&mut AreaProbe>,
+    grid: Res<Grid>,
+    sim_time: Res<SimTime>,
+    ui_state: Res<UiState>,
+) {
+    if ui_state.is_running {
+        let alpha =
+            1. - (-grid.delta_t as f64 / ui_state.averaging_time.max(grid.delta_t) as f64).exp();
+        for mut probe in line_probes.iter_mut() {
+            probe.update(&grid.pressure, ui_state.boundary_width, alpha);
+        }
+
+        if ui_state.is_recording {
+            for mut probe in area_probes.iter_mut() {
+                probe.update(
+                    &grid.pressure,
+                    ui_state.boundary_width,
+                    sim_time.time_since_start as f64,
+                    ui_state.recording_policy,
+                    grid.delta_t,
+                );
+            }
+        }
+    }
+}
+
+/// A system used to write the pressure field to disk while the field recorder is recording
+pub fn field_recorder_system(
+    mut recorder: ResMut<FieldRecorder>,
+    grid: Res<Grid>,
+    ui_state: Res<UiState>,
+) {
+    if ui_state.is_running && recorder.is_recording() {
+        if let Err(err) = recorder.record(&grid.pressure, ui_state.boundary_width) {
+            recorder.error = Some(err.to_string());
+            // the file is unusable anyway, only the error of the recording is shown
+            let _ = recorder.stop();
+        }
+    }
+}
+
+/// A system used to accumulate the field maps and the intensity if they are rendered
+pub fn field_map_system(mut field_map: ResMut<FieldMap>, grid: Res<Grid>, ui_state: Res<UiState>) {
+    if !ui_state.render_mode.is_field_map() && !field_map.peak.is_empty() {
+        // statistics of a hidden map would be missing the time it was hidden
+        field_map.clear_levels();
+    }
+    if ui_state.is_running {
+        if ui_state.render_mode.is_field_map() {
+            field_map.accumulate(&grid.pressure, grid.delta_t, ui_state.averaging_time);
+        }
+        if ui_state.vector_overlay == VectorOverlay::Intensity {
+            field_map.accumulate_intensity(&grid, ui_state.averaging_time);
+        }
+    }
+}
+
+/// A system used to update the running DFTs of all cells if the steady state is rendered
+pub fn steady_state_system(
+    mut steady_state: ResMut<SteadyStateMap>,
+    grid: Res<Grid>,
+    sim_time: Res<SimTime>,
+    ui_state: Res<UiState>,
+) {
+    if ui_state.is_running && ui_state.render_mode.is_steady_state() {
+        // the time was already advanced, the pressure is the response to the sources of the last step
+        steady_state.accumulate(
+            &grid.pressure,
+            (sim_time.time_since_start - grid.delta_t) as f64,
+            grid.delta_t,
+            ui_state.averaging_time,
+        );
+    }
+}
+
+/// A system used to write the remaining samples of streaming microphones to disk
+/// while the simulation is paused
+pub fn flush_system(
+    mut microphones: Query<&mut Microphone>,
+    ui_state: Res<UiState>,
+    stream_directory: Res<StreamDirectory>,
+) {
+    if !ui_state.is_running {
+        for mut mic in microphones.iter_mut() {
+            if mic.has_pending_samples() {
+                mic.flush_stream(&stream_directory.0);
+            }
+        }
+    }
+}
+
+/// A system used to store the result of a source calibration once its measurement finished
+pub fn calibration_system(mut calibration: ResMut<SourceCalibration>) {
+    if calibration.is_measuring() {
+        calibration.poll();
+    }
+}
@@ -0,0 +1,610 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use bevy::prelude::*;
+
+use super::plugin::ComponentIDs;
+use crate::components::microphone::Microphone;
+use crate::components::source::{Source, SourceType};
+use crate::components::wall::{CircWall, RectWall};
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+
+const CENTER_X: i32 = SIMULATION_WIDTH as i32 / 2;
+const CENTER_Y: i32 = SIMULATION_HEIGHT as i32 / 2;
+/// thickness of barriers and reflectors in cells
+const WALL_THICKNESS: i32 = 4;
+
+/// A curated scene that can be created from the template gallery.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Template {
+    #[default]
+    SingleSlit,
+    DoubleSlit,
+    Young,
+    Waveguide,
+    HelmholtzResonator,
+    ParabolicReflector,
+    CornerReflector,
+    WhisperingGallery,
+    SchroederDiffuser,
+    ShoeboxRoom,
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Template::SingleSlit => write!(f, "Single Slit"),
+            Template::DoubleSlit => write!(f, "Double Slit"),
+            Template::Young => write!(f, "Young's Interference"),
+            Template::Waveguide => write!(f, "Waveguide"),
+            Template::HelmholtzResonator => write!(f, "Helmholtz Resonator"),
+            Template::ParabolicReflector => write!(f, "Parabolic Reflector"),
+            Template::CornerReflector => write!(f, "Corner Reflector"),
+            Template::WhisperingGallery => write!(f, "Whispering Gallery"),
+            Template::SchroederDiffuser => write!(f, "Schroeder Diffuser"),
+            Template::ShoeboxRoom => write!(f, "Shoebox Room"),
+        }
+    }
+}
+
+/// What a [`TemplateParameter`] describes, determines its unit and precision.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParameterKind {
+    /// a distance in cells
+    Length,
+    /// a frequency in Hz
+    Frequency,
+    /// an amount of something
+    Count,
+    /// a unitless factor, e.g. a reflection factor
+    Factor,
+}
+
+/// A parameter of a template that can be changed before the scene is created.
+#[derive(Debug, Clone)]
+pub struct TemplateParameter {
+    pub name: &'static str,
+    pub kind: ParameterKind,
+    pub value: f32,
+    pub range: RangeInclusive<f32>,
+}
+
+impl TemplateParameter {
+    fn new(
+        name: &'static str,
+        kind: ParameterKind,
+        value: f32,
+        range: RangeInclusive<f32>,
+    ) -> Self {
+        Self {
+            name,
+            kind,
+            value,
+            range,
+        }
+    }
+
+    fn length(name: &'static str, value: u32, range: RangeInclusive<u32>) -> Self {
+        Self::new(
+            name,
+            ParameterKind::Length,
+            value as f32,
+            *range.start() as f32..=*range.end() as f32,
+        )
+    }
+
+    fn frequency(value: f32) -> Self {
+        Self::new("Frequency", ParameterKind::Frequency, value, 20.0..=20000.)
+    }
+
+    fn count(name: &'static str, value: u32, range: RangeInclusive<u32>) -> Self {
+        Self::new(
+            name,
+            ParameterKind::Count,
+            value as f32,
+            *range.start() as f32..=*range.end() as f32,
+        )
+    }
+}
+
+impl Template {
+    pub const ALL: [Template; 10] = [
+        Template::SingleSlit,
+        Template::DoubleSlit,
+        Template::Young,
+        Template::Waveguide,
+        Template::HelmholtzResonator,
+        Template::ParabolicReflector,
+        Template::CornerReflector,
+        Template::WhisperingGallery,
+        Template::SchroederDiffuser,
+        Template::ShoeboxRoom,
+    ];
+
+    /// A short explanation of the setup and what can be observed in it.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Template::SingleSlit => {
+                "A plane wave, created by a line of sources, hits a barrier with a single slit. \
+                Behind the slit the wave spreads out. The narrower the slit compared to the \
+                wavelength, the more it acts like a point source."
+            }
+            Template::DoubleSlit => {
+                "A plane wave passes a barrier with two slits. The waves from both slits \
+                interfere and form a pattern of loud and quiet directions behind the barrier. \
+                The angle between the lines of silence shrinks when the slits are moved apart."
+            }
+            Template::Young => {
+                "Young's experiment: a point source illuminates a pinhole, which makes the \
+                wave coherent, before it passes two slits. The microphones behind the second \
+                barrier sample the interference pattern."
+            }
+            Template::Waveguide => {
+                "A source at the entrance of a channel between two parallel walls. Below the \
+                cutoff frequency only a plane wave travels along the channel, above it \
+                higher order modes with a zigzag pattern appear."
+            }
+            Template::HelmholtzResonator => {
+                "A closed cavity connected to the outside through a short neck, excited by an \
+                impulse. The air in the neck oscillates against the air in the cavity at a \
+                single resonance frequency, visible as a peak in the spectrum of the microphone \
+                inside. Larger cavities and longer or narrower necks lower the resonance."
+            }
+            Template::ParabolicReflector => {
+                "A source in the focus of a parabolic reflector. All reflections leave the \
+                reflector in parallel and form a plane wave that stays focused over a long \
+                distance."
+            }
+            Template::CornerReflector => {
+                "Two walls at a right angle with a source on the bisecting line. Every wave \
+                that enters the corner is reflected back in the direction it came from, \
+                which raises the level in front of the corner."
+            }
+            Template::WhisperingGallery => {
+                "A large circular room with a source close to its wall. The sound creeps \
+                along the curved wall and arrives at the opposite side much louder than \
+                in the center of the room."
+            }
+            Template::SchroederDiffuser => {
+                "A quadratic residue diffuser: wells with depths proportional to n² mod N \
+                scatter an incoming wave evenly in all directions. Prime values of N give \
+                the most uniform diffusion. The microphones are placed on a half circle \
+                around the diffuser."
+            }
+            Template::ShoeboxRoom => {
+                "A rectangular room with partially reflecting walls, an impulsive source \
+                and a regular grid of microphones, e.g. to compare impulse responses or to \
+                examine room modes at different positions."
+            }
+        }
+    }
+
+    /// The parameters of the template with their default values.
+    pub fn parameters(&self) -> Vec<TemplateParameter> {
+        match self {
+            Template::SingleSlit => vec![
+                TemplateParameter::length("Slit width", 30, 2..=300),
+                TemplateParameter::length("Barrier position", 200, 50..=600),
+                TemplateParameter::frequency(2000.),
+            ],
+            Template::DoubleSlit => vec![
+                TemplateParameter::length("Slit width", 10, 2..=100),
+                TemplateParameter::length("Slit spacing", 80, 4..=400),
+                TemplateParameter::length("Barrier position", 200, 50..=600),
+                TemplateParameter::frequency(2000.),
+            ],
+            Template::Young => vec![
+                TemplateParameter::length("Pinhole width", 6, 2..=50),
+                TemplateParameter::length("Slit width", 6, 2..=50),
+                TemplateParameter::length("Slit spacing", 60, 4..=300),
+                TemplateParameter::frequency(3000.),
+            ],
+            Template::Waveguide => vec![
+                TemplateParameter::length("Width", 60, 10..=400),
+                TemplateParameter::length("Length", 500, 100..=650),
+                TemplateParameter::frequency(1000.),
+            ],
+            Template::HelmholtzResonator => vec![
+                TemplateParameter::length("Cavity radius", 60, 10..=200),
+                TemplateParameter::length("Neck width", 10, 2..=60),
+                TemplateParameter::length("Neck length", 30, 2..=200),
+            ],
+            Template::ParabolicReflector => vec![
+                TemplateParameter::length("Focal length", 60, 10..=300),
+                TemplateParameter::length("Aperture", 400, 50..=680),
+                TemplateParameter::frequency(3000.),
+            ],
+            Template::CornerReflector => vec![
+                TemplateParameter::length("Wall length", 300, 50..=500),
+                TemplateParameter::length("Source distance", 80, 10..=300),
+                TemplateParameter::frequency(1500.),
+            ],
+            Template::WhisperingGallery => vec![
+                TemplateParameter::length("Radius", 300, 50..=340),
+                TemplateParameter::length("Source distance", 10, 2..=100),
+                TemplateParameter::frequency(3000.),
+            ],
+            Template::SchroederDiffuser => vec![
+                TemplateParameter::count("Wells (N)", 7, 3..=31),
+                TemplateParameter::length("Well width", 12, 4..=40),
+                TemplateParameter::length("Maximum depth", 60, 10..=200),
+                TemplateParameter::frequency(1500.),
+            ],
+            Template::ShoeboxRoom => vec![
+                TemplateParameter::length("Room width", 500, 50..=680),
+                TemplateParameter::length("Room depth", 350, 50..=680),
+                TemplateParameter::new("Reflection factor", ParameterKind::Factor, 0.9, 0.0..=1.),
+                TemplateParameter::count("Mic rows", 3, 1..=10),
+                TemplateParameter::count("Mic columns", 4, 1..=10),
+            ],
+        }
+    }
+
+    /// Creates the components of the template. `parameters` have to be the ones returned
+    /// by [`Template::parameters`], possibly with changed values.
+    pub fn build(&self, parameters: &[TemplateParameter], ids: &mut ComponentIDs) -> TemplateScene {
+        let mut scene = SceneBuilder {
+            scene: TemplateScene::default(),
+            ids,
+        };
+
+        match self {
+            Template::SingleSlit => {
+                let [width, position, frequency] = values(parameters);
+                let (width, position) = (width as i32, position as i32);
+                scene.plane_wave(20, frequency);
+                scene.barrier(position, &[(CENTER_Y, width)]);
+                scene.mic_column(position + 250, 100);
+            }
+            Template::DoubleSlit => {
+                let [width, spacing, position, frequency] = values(parameters);
+                let (width, position) = (width as i32, position as i32);
+                // the slits must not overlap
+                let spacing = (spacing as i32).max(width + 2);
+                scene.plane_wave(20, frequency);
+                scene.barrier(
+                    position,
+                    &[
+                        (CENTER_Y - spacing / 2, width),
+                        (CENTER_Y + spacing - spacing / 2, width),
+                    ],
+                );
+                scene.mic_column(position + 250, 100);
+            }
+            Template::Young => {
+                let [pinhole, width, spacing, frequency] = values(parameters);
+                let width = width as i32;
+                let spacing = (spacing as i32).max(width + 2);
+                scene.sin(60, CENTER_Y, frequency, 10.);
+                scene.barrier(150, &[(CENTER_Y, pinhole as i32)]);
+                scene.barrier(
+                    300,
+                    &[
+                        (CENTER_Y - spacing / 2, width),
+                        (CENTER_Y + spacing - spacing / 2, width),
+                    ],
+                );
+                scene.mic_column(600, 60);
+            }
+            Template::Waveguide => {
+                let [width, length, frequency] = values(parameters);
+                let (width, length) = (width as i32, length as i32);
+                let start = CENTER_X - length / 2;
+                let end = start + length;
+                let top = CENTER_Y - width / 2;
+                let bottom = top + width;
+                scene.wall(start, top - WALL_THICKNESS, end, top - 1);
+                scene.wall(start, bottom + 1, end, bottom + WALL_THICKNESS);
+                scene.sin(start + 10, CENTER_Y, frequency, 10.);
+                for i in 1..=4 {
+                    scene.mic(start + length * i / 4 - 10, CENTER_Y);
+                }
+            }
+            Template::HelmholtzResonator => {
+                let [radius, width, length] = values(parameters);
+                let (radius, width, length) = (radius as i32, width as i32, length as i32);
+                // a neck wider than the cavity would leave no cavity
+                let width = width.min(radius);
+                let (center_x, center_y) = (250, CENTER_Y);
+                // the neck walls start where the circle is closed again
+                let half_opening = width / 2 + 1;
+                let opening = 2.
+                    * (half_opening as f32 / radius as f32)
+                        .min(1.)
+                        .asin()
+                        .to_degrees();
+                scene.circle(center_x, center_y, radius, opening);
+
+                let neck_start = center_x
+                    + ((radius * radius - (half_opening + 2).pow(2)).max(0) as f32).sqrt() as i32
+                    - 1;
+                let neck_end = center_x + radius + length;
+                scene.wall(
+                    neck_start,
+                    center_y - half_opening - 2,
+                    neck_end,
+                    center_y - half_opening,
+                );
+                scene.wall(
+                    neck_start,
+                    center_y + half_opening,
+                    neck_end,
+                    center_y + half_opening + 2,
+                );
+
+                scene.mic(center_x, center_y);
+                scene.mic(neck_end + 20, center_y);
+                scene.source(
+                    neck_end + 150,
+                    center_y,
+                    SourceType::Impulse { amplitude: 10. },
+                );
+            }
+            Template::ParabolicReflector => {
+                let [focal_length, aperture, frequency] = values(parameters);
+                let (focal_length, half_aperture) = (focal_length as i32, aperture as i32 / 2);
+                let vertex = 100;
+                let parabola = |y: i32| vertex + y * y / (4 * focal_length);
+                // the curve is approximated by short wall segments
+                let segment = 4;
+                let mut y = -half_aperture;
+                while y < half_aperture {
+                    let y_end = (y + segment - 1).min(half_aperture);
+                    let x_min = parabola(y).min(parabola(y_end));
+                    let x_max = parabola(y).max(parabola(y_end));
+                    if x_min < SIMULATION_WIDTH as i32 {
+                        scene.wall(
+                            x_min,
+                            CENTER_Y + y,
+                            x_max + WALL_THICKNESS - 1,
+                            CENTER_Y + y_end,
+                        );
+                    }
+                    y += segment;
+                }
+
+                scene.sin(vertex + focal_length, CENTER_Y, frequency, 10.);
+                scene.mic(400, CENTER_Y);
+                scene.mic(600, CENTER_Y);
+                scene.mic(600, CENTER_Y - 150);
+            }
+            Template::CornerReflector => {
+                let [length, distance, frequency] = values(parameters);
+                let (length, distance) = (length as i32, distance as i32);
+                // the inner corner of the two walls
+                let (corner_x, corner_y) = (150, 549);
+                scene.wall(
+                    corner_x - WALL_THICKNESS,
+                    corner_y - length,
+                    corner_x - 1,
+                    corner_y + WALL_THICKNESS,
+                );
+                scene.wall(
+                    corner_x - WALL_THICKNESS,
+                    corner_y + 1,
+                    corner_x + length,
+                    corner_y + WALL_THICKNESS,
+                );
+                scene.sin(corner_x + distance, corner_y - distance, frequency, 10.);
+                scene.mic(corner_x + distance / 2, corner_y - distance / 2);
+                scene.mic(corner_x + 350, corner_y - 350);
+                scene.mic(corner_x + 350, corner_y - 150);
+            }
+            Template::WhisperingGallery => {
+                let [radius, distance, frequency] = values(parameters);
+                let (radius, distance) = (radius as i32, distance as i32);
+                scene.circle(CENTER_X, CENTER_Y, radius, 0.);
+                scene.sin(CENTER_X, CENTER_Y - radius + distance, frequency, 10.);
+                scene.mic(CENTER_X, CENTER_Y + radius - distance);
+                scene.mic(CENTER_X + radius - distance, CENTER_Y);
+                scene.mic(CENTER_X, CENTER_Y);
+            }
+            Template::SchroederDiffuser => {
+                let [wells, well_width, max_depth, frequency] = values(parameters);
+                let (wells, max_depth) = (wells as i32, max_depth as i32);
+                let fin = 2;
+                // the diffuser has to fit into the simulation area
+                let well_width =
+                    (well_width as i32).min((SIMULATION_WIDTH as i32 - 20) / wells - fin);
+                let period = well_width + fin;
+                let left = CENTER_X - (wells * period + fin) / 2;
+                let back = 620;
+                let surface = back - max_depth;
+
+                scene.wall(
+                    left,
+                    back,
+                    left + wells * period + fin - 1,
+                    back + WALL_THICKNESS + 3,
+                );
+                for n in 0..=wells {
+                    let x = left + n * period;
+                    scene.wall(x, surface, x + fin - 1, back - 1);
+                }
+                for n in 0..wells {
+                    let depth = (n * n % wells) * max_depth / (wells - 1).max(1);
+                    if depth < max_depth {
+                        let x = left + n * period + fin;
+                        scene.wall(x, surface + depth, x + well_width - 1, back - 1);
+                    }
+                }
+
+                scene.sin(CENTER_X, (surface - 250).max(10), frequency, 10.);
+                for i in 0..7 {
+                    let angle = (15. + 25. * i as f32).to_radians();
+                    scene.mic(
+                        CENTER_X + (200. * angle.cos()) as i32,
+                        surface - (200. * angle.sin()) as i32,
+                    );
+                }
+            }
+            Template::ShoeboxRoom => {
+                let [width, depth, reflection_factor, rows, columns] = values(parameters);
+                let (width, depth) = (width as i32, depth as i32);
+                let (rows, columns) = (rows as i32, columns as i32);
+                let left = CENTER_X - width / 2;
+                let top = CENTER_Y - depth / 2;
+                scene.room(left, top, left + width, top + depth, reflection_factor);
+                scene.source(
+                    left + width / 5,
+                    top + depth / 3,
+                    SourceType::Impulse { amplitude: 10. },
+                );
+                for row in 1..=rows {
+                    for column in 1..=columns {
+                        scene.mic(
+                            left + column * width / (columns + 1),
+                            top + row * depth / (rows + 1),
+                        );
+                    }
+                }
+            }
+        }
+
+        scene.scene
+    }
+}
+
+/// Collects the values of the first `N` parameters.
+fn values<const N: usize>(parameters: &[TemplateParameter]) -> [f32; N] {
+    std::array::from_fn(|i| parameters[i].value)
+}
+
+/// The components of a scene created from a [`Template`].
+#[derive(Default)]
+pub struct TemplateScene {
+    pub sources: Vec<Source>,
+    pub mics: Vec<Microphone>,
+    pub rect_walls: Vec<RectWall>,
+    pub circ_walls: Vec<CircWall>,
+}
+
+impl TemplateScene {
+    pub fn spawn(self, commands: &mut Commands) {
+        for source in self.sources {
+            commands.spawn(source);
+        }
+        for mic in self.mics {
+            commands.spawn(mic);
+        }
+        for wall in self.rect_walls {
+            commands.spawn(wall);
+        }
+        for wall in self.circ_walls {
+            commands.spawn(wall);
+        }
+    }
+}
+
+/// Helper to create the components of a template with valid ids.
+/// Positions are given relative to the simulation area and moved to its edge if they are outside.
+struct SceneBuilder<'a> {
+    scene: TemplateScene,
+    ids: &'a mut ComponentIDs,
+}
+
+impl SceneBuilder<'_> {
+    fn source(&mut self, x: i32, y: i32, source_type: SourceType) {
+        let id = self.ids.get_new_source_id();
+        self.scene
+            .sources
+            .push(Source::new(clamp_x(x), clamp_y(y), source_type, id));
+    }
+
+    fn sin(&mut self, x: i32, y: i32, frequency: f32, amplitude: f32) {
+        self.source(
+            x,
+            y,
+            SourceType::Sin {
+                phase: 0.,
+                frequency,
+                amplitude,
+            },
+        );
+    }
+
+    /// A line of sources over the full height of the simulation area, which emits a plane wave.
+    fn plane_wave(&mut self, x: i32, frequency: f32) {
+        // the spacing is far below half a wavelength, so the line behaves like a continuous source
+        for y in (0..SIMULATION_HEIGHT as i32).step_by(4) {
+            self.sin(x, y, frequency, 1.);
+        }
+    }
+
+    fn mic(&mut self, x: i32, y: i32) {
+        let id = self.ids.get_new_mic_id();
+        self.scene
+            .mics
+            .push(Microphone::new(clamp_x(x), clamp_y(y), id));
+    }
+
+    /// Five microphones on a vertical line through the center, `spacing` cells apart.
+    fn mic_column(&mut self, x: i32, spacing: i32) {
+        for i in -2..=2 {
+            self.mic(x, CENTER_Y + i * spacing);
+        }
+    }
+
+    /// A solid, fully reflecting wall between the two corners (inclusive).
+    fn wall(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        self.rect_wall(x0, y0, x1, y1, false, 1.);
+    }
+
+    /// A hollow rectangular room between the two corners (inclusive).
+    fn room(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, reflection_factor: f32) {
+        self.rect_wall(x0, y0, x1, y1, true, reflection_factor);
+    }
+
+    fn rect_wall(
+        &mut self,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        is_hollow: bool,
+        reflection_factor: f32,
+    ) {
+        let id = self.ids.get_new_wall_id();
+        self.scene.rect_walls.push(RectWall::new(
+            clamp_x(x0.min(x1)),
+            clamp_y(y0.min(y1)),
+            clamp_x(x0.max(x1)),
+            clamp_y(y0.max(y1)),
+            is_hollow,
+            reflection_factor,
+            id,
+        ));
+    }
+
+    /// A hollow, fully reflecting circle with an opening of `open_segment` degrees to the right.
+    fn circle(&mut self, x: i32, y: i32, radius: i32, open_segment: f32) {
+        let id = self.ids.get_new_wall_id();
+        let mut wall = CircWall::new(clamp_x(x), clamp_y(y), radius.max(1) as u32, true, 1., id);
+        wall.open_circ_segment = open_segment;
+        self.scene.circ_walls.push(wall);
+    }
+
+    /// A vertical barrier over the full height of the simulation area at `x`
+    /// with openings given as (center, width).
+    fn barrier(&mut self, x: i32, openings: &[(i32, i32)]) {
+        let mut top = 0;
+        for &(center, width) in openings {
+            let opening_start = center - width / 2;
+            if opening_start > top {
+                self.wall(x, top, x + WALL_THICKNESS - 1, opening_start - 1);
+            }
+            top = opening_start + width;
+        }
+        if top < SIMULATION_HEIGHT as i32 {
+            self.wall(x, top, x + WALL_THICKNESS - 1, SIMULATION_HEIGHT as i32 - 1);
+        }
+    }
+}
+
+fn clamp_x(x: i32) -> u32 {
+    x.clamp(0, SIMULATION_WIDTH as i32 - 1) as u32
+}
+
+fn clamp_y(y: i32) -> u32 {
+    y.clamp(0, SIMULATION_HEIGHT as i32 - 1) as u32
+}
@@ -0,0 +1,132 @@
+//! Checks of the scene input of scripts, the Python bindings and remote clients.
+//!
+//! The bindings only convert [`InputError`] into their own error type,
+//! so every entry point rejects the same input with the same message.
+
+use std::fmt;
+
+use bevy::math::UVec2;
+
+use crate::components::source::SourceType;
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+
+/// Why a value of a script, the Python bindings or a remote client was rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputError {
+    /// a position outside of the simulation area
+    Position { x: i64, y: i64 },
+    /// a radius that is not positive or larger than the simulation area
+    Radius(i64),
+    /// a decimation factor that is not positive
+    Decimation(i64),
+    /// a reflection factor outside of `0..=1`
+    ReflectionFactor(f32),
+    /// the name of an unknown source type
+    SourceType(String),
+    /// a parameter the source type does not have
+    Parameter { source_type: String, name: String },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Position { x, y } => write!(
+                f,
+                "Position ({x}, {y}) is outside of the simulation area \
+                ({SIMULATION_WIDTH} x {SIMULATION_HEIGHT} cells)"
+            ),
+            InputError::Radius(radius) => write!(
+                f,
+                "Invalid radius: {radius}, expected a value between 1 and {MAX_RADIUS}"
+            ),
+            InputError::Decimation(decimation) => write!(
+                f,
+                "Invalid decimation: {decimation}, expected a positive value"
+            ),
+            InputError::ReflectionFactor(reflection_factor) => write!(
+                f,
+                "Invalid reflection factor: {reflection_factor}, expected a value between 0 and 1"
+            ),
+            InputError::SourceType(name) => write!(
+                f,
+                "Unknown source type: {name}, expected sin, gauss, noise, impulse or sweep"
+            ),
+            InputError::Parameter { source_type, name } => {
+                write!(f, "A {source_type} source has no parameter {name}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Checks that a position lies inside the simulation area.
+pub fn position(x: i64, y: i64) -> Result<UVec2, InputError> {
+    if (0..SIMULATION_WIDTH as i64).contains(&x) && (0..SIMULATION_HEIGHT as i64).contains(&y) {
+        Ok(UVec2::new(x as u32, y as u32))
+    } else {
+        Err(InputError::Position { x, y })
+    }
+}
+
+/// The minimum and maximum corner of a rectangle given by two arbitrary corners.
+pub fn corners(x0: i64, y0: i64, x1: i64, y1: i64) -> Result<(UVec2, UVec2), InputError> {
+    let a = position(x0, y0)?;
+    let b = position(x1, y1)?;
+    Ok((a.min(b), a.max(b)))
+}
+
+/// The largest radius of a circular wall in cells.
+/// Larger circles lie almost completely outside of the simulation area,
+/// while their cells would still have to be drawn when the walls are updated.
+pub const MAX_RADIUS: u32 = if SIMULATION_WIDTH > SIMULATION_HEIGHT {
+    SIMULATION_WIDTH
+} else {
+    SIMULATION_HEIGHT
+};
+
+pub fn radius(radius: i64) -> Result<u32, InputError> {
+    if (1..=MAX_RADIUS as i64).contains(&radius) {
+        Ok(radius as u32)
+    } else {
+        Err(InputError::Radius(radius))
+    }
+}
+
+pub fn decimation(decimation: i64) -> Result<u32, InputError> {
+    if (1..=u32::MAX as i64).contains(&decimation) {
+        Ok(decimation as u32)
+    } else {
+        Err(InputError::Decimation(decimation))
+    }
+}
+
+pub fn reflection_factor(reflection_factor: f32) -> Result<f32, InputError> {
+    if (0. ..=1.).contains(&reflection_factor) {
+        Ok(reflection_factor)
+    } else {
+        Err(InputError::ReflectionFactor(reflection_factor))
+    }
+}
+
+/// The default source type for a short name, see [`SourceType::from_name`].
+pub fn source_type(name: &str) -> Result<SourceType, InputError> {
+    SourceType::from_name(name).ok_or_else(|| InputError::SourceType(name.to_string()))
+}
+
+/// Changes a parameter of a source type by its field name, see [`SourceType::parameter_mut`].
+pub fn set_parameter(
+    source_type: &mut SourceType,
+    name: &str,
+    value: f32,
+) -> Result<(), InputError> {
+    let kind = source_type.to_string();
+    let parameter = source_type
+        .parameter_mut(name)
+        .ok_or_else(|| InputError::Parameter {
+            source_type: kind,
+            name: name.to_string(),
+        })?;
+    *parameter = value;
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::{Color32, Vec2};
+
+use super::state::UiState;
+use crate::render::animation::{AnimationFormat, AnimationRecorder};
+use crate::simulation::grid::Grid;
+
+/// Draws the window to record the simulation as a PNG sequence or an animated GIF.
+pub fn draw_animation_export(
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut recorder: ResMut<AnimationRecorder>,
+    grid: Res<Grid>,
+) {
+    if !ui_state.show_animation_export {
+        return;
+    }
+
+    let mut show_animation_export = ui_state.show_animation_export;
+
+    egui::Window::new("Record Animation")
+        .open(&mut show_animation_export)
+        .default_size(Vec2::new(400., 400.))
+        .resizable(false)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            let is_recording = recorder.is_recording();
+
+            ui.add_enabled_ui(!is_recording, |ui| {
+                egui::ComboBox::from_label("Format")
+                    .selected_text(recorder.format.to_string())
+                    .show_ui(ui, |ui| {
+                        for format in [AnimationFormat::PngSequence, AnimationFormat::Gif] {
+                            if ui
+                                .selectable_value(&mut recorder.format, format, format.to_string())
+                                .changed()
+                            {
+                                recorder.path = PathBuf::from(match format {
+                                    AnimationFormat::PngSequence => "animation",
+                                    AnimationFormat::Gif => "animation.gif",
+                                });
+                            }
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.label(match recorder.format {
+                        AnimationFormat::PngSequence => "Directory",
+                        AnimationFormat::Gif => "File",
+                    });
+                    let mut path = recorder.path.to_string_lossy().into_owned();
+                    if ui.text_edit_singleline(&mut path).changed() {
+                        recorder.path = PathBuf::from(path);
+                    }
+                });
+
+                ui.separator();
+
+                ui.add(
+                    egui::DragValue::new(&mut recorder.step_interval)
+                        .clamp_range(1..=10000)
+                        .prefix("Every ")
+                        .suffix(". step"),
+                )
+                .on_hover_text("Capture only every n-th simulation step");
+                ui.label(format!(
+                    "{:.3} ms of simulated time per frame",
+                    recorder.step_interval as f32 * grid.delta_t * 1000.
+                ));
+                ui.add(
+                    egui::DragValue::new(&mut recorder.resolution)
+                        .clamp_range(100..=4096)
+                        .prefix("Resolution: ")
+                        .suffix(" px"),
+                )
+                .on_hover_text("Width and height of the simulation area in the frames");
+                if recorder.format == AnimationFormat::Gif {
+                    ui.add(
+                        egui::DragValue::new(&mut recorder.frame_rate)
+                            .clamp_range(1..=100)
+                            .prefix("Playback: ")
+                            .suffix(" fps"),
+                    );
+                }
+
+                ui.checkbox(&mut recorder.show_overlays, "Outlines")
+                    .on_hover_text("Outline the sources, microphones, probes and walls");
+                ui.checkbox(&mut recorder.show_color_bar, "Color bar");
+                ui.checkbox(&mut recorder.show_time, "Time stamp");
+
+                ui.horizontal(|ui| {
+                    let mut limited = recorder.max_frames.is_some();
+                    if ui.checkbox(&mut limited, "Stop after").changed() {
+                        recorder.max_frames = limited.then_some(100);
+                    }
+                    if let Some(frames) = recorder.max_frames.as_mut() {
+                        ui.add(
+                            egui::DragValue::new(frames)
+                                .clamp_range(1..=100000)
+                                .suffix(" frames"),
+                        );
+                    }
+                });
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if is_recording {
+                    if ui.button("Stop").clicked() {
+                        recorder.stop();
+                    }
+                } else if ui
+                    .button("Record")
+                    .on_hover_text("Capture frames while the simulation is running")
+                    .clicked()
+                {
+                    if let Err(err) = recorder.start() {
+                        recorder.error = Some(err.to_string());
+                    }
+                }
+
+                if is_recording {
+                    let text = format!("{} frames", recorder.frames());
+                    match recorder.max_frames {
+                        Some(max) => {
+                            ui.add(
+                                egui::ProgressBar::new(recorder.frames() as f32 / max as f32)
+                                    .text(text),
+                            );
+                        }
+                        None => {
+                            ui.label(text);
+                        }
+                    }
+                } else if recorder.is_writing() {
+                    ui.spinner();
+                    ui.label(format!("Writing {} frames", recorder.frames()));
+                } else if recorder.frames() > 0 {
+                    ui.label(format!(
+                        "{} frames written to {}",
+                        recorder.frames(),
+                        recorder.path.display()
+                    ));
+                }
+            });
+
+            if let Some(error) = &recorder.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        });
+
+    ui_state.show_animation_export = show_animation_export;
+}
@@ -10,15 +10,28 @@ use super::help::draw_help;
 use super::preferences::draw_preferences;
 use super::tabs::{DockState, PlotTabs};
 use crate::components::gizmo::GizmoComponent;
+use crate::components::mic_array::MicArray;
 use crate::components::microphone::*;
+use crate::components::probe::{AreaProbe, LineProbe};
 use crate::components::source::*;
 use crate::components::states::{MenuSelected, Selected};
 use crate::components::wall::{CircWall, RectWall, WResize};
 use crate::events::{Load, New, Reset, Save, UpdateWalls};
 use crate::math::constants::*;
+use crate::math::dispersion::{
+    cells_per_wavelength, cutoff_frequency, max_reliable_frequency, nyquist_frequency, Resolution,
+    DISPERSION_ERROR_THRESHOLD,
+};
+use crate::math::units::{PressureUnits, SourceLevelUnit};
+use crate::remote::osc::OscControl;
+use crate::remote::plugin::RemoteControl;
 use crate::render::gradient::Gradient;
-use crate::render::screenshot::screenshot_grid;
+use crate::render::screenshot::export_field_map;
+use crate::simulation::calibration::SourceCalibration;
+use crate::simulation::field_map::FieldMap;
 use crate::simulation::grid::Grid;
+use crate::simulation::steady_state::SteadyStateMap;
+use crate::simulation::validation::MAX_RADIUS;
 use crate::ui::state::*;
 use crate::undo::{UndoEvent, UndoRedo};
 
@@ -106,6 +119,18 @@ pub struct QuerySystemParams<'w, 's> {
             AllMics<'w, 's>,
         ),
     >,
+    mic_arrays: Query<'w, 's, (Entity, &'static mut MicArray)>,
+    ir_measurement: ResMut<'w, IrMeasurement>,
+    source_records: Res<'w, SourceRecords>,
+    transfer_function: ResMut<'w, TransferFunctionSettings>,
+    field_map: Res<'w, FieldMap>,
+    steady_state: Res<'w, SteadyStateMap>,
+    line_probes: Query<'w, 's, &'static LineProbe>,
+    area_probes: Query<'w, 's, &'static AreaProbe>,
+    remote: ResMut<'w, RemoteControl>,
+    osc: ResMut<'w, OscControl>,
+    calibration: ResMut<'w, SourceCalibration>,
+    stream_directory: ResMut<'w, StreamDirectory>,
 }
 
 pub const CTRL_KEY_TEXT: &str = if cfg!(target_os = "macos") {
@@ -145,6 +170,18 @@ pub fn draw_egui(
         mut circ_wall_set,
         mut source_set,
         mut mic_set,
+        mut mic_arrays,
+        mut ir_measurement,
+        source_records,
+        mut transfer_function,
+        field_map,
+        steady_state,
+        line_probes,
+        area_probes,
+        mut remote,
+        mut osc,
+        mut calibration,
+        mut stream_directory,
     } = sets;
 
     let ctx = egui_context.ctx_mut();
@@ -158,6 +195,12 @@ pub fn draw_egui(
 
     if ui_state.show_preferences {
         let mut show_preferences = ui_state.show_preferences;
+        let mut source_ids = source_set
+            .p3()
+            .iter()
+            .map(|source| source.id)
+            .collect::<Vec<_>>();
+        source_ids.sort();
 
         draw_preferences(
             &mut show_preferences,
@@ -167,6 +210,10 @@ pub fn draw_egui(
             &mut grid,
             &mut pixel_buffers,
             &mut gradient,
+            &mut remote,
+            &mut osc,
+            &mut stream_directory,
+            &source_ids,
         );
 
         ui_state.show_preferences = show_preferences;
@@ -234,7 +281,10 @@ pub fn draw_egui(
                 ui.horizontal(|ui| {
                     if ui.button("Save").clicked() {
                         show_new_warning = false;
-                        events.save_ev.send(Save { new_file: true });
+                        events.save_ev.send(Save {
+                            new_file: true,
+                            checkpoint: false,
+                        });
                     }
                     if ui.button("Don't save").clicked() {
                         show_new_warning = false;
@@ -272,32 +322,97 @@ pub fn draw_egui(
                         ui_state.show_new_warning = true;
                     }
 
+                    if ui
+                        .button("New from Template")
+                        .on_hover_text("Create a new simulation from a predefined scene")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        ui_state.show_template_gallery = true;
+                    }
+
                     if ui
                         .add(egui::Button::new("Save").shortcut_text(format!("{CTRL_KEY_TEXT}+S")))
                         .on_hover_text("Save the current state of the simulation")
                         .clicked()
                     {
                         ui.close_menu();
-                        events.save_ev.send(Save { new_file: false });
+                        events.save_ev.send(Save {
+                            new_file: false,
+                            checkpoint: false,
+                        });
+                    }
+
+                    if ui
+                        .button("Save Checkpoint")
+                        .on_hover_text(
+                            "Save the scene together with the current state of the simulation to continue it later",
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        events.save_ev.send(Save {
+                            new_file: false,
+                            checkpoint: true,
+                        });
                     }
 
                     if ui
                         .add(egui::Button::new("Open").shortcut_text(format!("{CTRL_KEY_TEXT}+O")))
-                        .on_hover_text("Open a previously saved state of the simulation")
+                        .on_hover_text("Open a previously saved scene or checkpoint")
                         .clicked()
                     {
                         ui.close_menu();
                         events.load_ev.send(Load);
                     }
 
+                    if ui
+                        .button("Export Microphones")
+                        .on_hover_text("Export microphone recordings as WAV files")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        ui_state.show_wav_export = true;
+                    }
+
+                    if ui
+                        .button("Record Field")
+                        .on_hover_text("Record the pressure field to a NumPy file")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        ui_state.show_field_recorder = true;
+                    }
+
+                    if ui
+                        .button("Record Animation")
+                        .on_hover_text("Record the simulation as a PNG sequence or an animated GIF")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        ui_state.show_animation_export = true;
+                    }
+
                     if ui
                         .button("Screenshot")
-                        .on_hover_text("Save a screenshot of the simulation")
+                        .on_hover_text("Save a screenshot of the simulation as PNG or SVG")
                         .clicked()
                     {
                         ui.close_menu();
+                        ui_state.show_screenshot_export = true;
+                    }
 
-                        screenshot_grid(&ui_state, &grid, &gradient, &mut commands)
+                    if ui
+                        .add_enabled(
+                            ui_state.render_mode.is_field_map(),
+                            egui::Button::new("Export Field Map"),
+                        )
+                        .on_hover_text("Save the levels of the current field map to a CSV file")
+                        .clicked()
+                    {
+                        ui.close_menu();
+
+                        export_field_map(&ui_state, &field_map, &mut commands)
                     }
 
                     if ui
@@ -333,6 +448,41 @@ pub fn draw_egui(
                     }
                 });
 
+                ui.menu_button("Analysis", |ui| {
+                    if ui
+                        .button("Steady State")
+                        .on_hover_text("Choose the frequencies of the steady state maps")
+                        .clicked()
+                    {
+                        ui_state.show_steady_state = true;
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Room Modes")
+                        .on_hover_text("Detect the modes of a closed room")
+                        .clicked()
+                    {
+                        ui_state.show_room_modes = true;
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Probes")
+                        .on_hover_text("Sample the pressure along lines and over areas")
+                        .clicked()
+                    {
+                        ui_state.show_probes = true;
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button("Script Console")
+                        .on_hover_text("Build scenes and run the simulation with Rhai scripts")
+                        .clicked()
+                    {
+                        ui_state.show_script_console = true;
+                        ui.close_menu();
+                    }
+                });
+
                 ui.menu_button("Help", |ui| {
                     if ui.button("Keybinds").clicked() {
                         ui_state.show_help = true;
@@ -432,6 +582,16 @@ pub fn draw_egui(
                                             SourceType::default_noise(),
                                             "White Noise",
                                         );
+                                        ui.selectable_value(
+                                            &mut source.source_type,
+                                            SourceType::default_impulse(),
+                                            "Impulse",
+                                        );
+                                        ui.selectable_value(
+                                            &mut source.source_type,
+                                            SourceType::default_sweep(),
+                                            "Sine Sweep",
+                                        );
                                     });
 
                                 match &mut source.source_type {
@@ -513,7 +673,54 @@ pub fn draw_egui(
                                             events.reset_ev.send(Reset::default());
                                         }
                                     }
-                                    SourceType::WhiteNoise { amplitude } => {
+                                    SourceType::WhiteNoise { amplitude }
+                                    | SourceType::Impulse { amplitude } => {
+                                        if ui
+                                            .add(
+                                                egui::Slider::new(amplitude, 0.0..=25.0)
+                                                    .text("Amplitude"),
+                                            )
+                                            .changed()
+                                        {
+                                            events.reset_ev.send(Reset::default());
+                                        }
+                                    }
+                                    SourceType::Sweep {
+                                        amplitude,
+                                        start_frequency,
+                                        end_frequency,
+                                        duration,
+                                    } => {
+                                        if ui
+                                            .add(
+                                                egui::Slider::new(start_frequency, 20.0..=20000.0)
+                                                    .logarithmic(true)
+                                                    .text("Start Frequency (Hz)"),
+                                            )
+                                            .changed()
+                                        {
+                                            events.reset_ev.send(Reset::default());
+                                        }
+                                        if ui
+                                            .add(
+                                                egui::Slider::new(end_frequency, 20.0..=20000.0)
+                                                    .logarithmic(true)
+                                                    .text("End Frequency (Hz)"),
+                                            )
+                                            .changed()
+                                        {
+                                            events.reset_ev.send(Reset::default());
+                                        }
+                                        if ui
+                                            .add(
+                                                egui::Slider::new(duration, 0.001..=1.0)
+                                                    .logarithmic(true)
+                                                    .text("Duration (s)"),
+                                            )
+                                            .changed()
+                                        {
+                                            events.reset_ev.send(Reset::default());
+                                        }
                                         if ui
                                             .add(
                                                 egui::Slider::new(amplitude, 0.0..=25.0)
@@ -526,6 +733,20 @@ pub fn draw_egui(
                                     }
                                 }
 
+                                draw_source_resolution(ui, &source.source_type, ui_state.delta_l);
+
+                                if ui_state.pressure_units == PressureUnits::Pascal
+                                    && draw_source_level(
+                                        ui,
+                                        source.id,
+                                        &mut source.source_type,
+                                        &mut calibration,
+                                        ui_state.delta_l,
+                                    )
+                                {
+                                    events.reset_ev.send(Reset::default());
+                                }
+
                                 if ui
                                     .add(egui::Button::new("Delete").fill(Color32::DARK_RED))
                                     .clicked()
@@ -584,6 +805,47 @@ pub fn draw_egui(
                                                 .clamp_range(0.0..=SIMULATION_HEIGHT as f32 - 1.),
                                         );
                                     });
+                                    ui.checkbox(&mut mic.armed, "Armed").on_hover_text(
+                                        "Record this microphone while recording is enabled",
+                                    );
+                                    ui.label(format!(
+                                        "Recorded: {:.3} ms ({} samples)",
+                                        mic.recorded_duration() * 1000.,
+                                        mic.record.len()
+                                    ));
+                                    ui.label(match ui_state.recording_policy {
+                                        RecordingPolicy::Unbounded => {
+                                            "Recording policy: Unbounded".to_string()
+                                        }
+                                        RecordingPolicy::RingBuffer { seconds }
+                                        | RecordingPolicy::Decimated { seconds, .. }
+                                        | RecordingPolicy::StreamToDisk { seconds, .. } => format!(
+                                            "Recording policy: {} (last {} s)",
+                                            ui_state.recording_policy, seconds
+                                        ),
+                                    });
+                                    if mic.streamed_samples() > 0 {
+                                        ui.label(format!(
+                                            "Streamed: {} samples to {}",
+                                            mic.streamed_samples(),
+                                            mic.stream_path(&stream_directory.0).display()
+                                        ));
+                                    }
+                                    if let Some(error) = mic.stream_error() {
+                                        ui.colored_label(
+                                            Color32::RED,
+                                            format!("Could not stream: {error}"),
+                                        );
+                                    }
+                                    if mic.dropped_samples() > 0 {
+                                        ui.colored_label(
+                                            Color32::RED,
+                                            format!(
+                                                "Dropped: {} samples that could not be streamed",
+                                                mic.dropped_samples()
+                                            ),
+                                        );
+                                    }
                                     if ui
                                         .add(egui::Button::new("Delete").fill(Color32::DARK_RED))
                                         .clicked()
@@ -595,7 +857,10 @@ pub fn draw_egui(
                                         .clicked()
                                     {
                                         let id = mic.id;
-                                        mic.write_to_file(&format!("mic_{}.csv", id));
+                                        mic.write_to_file(
+                                            &format!("mic_{}.csv", id),
+                                            ui_state.pressure_units,
+                                        );
                                     }
                                 });
                         if collapse.header_response.contains_pointer()
@@ -819,7 +1084,7 @@ pub fn draw_egui(
                                             .add(
                                                 egui::DragValue::new(&mut wall.radius)
                                                     .speed(1)
-                                                    .clamp_range(1..=1000),
+                                                    .clamp_range(1..=MAX_RADIUS),
                                             )
                                             .changed()
                                         {
@@ -919,6 +1184,18 @@ pub fn draw_egui(
                         ui_state.is_running = !ui_state.is_running;
                     }
 
+                    if ui
+                        .button(if ui_state.is_recording {
+                            "Pause Recording"
+                        } else {
+                            "Record"
+                        })
+                        .on_hover_text("Start or pause recording of all armed microphones")
+                        .clicked()
+                    {
+                        ui_state.is_recording = !ui_state.is_recording;
+                    }
+
                     if ui.button("Reset").clicked() {
                         events.reset_ev.send(Reset { force: true });
                     }
@@ -946,14 +1223,7 @@ pub fn draw_egui(
 
                     ui.checkbox(&mut ui_state.reset_on_change, "Reset on change");
 
-                    if ui
-                        .checkbox(&mut ui_state.show_plots, "Show Plots")
-                        .clicked()
-                    {
-                        for (_, mut mic) in mic_set.p0().iter_mut() {
-                            mic.clear();
-                        }
-                    }
+                    ui.checkbox(&mut ui_state.show_plots, "Show Plots");
                 });
 
                 ui.add_space(5.);
@@ -981,6 +1251,53 @@ pub fn draw_egui(
 
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut ui_state.hide_gizmos, "Always hide gizmos");
+
+                    ui.add(egui::Separator::default().vertical());
+                    egui::ComboBox::from_label("Render Mode")
+                        .selected_text(ui_state.render_mode.to_string())
+                        .show_ui(ui, |ui| {
+                            for mode in [
+                                RenderMode::Pressure,
+                                RenderMode::Rms,
+                                RenderMode::Peak,
+                                RenderMode::Leq,
+                                RenderMode::Magnitude,
+                                RenderMode::Phase,
+                            ] {
+                                ui.selectable_value(
+                                    &mut ui_state.render_mode,
+                                    mode,
+                                    mode.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Show the instantaneous pressure or a level accumulated since the last reset",
+                        );
+
+                    if ui_state.render_mode.is_steady_state() && ui.button("Frequencies").clicked() {
+                        ui_state.show_steady_state = true;
+                    }
+
+                    ui.add(egui::Separator::default().vertical());
+                    egui::ComboBox::from_label("Vectors")
+                        .selected_text(ui_state.vector_overlay.to_string())
+                        .show_ui(ui, |ui| {
+                            for overlay in [
+                                VectorOverlay::None,
+                                VectorOverlay::Velocity,
+                                VectorOverlay::Intensity,
+                            ] {
+                                ui.selectable_value(
+                                    &mut ui_state.vector_overlay,
+                                    overlay,
+                                    overlay.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("Draw the particle velocity or the time averaged intensity as arrows");
                 });
 
                 ui.add_space(5.);
@@ -991,6 +1308,28 @@ pub fn draw_egui(
                         sim_time.time_since_start * 1000.
                     ));
 
+                    ui.add(egui::Separator::default().vertical());
+                    let max_frequency = max_reliable_frequency(ui_state.delta_l);
+                    let exceeded = source_set.p3().iter().any(|source| {
+                        source
+                            .source_type
+                            .highest_frequency()
+                            .is_some_and(|frequency| frequency > max_frequency)
+                    });
+                    ui.colored_label(
+                        if exceeded {
+                            Color32::YELLOW
+                        } else {
+                            ui.visuals().text_color()
+                        },
+                        format!("Max reliable frequency: {max_frequency:.0} Hz"),
+                    )
+                    .on_hover_text(format!(
+                        "Waves above this frequency are more than {:.0} % too slow because of numerical dispersion. \
+                        Decrease Delta L in the preferences to simulate higher frequencies.",
+                        DISPERSION_ERROR_THRESHOLD * 100.
+                    ));
+
                     ui.add(egui::Separator::default().vertical());
                     ui.label(format!(
                         "FPS: {:.1}",
@@ -1087,7 +1426,21 @@ pub fn draw_egui(
                     .collect::<Vec<_>>();
                 mics.sort_by_cached_key(|mic| mic.id);
 
+                let mut mic_arrays = mic_arrays
+                    .iter_mut()
+                    .map(|(entity, mic_array)| (entity, mic_array.into_inner()))
+                    .collect::<Vec<_>>();
+                mic_arrays.sort_by_cached_key(|(_, mic_array)| mic_array.id);
+
+                let mut source_binding = source_set.p0();
+                let mut sources = source_binding
+                    .iter_mut()
+                    .map(|(_, source)| source.into_inner())
+                    .collect::<Vec<_>>();
+                sources.sort_by_cached_key(|source| source.id);
+
                 let mut pb = pixel_buffers.iter_mut().nth(1).expect("two pixel buffers");
+                let sample_interval = ui_state.recording_policy.sample_interval(grid.delta_t);
 
                 let mut style = egui_dock::Style::from_egui(ui.style());
                 style.tab_bar.bg_fill = Color32::from_rgb(27, 27, 27);
@@ -1100,9 +1453,14 @@ pub fn draw_egui(
                         ui,
                         &mut PlotTabs::new(
                             &mut mics,
+                            &mut mic_arrays,
+                            &mut sources,
+                            &mut ir_measurement,
+                            &source_records,
+                            &mut transfer_function,
                             &mut pb,
                             &mut commands.reborrow(),
-                            grid.delta_t,
+                            sample_interval,
                             sim_time.time_since_start as f64,
                             time.delta_seconds_f64(),
                             &mut ui_state,
@@ -1240,6 +1598,34 @@ pub fn draw_egui(
 
             ui_state.image_rect = image.rect;
 
+            if ui_state.render_mode != RenderMode::Pressure
+                || ui_state.pressure_units == PressureUnits::Pascal
+            {
+                let bar = egui::Rect::from_min_size(
+                    image.rect.right_top() + Vec2::new(-30., 10.),
+                    Vec2::new(15., (image.rect.height() / 3.).max(50.)),
+                );
+                let (min, max, unit) = ui_state.color_scale();
+                gradient.draw_color_bar(ui.painter(), bar, min, max, unit);
+            }
+
+            if ui_state.render_mode.is_steady_state() {
+                if let Some(bin) = steady_state.selected_bin() {
+                    let (text, color) = if bin.is_converged() {
+                        ("converged", Color32::GREEN)
+                    } else {
+                        ("settling", Color32::YELLOW)
+                    };
+                    ui.painter().text(
+                        image.rect.left_top() + Vec2::new(10., 10.),
+                        egui::Align2::LEFT_TOP,
+                        format!("{} Hz: {}", bin.frequency, text),
+                        egui::FontId::monospace(14.),
+                        color,
+                    );
+                }
+            }
+
             // Gizmos
 
             if !ui_state.render_abc_area && !ui_state.hide_gizmos {
@@ -1392,8 +1778,159 @@ pub fn draw_egui(
                         );
                     }
                 }
+
+                // probes
+                for probe in line_probes.iter() {
+                    probe.draw_gizmo(
+                        painter,
+                        &ui_state.current_tool,
+                        false,
+                        &ui_state.image_rect,
+                        Some(&format!("L{}", probe.id)),
+                        ui_state.delta_l,
+                        *gradient,
+                    );
+                }
+                for probe in area_probes.iter() {
+                    probe.draw_gizmo(
+                        painter,
+                        &ui_state.current_tool,
+                        false,
+                        &ui_state.image_rect,
+                        Some(&format!("A{}", probe.id)),
+                        ui_state.delta_l,
+                        *gradient,
+                    );
+                }
+
+                // recording indicators
+                let is_recording = ui_state.is_recording && ui_state.is_running;
+                for (_, mic) in mic_set.p2().iter() {
+                    mic.draw_recording_indicator(painter, &ui_state.image_rect, is_recording);
+                }
             }
         });
 
     ui_state.collapse_header = false;
 }
+
+/// Shows how many cells a wavelength of the source spans and warns if the grid is too coarse
+/// to simulate its frequencies accurately.
+fn draw_source_resolution(ui: &mut egui::Ui, source_type: &SourceType, delta_l: f32) {
+    let response = match source_type.highest_frequency() {
+        None => ui.colored_label(
+            Color32::YELLOW,
+            format!(
+                "Broadband source, only frequencies up to {:.0} Hz are accurate",
+                max_reliable_frequency(delta_l)
+            ),
+        ),
+        Some(frequency) => {
+            let cells = cells_per_wavelength(frequency, delta_l);
+            match Resolution::of(frequency, delta_l) {
+                Resolution::Accurate(_) => ui.label(format!(
+                    "{cells:.1} cells per wavelength at {frequency:.0} Hz"
+                )),
+                Resolution::Dispersive(error) => ui.colored_label(
+                    Color32::YELLOW,
+                    format!(
+                        "{cells:.1} cells per wavelength at {frequency:.0} Hz, \
+                        waves are {:.1} % too slow",
+                        error * 100.
+                    ),
+                ),
+                Resolution::AboveCutoff => ui.colored_label(
+                    Color32::RED,
+                    format!(
+                        "{frequency:.0} Hz is above the cutoff frequency of the grid ({:.0} Hz) \
+                        and does not propagate",
+                        cutoff_frequency(delta_l)
+                    ),
+                ),
+                Resolution::AboveNyquist => ui.colored_label(
+                    Color32::RED,
+                    format!(
+                        "{frequency:.0} Hz is above the Nyquist frequency ({:.0} Hz)",
+                        nyquist_frequency(delta_l)
+                    ),
+                ),
+            }
+        }
+    };
+    response.on_hover_text(
+        "The highest frequency of the source should span enough cells. \
+        Decrease Delta L in the preferences to simulate higher frequencies accurately.",
+    );
+}
+
+/// Shows the level of a source at 1 m and lets the user enter it instead of the amplitude.
+/// Returns true if the amplitude was changed.
+fn draw_source_level(
+    ui: &mut egui::Ui,
+    id: usize,
+    source_type: &mut SourceType,
+    calibration: &mut SourceCalibration,
+    delta_l: f32,
+) -> bool {
+    if matches!(source_type, SourceType::Impulse { .. }) {
+        ui.label("Impulses have no steady level to calibrate");
+        return false;
+    }
+
+    if calibration.is_measuring() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label("Measuring the free field response...");
+        });
+        return false;
+    }
+
+    if !calibration.is_measured(delta_l) {
+        if ui
+            .button("Calibrate Level")
+            .on_hover_text(
+                "Measures the free field response of the grid to relate the amplitude \
+                to the sound pressure at 1 m. The measurement runs in the background.",
+            )
+            .clicked()
+        {
+            calibration.measure(delta_l);
+        }
+        return false;
+    }
+
+    let Some(mut level) = calibration.source_level(source_type, delta_l) else {
+        return false;
+    };
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(("source_level_unit", id))
+            .selected_text(calibration.level_unit.to_string())
+            .show_ui(ui, |ui| {
+                for unit in SourceLevelUnit::ALL {
+                    ui.selectable_value(&mut calibration.level_unit, unit, unit.to_string());
+                }
+            });
+        let speed = match calibration.level_unit {
+            SourceLevelUnit::SplAt1m => 0.1,
+            _ => level.abs().max(1e-6) * 0.01,
+        };
+        if ui
+            .add(
+                egui::DragValue::new(&mut level)
+                    .speed(speed)
+                    .suffix(calibration.level_unit.suffix()),
+            )
+            .changed()
+        {
+            changed = calibration.set_source_level(source_type, level, delta_l);
+        }
+    })
+    .response
+    .on_hover_text(
+        "The RMS level in the free field, the sound power is per meter of the line source \
+        that a point in two dimensions represents",
+    );
+    changed
+}
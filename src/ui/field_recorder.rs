@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::{Color32, Pos2, Rect, Stroke, Vec2};
+
+use super::probes::point_edit;
+use super::state::{SimTime, UiState};
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::transformations::grid_to_image;
+use crate::simulation::field_recorder::{metadata_path, FieldRecorder};
+use crate::simulation::grid::Grid;
+
+/// Draws the window to record the pressure field to a `.npy` file.
+/// The recorded region is outlined on the simulation while the window is open.
+pub fn draw_field_recorder(
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut recorder: ResMut<FieldRecorder>,
+    grid: Res<Grid>,
+    sim_time: Res<SimTime>,
+) {
+    if !ui_state.show_field_recorder {
+        return;
+    }
+
+    let mut show_field_recorder = ui_state.show_field_recorder;
+    let ctx = egui_context.ctx_mut();
+
+    egui::Window::new("Record Field")
+        .open(&mut show_field_recorder)
+        .default_size(Vec2::new(400., 400.))
+        .resizable(false)
+        .collapsible(false)
+        .constrain(true)
+        .show(ctx, |ui| {
+            let is_recording = recorder.is_recording();
+
+            ui.add_enabled_ui(!is_recording, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("File");
+                    let mut path = recorder.path.to_string_lossy().into_owned();
+                    if ui.text_edit_singleline(&mut path).changed() {
+                        recorder.path = PathBuf::from(path);
+                    }
+                });
+                ui.label(format!(
+                    "The metadata is written to {}",
+                    metadata_path(&recorder.path).display()
+                ));
+
+                ui.separator();
+                ui.heading("Region");
+                ui.horizontal(|ui| {
+                    ui.label("From");
+                    point_edit(ui, &mut recorder.min);
+                    ui.label("To");
+                    point_edit(ui, &mut recorder.max);
+                });
+                if ui.button("Full Simulation").clicked() {
+                    recorder.min = UVec2::ZERO;
+                    recorder.max = UVec2::new(SIMULATION_WIDTH - 1, SIMULATION_HEIGHT - 1);
+                }
+
+                ui.separator();
+                ui.heading("Decimation");
+                ui.add(
+                    egui::DragValue::new(&mut recorder.spatial_decimation)
+                        .clamp_range(1..=64)
+                        .prefix("Every ")
+                        .suffix(". cell"),
+                )
+                .on_hover_text("Record only every n-th cell in x and y direction");
+                ui.add(
+                    egui::DragValue::new(&mut recorder.time_decimation)
+                        .clamp_range(1..=1000)
+                        .prefix("Every ")
+                        .suffix(". step"),
+                )
+                .on_hover_text("Record only every n-th simulation step");
+
+                ui.horizontal(|ui| {
+                    let mut limited = recorder.max_duration.is_some();
+                    if ui.checkbox(&mut limited, "Stop after").changed() {
+                        recorder.max_duration = limited.then_some(0.1);
+                    }
+                    if let Some(duration) = recorder.max_duration.as_mut() {
+                        ui.add(
+                            egui::DragValue::new(duration)
+                                .speed(0.01)
+                                .clamp_range(0.001..=100.)
+                                .suffix(" s"),
+                        );
+                    }
+                });
+            });
+
+            let size = recorder.frame_size();
+            ui.label(format!(
+                "{} × {} cells, {} per frame, {} per simulated second",
+                size.x,
+                size.y,
+                format_bytes(recorder.frame_bytes() as f32),
+                format_bytes(recorder.bytes_per_second(grid.delta_t))
+            ));
+            if let Some(duration) = recorder.max_duration {
+                ui.label(format!(
+                    "Expected size: {}",
+                    format_bytes(recorder.bytes_per_second(grid.delta_t) * duration)
+                ));
+            }
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if is_recording {
+                    if ui.button("Stop").clicked() {
+                        if let Err(err) = recorder.stop() {
+                            recorder.error = Some(err.to_string());
+                        }
+                    }
+                } else if ui
+                    .button("Record")
+                    .on_hover_text("Record while the simulation is running")
+                    .clicked()
+                {
+                    if let Err(err) =
+                        recorder.start(ui_state.delta_l, grid.delta_t, sim_time.time_since_start)
+                    {
+                        recorder.error = Some(err.to_string());
+                    }
+                }
+
+                if is_recording {
+                    let text = format!(
+                        "{} frames, {:.0} ms, {}",
+                        recorder.frames(),
+                        recorder.recorded_duration() * 1000.,
+                        format_bytes(recorder.bytes() as f32)
+                    );
+                    match recorder.max_duration {
+                        Some(duration) => {
+                            ui.add(
+                                egui::ProgressBar::new(recorder.recorded_duration() / duration)
+                                    .text(text),
+                            );
+                        }
+                        None => {
+                            ui.label(text);
+                        }
+                    }
+                }
+            });
+
+            if let Some(error) = &recorder.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        });
+
+    // outline the recorded region
+    if !ui_state.render_abc_area {
+        let min = recorder.min.min(recorder.max);
+        let max = recorder.min.max(recorder.max) + UVec2::ONE;
+        let rect = Rect::from_two_pos(
+            grid_to_image(Pos2::new(min.x as f32, min.y as f32), &ui_state.image_rect),
+            grid_to_image(Pos2::new(max.x as f32, max.y as f32), &ui_state.image_rect),
+        );
+        ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("field_recorder_region"),
+        ))
+        .rect_stroke(rect, 0., Stroke::new(2., Color32::from_rgb(255, 80, 80)));
+    }
+
+    ui_state.show_field_recorder = show_field_recorder;
+}
+
+/// Formats a size in bytes with a binary prefix.
+fn format_bytes(bytes: f32) -> String {
+    let mut size = bytes;
+    for unit in ["B", "KiB", "MiB", "GiB"] {
+        if size < 1024. {
+            return format!("{size:.1} {unit}");
+        }
+        size /= 1024.;
+    }
+    format!("{size:.1} TiB")
+}
@@ -1,19 +1,139 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_file_dialog::DialogFileLoaded;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use bevy_pixel_buffer::pixel_buffer::PixelBufferSize;
+use bevy_pixel_buffer::query::QueryPixelBuffer;
+use egui::{Color32, Vec2};
 use serde::Deserialize;
+use serde_json::Value;
 
-use super::state::UiState;
-use crate::components::microphone::Microphone;
-use crate::components::source::Source;
+use super::saving::{CHECKPOINT_MAGIC, SCENE_VERSION};
+use super::state::{SimTime, UiState};
+use super::tabs::{DockState, Tab};
+use crate::components::mic_array::MicArray;
+use crate::components::microphone::{Microphone, RecordState, RecordingPolicy};
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::components::source::{Source, SourceRecords};
 use crate::components::wall::{CircWall, RectWall};
-use crate::events::UpdateWalls;
+use crate::events::{LoadFile, UpdateWalls};
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::units::PressureUnits;
+use crate::remote::osc::{OscControl, OscSettings, OSC_PORTS};
 use crate::render::gradient::Gradient;
-use crate::simulation::grid::Grid;
+use crate::simulation::field_map::FieldMap;
+use crate::simulation::grid::{Cell, Grid};
 use crate::simulation::plugin::ComponentIDs;
+use crate::simulation::steady_state::SteadyStateMap;
+use crate::simulation::validation::{self, MAX_RADIUS};
 
 /// Marker component for the file dialog and the corresponding event.
 pub struct SaveFileContents;
 
+/// The solver and view settings that are loaded with the scene. Used for deserialization.
+#[derive(Deserialize)]
+struct Settings {
+    delta_l: f32,
+    boundary_width: u32,
+    framerate: f64,
+    fft_window_size: usize,
+    reset_on_change: bool,
+    recording_policy: RecordingPolicy,
+    averaging_time: f32,
+    /// scenes saved before physical units were added use raw values
+    #[serde(default)]
+    pressure_units: PressureUnits,
+    show_plots: bool,
+    plot_layout: egui_dock::DockState<Tab>,
+    /// scenes saved before the OSC input was added keep the current OSC settings
+    #[serde(default)]
+    osc: Option<OscSettings>,
+}
+
+impl Settings {
+    /// Checks that all settings are within the ranges that can be selected in the ui.
+    fn validate(&self, errors: &mut Vec<String>) {
+        if !self.delta_l.is_finite() || self.delta_l <= 0. {
+            errors.push(format!("Invalid delta l: {}", self.delta_l));
+        }
+        if !(2..=200).contains(&self.boundary_width) {
+            errors.push(format!(
+                "Invalid boundary width: {}, expected a value between 2 and 200",
+                self.boundary_width
+            ));
+        }
+        if !(1. ..=500.).contains(&self.framerate) {
+            errors.push(format!(
+                "Invalid simulation speed: {} Hz, expected a value between 1 and 500",
+                self.framerate
+            ));
+        }
+        if !(256..=8192).contains(&self.fft_window_size) || !self.fft_window_size.is_power_of_two()
+        {
+            errors.push(format!("Invalid FFT window size: {}", self.fft_window_size));
+        }
+        if !self.averaging_time.is_finite() || self.averaging_time <= 0. {
+            errors.push(format!("Invalid averaging time: {}", self.averaging_time));
+        }
+        if let Some(osc) = &self.osc {
+            if !OSC_PORTS.contains(&osc.port) {
+                errors.push(format!(
+                    "Invalid OSC port: {}, expected a value between {} and {}",
+                    osc.port,
+                    OSC_PORTS.start(),
+                    OSC_PORTS.end()
+                ));
+            }
+            for mapping in &osc.mappings {
+                if !mapping.min.is_finite() || !mapping.max.is_finite() {
+                    errors.push(format!(
+                        "Invalid range of the OSC address {}: {} to {}",
+                        mapping.address, mapping.min, mapping.max
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Applies the settings to the simulation and the ui.
+    fn apply(
+        self,
+        ui_state: &mut UiState,
+        grid: &mut Grid,
+        pixel_buffers: &mut QueryPixelBuffer,
+        fixed_timestep: &mut Time<Fixed>,
+        dock_state: &mut DockState,
+        osc: &mut OscControl,
+    ) {
+        ui_state.delta_l = self.delta_l;
+        grid.update_delta_t(ui_state.delta_l);
+        set_boundary_width(ui_state, grid, pixel_buffers, self.boundary_width);
+
+        // like in the ui, speeds above 60 Hz require the epilepsy warning to be confirmed
+        ui_state.framerate = if ui_state.read_epilepsy_warning {
+            self.framerate
+        } else {
+            self.framerate.min(60.)
+        };
+        fixed_timestep.set_timestep_hz(ui_state.framerate);
+
+        ui_state.fft_window_size = self.fft_window_size;
+        ui_state.reset_on_change = self.reset_on_change;
+        ui_state.recording_policy = self.recording_policy;
+        ui_state.averaging_time = self.averaging_time;
+        // keep the color scale of the levels where it was
+        let shift = self.pressure_units.level_offset() - ui_state.pressure_units.level_offset();
+        ui_state.min_level += shift;
+        ui_state.max_level += shift;
+        ui_state.pressure_units = self.pressure_units;
+        ui_state.show_plots = self.show_plots;
+        dock_state.tree = self.plot_layout;
+        if let Some(settings) = self.osc {
+            osc.apply_settings(settings);
+        }
+    }
+}
+
 /// The data that is loaded from a file. Used for deserialization.
 #[derive(Deserialize)]
 struct SaveData {
@@ -21,43 +141,368 @@ struct SaveData {
     mics: Vec<Microphone>,
     rect_walls: Vec<RectWall>,
     circ_walls: Vec<CircWall>,
+    #[serde(default)]
+    mic_arrays: Vec<MicArray>,
+    #[serde(default)]
+    line_probes: Vec<LineProbe>,
+    #[serde(default)]
+    area_probes: Vec<AreaProbe>,
     gradient: Gradient,
     max_gradient: f32,
     min_gradient: f32,
+    /// missing in files of version 2 and older, the current settings are kept for them
+    #[serde(default)]
+    settings: Option<Settings>,
+}
+
+impl SaveData {
+    /// Parses a scene file, migrates it to the current version and validates its fields.
+    /// Returns all problems that prevent the file from being loaded.
+    fn parse(contents: &[u8]) -> Result<SaveData, Vec<String>> {
+        let value: Value = serde_json::from_slice(contents)
+            .map_err(|err| vec![format!("The file is not valid JSON: {err}")])?;
+        SaveData::from_value(value)
+    }
+
+    /// Migrates the raw JSON of a scene to the current version, deserializes and validates it.
+    fn from_value(mut value: Value) -> Result<SaveData, Vec<String>> {
+        // files from before versioning was introduced have no version field
+        let version = match value.get("version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| vec![format!("Invalid scene version: {version}")])?,
+        };
+        if version > SCENE_VERSION as u64 {
+            return Err(vec![format!(
+                "The file was saved by a newer version of wavefront \
+                (scene version {version}, supported up to {SCENE_VERSION})."
+            )]);
+        }
+        migrate(&mut value, version);
+
+        let save_data: SaveData = serde_json::from_value(value)
+            .map_err(|err| vec![format!("The file is not a valid scene: {err}")])?;
+
+        let errors = save_data.validate();
+        if errors.is_empty() {
+            Ok(save_data)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks that all positions lie inside the simulation area, that circular walls are not
+    /// larger than it and that all reflection factors are between 0 and 1.
+    fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+
+        for source in &self.sources {
+            check_point(
+                &mut errors,
+                &format!("Source {}", source.id),
+                UVec2::new(source.x, source.y),
+            );
+        }
+        for mic in &self.mics {
+            check_point(
+                &mut errors,
+                &format!("Microphone {}", mic.id),
+                UVec2::new(mic.x, mic.y),
+            );
+        }
+        for wall in &self.rect_walls {
+            let name = format!("Rectangular wall {}", wall.id);
+            check_point(&mut errors, &name, wall.rect.min);
+            check_point(&mut errors, &name, wall.rect.max);
+            if wall.rect.min.x > wall.rect.max.x || wall.rect.min.y > wall.rect.max.y {
+                errors.push(format!("{name} has a negative size"));
+            }
+            check_reflection_factor(&mut errors, &name, wall.reflection_factor);
+        }
+        for wall in &self.circ_walls {
+            let name = format!("Circular wall {}", wall.id);
+            check_point(&mut errors, &name, wall.center);
+            if validation::radius(wall.radius.into()).is_err() {
+                errors.push(format!(
+                    "{name} has a radius of {}, expected a value between 1 and {MAX_RADIUS}",
+                    wall.radius
+                ));
+            }
+            check_reflection_factor(&mut errors, &name, wall.reflection_factor);
+        }
+        for probe in &self.line_probes {
+            let name = format!("Line probe {}", probe.id);
+            check_point(&mut errors, &name, probe.start);
+            check_point(&mut errors, &name, probe.end);
+        }
+        for probe in &self.area_probes {
+            let name = format!("Area probe {}", probe.id);
+            check_point(&mut errors, &name, probe.start);
+            check_point(&mut errors, &name, probe.end);
+        }
+
+        if !self.min_gradient.is_finite()
+            || !self.max_gradient.is_finite()
+            || self.min_gradient >= self.max_gradient
+        {
+            errors.push(format!(
+                "Invalid gradient range: {} to {}",
+                self.min_gradient, self.max_gradient
+            ));
+        }
+
+        if let Some(settings) = &self.settings {
+            settings.validate(&mut errors);
+        }
+
+        errors
+    }
+}
+
+/// Upgrades the raw JSON of a scene file from `version` to [`SCENE_VERSION`] in place.
+pub fn migrate(value: &mut Value, version: u64) {
+    let Some(object) = value.as_object_mut() else {
+        // not a scene at all, deserialization reports the error
+        return;
+    };
+
+    if version < 2 {
+        // mic arrays and probes were added without a version bump
+        for key in ["mic_arrays", "line_probes", "area_probes"] {
+            object
+                .entry(key)
+                .or_insert_with(|| Value::Array(Vec::new()));
+        }
+    }
+    // version 3 added the optional settings, older files keep the current settings
 }
 
-/// Loads a file when receiving a [`DialogFileLoaded`] event from the file dialog.
+fn check_point(errors: &mut Vec<String>, name: &str, point: UVec2) {
+    if point.x >= SIMULATION_WIDTH || point.y >= SIMULATION_HEIGHT {
+        errors.push(format!(
+            "{name} at ({}, {}) is outside of the simulation area",
+            point.x, point.y
+        ));
+    }
+}
+
+fn check_reflection_factor(errors: &mut Vec<String>, name: &str, reflection_factor: f32) {
+    if !(0. ..=1.).contains(&reflection_factor) {
+        errors.push(format!(
+            "{name} has a reflection factor of {reflection_factor}, expected a value between 0 and 1"
+        ));
+    }
+}
+
+/// The recording state of a microphone in a checkpoint.
+/// Checkpoints saved before the decimation and stream state was added only contain the record.
+#[derive(Deserialize)]
+struct MicRecord {
+    id: usize,
+    #[serde(flatten)]
+    state: RecordState,
+}
+
+/// The JSON header of a checkpoint. Used for deserialization.
+#[derive(Deserialize)]
+struct CheckpointHeader {
+    scene: Value,
+    time_since_start: f32,
+    delta_l: f32,
+    boundary_width: u32,
+    mic_records: Vec<MicRecord>,
+    #[serde(default)]
+    source_records: SourceRecords,
+}
+
+/// The state of a running simulation that is loaded from a checkpoint.
+struct Checkpoint {
+    time_since_start: f32,
+    delta_l: f32,
+    boundary_width: u32,
+    mic_records: Vec<MicRecord>,
+    source_records: SourceRecords,
+    cur_cells: Vec<Cell>,
+    next_cells: Vec<Cell>,
+    pressure: Vec<f32>,
+}
+
+impl Checkpoint {
+    /// Parses a checkpoint file as written by
+    /// [`serialize_checkpoint`](super::saving::serialize_checkpoint).
+    /// Returns the scene and the state of the simulation.
+    fn parse(contents: &[u8]) -> Result<(SaveData, Checkpoint), Vec<String>> {
+        let truncated = || vec!["The checkpoint file is truncated.".to_string()];
+
+        let contents = &contents[CHECKPOINT_MAGIC.len()..];
+        let (length, contents) = contents.split_first_chunk::<8>().ok_or_else(truncated)?;
+        let length = u64::from_le_bytes(*length) as usize;
+        if contents.len() < length {
+            return Err(truncated());
+        }
+        let (header, state) = contents.split_at(length);
+
+        let header: CheckpointHeader = serde_json::from_slice(header)
+            .map_err(|err| vec![format!("The checkpoint header is invalid: {err}")])?;
+        let save_data = SaveData::from_value(header.scene)?;
+
+        if !header.delta_l.is_finite() || header.delta_l <= 0. {
+            return Err(vec![format!("Invalid delta l: {}", header.delta_l)]);
+        }
+
+        let width = SIMULATION_WIDTH as usize + 2 * header.boundary_width as usize;
+        let height = SIMULATION_HEIGHT as usize + 2 * header.boundary_width as usize;
+        let cell_count = width * height;
+        // two sets of cells with four ports each and the pressure of every cell
+        let expected = cell_count * 9 * 4;
+        if state.len() != expected {
+            return Err(vec![format!(
+                "The checkpoint should contain {expected} bytes of simulation state, found {}.",
+                state.len()
+            )]);
+        }
+
+        let values = state
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let (cells, pressure) = values.split_at(cell_count * 8);
+        let mut cur_cells = cells
+            .chunks_exact(4)
+            .map(|ports| Cell {
+                bottom: ports[0],
+                left: ports[1],
+                top: ports[2],
+                right: ports[3],
+            })
+            .collect::<Vec<_>>();
+        let next_cells = cur_cells.split_off(cell_count);
+
+        Ok((
+            save_data,
+            Checkpoint {
+                time_since_start: header.time_since_start,
+                delta_l: header.delta_l,
+                boundary_width: header.boundary_width,
+                mic_records: header.mic_records,
+                source_records: header.source_records,
+                cur_cells,
+                next_cells,
+                pressure: pressure.to_vec(),
+            },
+        ))
+    }
+
+    /// Removes and returns the recording state of the microphone with the given id.
+    fn take_record(&mut self, mic_id: usize) -> Option<RecordState> {
+        let index = self
+            .mic_records
+            .iter()
+            .position(|record| record.id == mic_id)?;
+        Some(self.mic_records.swap_remove(index).state)
+    }
+}
+
+/// The problems of the last file that could not be loaded.
+/// A dialog lists them until it is dismissed.
+#[derive(Resource, Default)]
+pub struct LoadErrors {
+    pub errors: Vec<String>,
+}
+
+/// All entities of the scene, used to despawn them before loading a file.
+#[derive(SystemParam)]
+pub struct SceneEntities<'w, 's> {
+    sources: Query<'w, 's, Entity, With<Source>>,
+    mics: Query<'w, 's, Entity, With<Microphone>>,
+    rect_walls: Query<'w, 's, Entity, With<RectWall>>,
+    circ_walls: Query<'w, 's, Entity, With<CircWall>>,
+    mic_arrays: Query<'w, 's, Entity, With<MicArray>>,
+    line_probes: Query<'w, 's, Entity, With<LineProbe>>,
+    area_probes: Query<'w, 's, Entity, With<AreaProbe>>,
+}
+
+impl SceneEntities<'_, '_> {
+    pub fn despawn_all(&self, commands: &mut Commands) {
+        for entity in self
+            .sources
+            .iter()
+            .chain(self.mics.iter())
+            .chain(self.rect_walls.iter())
+            .chain(self.circ_walls.iter())
+            .chain(self.mic_arrays.iter())
+            .chain(self.line_probes.iter())
+            .chain(self.area_probes.iter())
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// The results of the analyses that are restarted when a file is loaded.
+/// Checkpoints restore the [`SourceRecords`], the field maps always restart.
+#[derive(SystemParam)]
+pub struct Analyses<'w> {
+    source_records: ResMut<'w, SourceRecords>,
+    field_map: ResMut<'w, FieldMap>,
+    steady_state: ResMut<'w, SteadyStateMap>,
+}
+
+/// Parses a scene or a checkpoint file.
+fn parse_file(contents: &[u8]) -> Result<(SaveData, Option<Checkpoint>), Vec<String>> {
+    if contents.starts_with(CHECKPOINT_MAGIC) {
+        Checkpoint::parse(contents).map(|(save_data, checkpoint)| (save_data, Some(checkpoint)))
+    } else {
+        SaveData::parse(contents).map(|save_data| (save_data, None))
+    }
+}
+
+/// Checks whether a scene or checkpoint file can be loaded and returns the problems if not.
+pub fn validate_file(contents: &[u8]) -> Result<(), Vec<String>> {
+    parse_file(contents).map(|_| ())
+}
+
+/// Loads a file when receiving a [`DialogFileLoaded`] event from the file dialog
+/// or a [`LoadFile`] event.
 /// All entities are despawned and the new entities are spawned.
+/// Checkpoints additionally restore the state of the simulation and the records,
+/// so it continues where it stopped. The field maps start over, see [`Analyses`].
+/// If the file cannot be loaded, the current scene is kept and the problems are shown in a dialog.
 pub fn file_loaded(
     mut ev_loaded: EventReader<DialogFileLoaded<SaveFileContents>>,
+    mut ev_load_file: EventReader<LoadFile>,
     mut commands: Commands,
     mut wall_update_ev: EventWriter<UpdateWalls>,
     mut grid: ResMut<Grid>,
     mut ids: ResMut<ComponentIDs>,
     mut gradient: ResMut<Gradient>,
-    sources: Query<(Entity, &Source)>,
-    mics: Query<(Entity, &Microphone)>,
-    rect_walls: Query<(Entity, &RectWall)>,
-    circ_walls: Query<(Entity, &CircWall)>,
+    mut sim_time: ResMut<SimTime>,
+    mut fixed_timestep: ResMut<Time<Fixed>>,
+    mut dock_state: ResMut<DockState>,
+    mut osc: ResMut<OscControl>,
+    mut pixel_buffers: QueryPixelBuffer,
+    scene: SceneEntities,
+    mut analyses: Analyses,
     mut ui_state: ResMut<UiState>,
+    mut load_errors: ResMut<LoadErrors>,
 ) {
-    if let Some(data) = ev_loaded.read().next() {
-        let save_data = serde_json::from_slice::<SaveData>(&data.contents).unwrap();
-
-        // Clear all entities
-        for (entity, _) in sources.iter() {
-            commands.entity(entity).despawn();
-        }
-        for (entity, _) in mics.iter() {
-            commands.entity(entity).despawn();
-        }
-        for (entity, _) in rect_walls.iter() {
-            commands.entity(entity).despawn();
-        }
-        for (entity, _) in circ_walls.iter() {
-            commands.entity(entity).despawn();
-        }
+    let contents = ev_loaded
+        .read()
+        .map(|data| &data.contents)
+        .chain(ev_load_file.read().map(|event| &event.contents))
+        .next();
+    if let Some(contents) = contents {
+        let (save_data, mut checkpoint) = match parse_file(contents) {
+            Ok(loaded) => loaded,
+            Err(errors) => {
+                load_errors.errors = errors;
+                return;
+            }
+        };
+        load_errors.errors.clear();
 
+        scene.despawn_all(&mut commands);
         ids.reset();
 
         // Load entities
@@ -65,7 +510,13 @@ pub fn file_loaded(
             commands.spawn(source);
             ids.get_new_source_id();
         }
-        for mic in save_data.mics {
+        for mut mic in save_data.mics {
+            if let Some(state) = checkpoint
+                .as_mut()
+                .and_then(|checkpoint| checkpoint.take_record(mic.id))
+            {
+                mic.restore_record_state(state);
+            }
             commands.spawn(mic);
             ids.get_new_mic_id();
         }
@@ -77,8 +528,55 @@ pub fn file_loaded(
             commands.spawn(circ_wall);
             ids.get_new_wall_id();
         }
+        for mic_array in save_data.mic_arrays {
+            commands.spawn(mic_array);
+        }
+        for probe in save_data.line_probes {
+            commands.spawn(probe);
+            ids.get_new_probe_id();
+        }
+        for probe in save_data.area_probes {
+            commands.spawn(probe);
+            ids.get_new_probe_id();
+        }
+
+        if let Some(settings) = save_data.settings {
+            settings.apply(
+                &mut ui_state,
+                &mut grid,
+                &mut pixel_buffers,
+                &mut fixed_timestep,
+                &mut dock_state,
+                &mut osc,
+            );
+        }
+
+        analyses.field_map.clear();
+        analyses.steady_state.clear();
+        *analyses.source_records = checkpoint
+            .as_mut()
+            .map(|checkpoint| std::mem::take(&mut checkpoint.source_records))
+            .unwrap_or_default();
+
+        match checkpoint {
+            Some(checkpoint) => {
+                // the cells are only valid with the solver settings they were computed with
+                ui_state.delta_l = checkpoint.delta_l;
+                grid.update_delta_t(ui_state.delta_l);
+                set_boundary_width(
+                    &mut ui_state,
+                    &mut grid,
+                    &mut pixel_buffers,
+                    checkpoint.boundary_width,
+                );
 
-        grid.reset_cells(ui_state.boundary_width);
+                grid.cur_cells = checkpoint.cur_cells;
+                grid.next_cells = checkpoint.next_cells;
+                grid.pressure = checkpoint.pressure;
+                sim_time.time_since_start = checkpoint.time_since_start;
+            }
+            None => grid.reset_cells(ui_state.boundary_width),
+        }
         wall_update_ev.send(UpdateWalls);
 
         *gradient = save_data.gradient;
@@ -86,3 +584,70 @@ pub fn file_loaded(
         ui_state.min_gradient = save_data.min_gradient;
     }
 }
+
+/// Changes the width of the absorbing boundary and resizes the wall and boundary caches
+/// and the pixel buffer. The cells have to be reset or replaced afterwards.
+fn set_boundary_width(
+    ui_state: &mut UiState,
+    grid: &mut Grid,
+    pixel_buffers: &mut QueryPixelBuffer,
+    boundary_width: u32,
+) {
+    if ui_state.boundary_width == boundary_width {
+        return;
+    }
+    ui_state.boundary_width = boundary_width;
+    grid.reset_walls(boundary_width);
+    grid.cache_boundaries(boundary_width);
+    resize_pixel_buffer(ui_state, pixel_buffers);
+}
+
+/// Resizes the main pixel buffer to the simulation area, with the boundary if it is shown.
+pub(crate) fn resize_pixel_buffer(ui_state: &UiState, pixel_buffers: &mut QueryPixelBuffer) {
+    let mut pb = pixel_buffers.iter_mut().next().expect("one pixel buffer");
+    pb.pixel_buffer.size = PixelBufferSize {
+        size: if ui_state.render_abc_area {
+            UVec2::new(
+                SIMULATION_WIDTH + 2 * ui_state.boundary_width,
+                SIMULATION_HEIGHT + 2 * ui_state.boundary_width,
+            )
+        } else {
+            UVec2::new(SIMULATION_WIDTH, SIMULATION_HEIGHT)
+        },
+        pixel_size: UVec2::new(1, 1),
+    };
+}
+
+/// Draws the dialog listing why the last file could not be loaded.
+pub fn draw_load_errors(mut egui_context: EguiContexts, mut load_errors: ResMut<LoadErrors>) {
+    if load_errors.errors.is_empty() {
+        return;
+    }
+
+    let mut dismissed = false;
+
+    egui::Window::new("Could not open file")
+        .default_size(Vec2::new(400., 300.))
+        .resizable(false)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label("The file was not loaded because of the following problems:");
+            ui.add_space(5.);
+            egui::ScrollArea::vertical()
+                .max_height(250.)
+                .show(ui, |ui| {
+                    for error in &load_errors.errors {
+                        ui.colored_label(Color32::RED, error);
+                    }
+                });
+            ui.add_space(5.);
+            if ui.button("OK").clicked() {
+                dismissed = true;
+            }
+        });
+
+    if dismissed {
+        load_errors.errors.clear();
+    }
+}
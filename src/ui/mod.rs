@@ -1,8 +1,17 @@
+pub mod animation;
 pub mod draw;
+pub mod field_recorder;
 pub mod help;
 pub mod loading;
 pub mod plugin;
 pub mod preferences;
+pub mod probes;
+pub mod room_modes;
 pub mod saving;
+pub mod screenshot;
+pub mod script_console;
 pub mod state;
+pub mod steady_state;
 pub mod tabs;
+pub mod templates;
+pub mod wav_export;
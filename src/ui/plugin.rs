@@ -2,10 +2,22 @@ use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 use bevy::prelude::*;
 use bevy_file_dialog::FileDialogPlugin;
 
+use super::animation::draw_animation_export;
 use super::draw::draw_egui;
-use super::loading::{file_loaded, SaveFileContents};
-use super::state::{ClipboardBuffer, FftMicrophone, UiState};
+use super::field_recorder::draw_field_recorder;
+use super::loading::{draw_load_errors, file_loaded, LoadErrors, SaveFileContents};
+use super::probes::draw_probes;
+use super::room_modes::draw_room_modes;
+use super::screenshot::draw_screenshot_export;
+use super::script_console::{draw_script_console, run_console_script, script_loaded, ScriptFile};
+use super::state::{
+    ClipboardBuffer, FftMicrophone, IrMeasurement, ScreenshotSettings, ScriptConsole,
+    TemplateGallery, TransferFunctionSettings, UiState, WavExportSettings,
+};
+use super::steady_state::draw_steady_state;
 use super::tabs::DockState;
+use super::templates::draw_template_gallery;
+use super::wav_export::{draw_wav_export, wav_directory_picked, WavExportDirectory};
 
 pub struct UiPlugin;
 
@@ -15,12 +27,40 @@ impl Plugin for UiPlugin {
             .init_resource::<ClipboardBuffer>()
             .init_resource::<DockState>()
             .init_resource::<FftMicrophone>()
+            .init_resource::<WavExportSettings>()
+            .init_resource::<IrMeasurement>()
+            .init_resource::<TransferFunctionSettings>()
+            .init_resource::<ScreenshotSettings>()
+            .init_resource::<LoadErrors>()
+            .init_resource::<TemplateGallery>()
+            .init_resource::<ScriptConsole>()
             .add_plugins((
                 FileDialogPlugin::new()
                     .with_save_file::<SaveFileContents>()
-                    .with_load_file::<SaveFileContents>(),
+                    .with_load_file::<SaveFileContents>()
+                    .with_load_file::<ScriptFile>()
+                    .with_pick_directory::<WavExportDirectory>(),
                 FrameTimeDiagnosticsPlugin,
             ))
-            .add_systems(Update, (draw_egui, file_loaded));
+            .add_systems(
+                Update,
+                (
+                    draw_egui,
+                    file_loaded,
+                    draw_wav_export.after(draw_egui),
+                    wav_directory_picked,
+                    draw_steady_state.after(draw_egui),
+                    draw_room_modes.after(draw_egui),
+                    draw_probes.after(draw_egui),
+                    draw_field_recorder.after(draw_egui),
+                    draw_animation_export.after(draw_egui),
+                    draw_screenshot_export.after(draw_egui),
+                    draw_load_errors.after(draw_egui),
+                    draw_template_gallery.after(draw_egui),
+                    draw_script_console.after(draw_egui),
+                    script_loaded,
+                    run_console_script.after(draw_script_console),
+                ),
+            );
     }
 }
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use bevy::math::UVec2;
 use bevy_pixel_buffer::pixel_buffer::PixelBufferSize;
 use bevy_pixel_buffer::query::QueryPixelBuffer;
@@ -6,8 +8,12 @@ use egui_extras::{Column, TableBuilder};
 
 use super::draw::EventSystemParams;
 use super::state::UiState;
+use crate::components::microphone::{RecordingPolicy, StreamDirectory};
 use crate::events::Reset;
 use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::math::units::PressureUnits;
+use crate::remote::osc::{OscControl, OscMapping, OscTarget, SourceParameter, OSC_PORTS};
+use crate::remote::plugin::RemoteControl;
 use crate::render::gradient::Gradient;
 use crate::simulation::grid::Grid;
 
@@ -19,6 +25,10 @@ pub fn draw_preferences(
     grid: &mut Grid,
     pixel_buffers: &mut QueryPixelBuffer,
     gradient: &mut Gradient,
+    remote: &mut RemoteControl,
+    osc: &mut OscControl,
+    stream_directory: &mut StreamDirectory,
+    sources: &[usize],
 ) {
     egui::Window::new("Preferences")
             .open(show_preferences)
@@ -30,6 +40,13 @@ pub fn draw_preferences(
                 // ui.set_min_width(800.);
                 let row_height = 20f32;
 
+                let decimation = ui_state_tmp.recording_policy.decimation();
+                let pressure_suffix = match ui_state_tmp.pressure_units {
+                    PressureUnits::Raw => String::new(),
+                    units => format!(" {}", units.pressure_suffix()),
+                };
+                let level_suffix = format!(" {}", ui_state_tmp.pressure_units.level_suffix());
+
                 ui.columns(1, |columns| {
                     columns[0].vertical_centered(|ui| {
                         ui.add_space(5.);
@@ -60,6 +77,31 @@ pub fn draw_preferences(
                                         });
                                     });
                                 });
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                            let previous_units = ui_state_tmp.pressure_units;
+                                            egui::ComboBox::from_id_source("pressure_units_select")
+                                                .selected_text(ui_state_tmp.pressure_units.to_string())
+                                                .show_ui(ui, |ui| {
+                                                    for units in [PressureUnits::Raw, PressureUnits::Pascal] {
+                                                        ui.selectable_value(&mut ui_state_tmp.pressure_units, units, units.to_string());
+                                                    }
+                                                })
+                                                .response
+                                                .on_hover_text("With Pa / dB SPL a pressure of 1 on the grid is 1 Pa and the level of sources can be entered at 1 m");
+                                            // keep the color scale of the levels where it was
+                                            let shift = ui_state_tmp.pressure_units.level_offset() - previous_units.level_offset();
+                                            ui_state_tmp.min_level += shift;
+                                            ui_state_tmp.max_level += shift;
+                                        });
+                                    });
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui|{
+                                            ui.label("Units");
+                                        });
+                                    });
+                                });
                                 body.row(row_height, |mut row| {
                                     row.col(|ui| {
                                         ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
@@ -94,7 +136,7 @@ pub fn draw_preferences(
                                     row.col(|ui| {
                                         ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
                                             ui.add(
-                                                egui::DragValue::new(&mut ui_state_tmp.min_gradient).speed(0.01)
+                                                egui::DragValue::new(&mut ui_state_tmp.min_gradient).speed(0.01).suffix(&pressure_suffix)
                                             );
                                         });
                                     });
@@ -108,7 +150,7 @@ pub fn draw_preferences(
                                     row.col(|ui| {
                                         ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
                                             ui.add(
-                                                egui::DragValue::new(&mut ui_state_tmp.max_gradient).speed(0.01)
+                                                egui::DragValue::new(&mut ui_state_tmp.max_gradient).speed(0.01).suffix(&pressure_suffix)
                                             );
                                         });
                                     });
@@ -118,6 +160,67 @@ pub fn draw_preferences(
                                         });
                                     });
                                 });
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                            ui.add(
+                                                egui::DragValue::new(&mut ui_state_tmp.min_level).speed(0.1).suffix(&level_suffix)
+                                            );
+                                        });
+                                    });
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                            ui.label("Min Level");
+                                        });
+                                    });
+                                });
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                            ui.add(
+                                                egui::DragValue::new(&mut ui_state_tmp.max_level).speed(0.1).suffix(&level_suffix)
+                                            );
+                                        });
+                                    });
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                            ui.label("Max Level");
+                                        });
+                                    });
+                                });
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                            ui.add(
+                                                egui::DragValue::new(&mut ui_state_tmp.averaging_time)
+                                                    .speed(0.001)
+                                                    .clamp_range(0.001..=10.)
+                                                    .suffix(" s")
+                                            );
+                                        });
+                                    });
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                            ui.label("Averaging Time");
+                                        });
+                                    });
+                                });
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                            ui.add(
+                                                egui::DragValue::new(&mut ui_state_tmp.vector_spacing)
+                                                    .clamp_range(4..=64)
+                                                    .suffix(" cells")
+                                            );
+                                        });
+                                    });
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                            ui.label("Vector Spacing");
+                                        });
+                                    });
+                                });
                                 body.row(row_height, |mut row| {
                                     row.col(|ui| {
                                         ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
@@ -189,9 +292,133 @@ pub fn draw_preferences(
                                         });
                                     });
                                 });
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                            ui.strong("Recording");
+                                        });
+                                    });
+                                });
+                                let seconds = match ui_state_tmp.recording_policy {
+                                    RecordingPolicy::Unbounded => 1.,
+                                    RecordingPolicy::RingBuffer { seconds }
+                                    | RecordingPolicy::Decimated { seconds, .. }
+                                    | RecordingPolicy::StreamToDisk { seconds, .. } => seconds,
+                                };
+                                body.row(row_height, |mut row| {
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                            egui::ComboBox::from_id_source("recording_policy_select")
+                                                .selected_text(ui_state_tmp.recording_policy.to_string())
+                                                .show_ui(ui, |ui| {
+                                                    for policy in [
+                                                        RecordingPolicy::Unbounded,
+                                                        RecordingPolicy::RingBuffer { seconds },
+                                                        RecordingPolicy::Decimated { factor: 4, seconds },
+                                                        RecordingPolicy::StreamToDisk { chunk_size: 4096, seconds },
+                                                    ] {
+                                                        // only compare the variant, not the parameters
+                                                        let selected = std::mem::discriminant(&policy)
+                                                            == std::mem::discriminant(&ui_state_tmp.recording_policy);
+                                                        if ui.selectable_label(selected, policy.to_string()).clicked() && !selected {
+                                                            ui_state_tmp.recording_policy = policy;
+                                                        }
+                                                    }
+                                                })
+                                                .response
+                                                .on_hover_text("How microphone recordings are stored. Bounded policies only keep the most recent samples in memory.");
+                                        });
+                                    });
+                                    row.col(|ui| {
+                                        ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                            ui.label("Recording policy");
+                                        });
+                                    });
+                                });
+                                match &mut ui_state_tmp.recording_policy {
+                                    RecordingPolicy::Unbounded => {}
+                                    RecordingPolicy::RingBuffer { seconds }
+                                    | RecordingPolicy::Decimated { seconds, .. }
+                                    | RecordingPolicy::StreamToDisk { seconds, .. } => {
+                                        body.row(row_height, |mut row| {
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    ui.add(
+                                                        egui::DragValue::new(seconds)
+                                                            .speed(0.01)
+                                                            .clamp_range(0.001..=600.),
+                                                    )
+                                                    .on_hover_text("The amount of simulation time kept in memory.");
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                                    ui.label("Retained window (s)");
+                                                });
+                                            });
+                                        });
+                                    }
+                                }
+                                match &mut ui_state_tmp.recording_policy {
+                                    RecordingPolicy::Decimated { factor, .. } => {
+                                        body.row(row_height, |mut row| {
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    ui.add(egui::DragValue::new(factor).clamp_range(1..=256))
+                                                        .on_hover_text("The amount of simulation steps averaged into one recorded sample.");
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                                    ui.label("Decimation factor");
+                                                });
+                                            });
+                                        });
+                                    }
+                                    RecordingPolicy::StreamToDisk { chunk_size, .. } => {
+                                        body.row(row_height, |mut row| {
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    ui.add(egui::DragValue::new(chunk_size).clamp_range(1..=1_048_576))
+                                                        .on_hover_text("The amount of samples written to mic_<id>_stream.csv at once.");
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                                    ui.label("Chunk size (samples)");
+                                                });
+                                            });
+                                        });
+                                        body.row(row_height, |mut row| {
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    let mut directory = stream_directory.0.to_string_lossy().into_owned();
+                                                    if ui
+                                                        .text_edit_singleline(&mut directory)
+                                                        .on_hover_text("The directory the microphones stream to, relative to the working directory.")
+                                                        .changed()
+                                                    {
+                                                        stream_directory.0 = PathBuf::from(directory);
+                                                    }
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui| {
+                                                    ui.label("Stream directory");
+                                                });
+                                            });
+                                        });
+                                    }
+                                    _ => {}
+                                }
                             });
                         });
 
+                        // records with different sample intervals cannot be mixed
+                        if ui_state_tmp.recording_policy.decimation() != decimation {
+                            events.reset_ev.send(Reset { force: true });
+                        }
+
                         ui.add_space(5.);
                         ui.separator();
                         ui.add_space(5.);
@@ -221,8 +448,197 @@ pub fn draw_preferences(
                             });
 
                             ui.add_space(5.);
+                            ui.separator();
+                            ui.add_space(5.);
+
+                            ui.heading("Remote Control");
+
+                            ui.push_id("remote_control_table", |ui| {
+                                TableBuilder::new(ui)
+                                    .resizable(false)
+                                    .striped(false)
+                                    .column(Column::remainder())
+                                    .column(Column::remainder())
+                                    .body(|mut body| {
+                                        body.row(row_height, |mut row| {
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    ui.checkbox(&mut remote.enabled, "")
+                                                        .on_hover_text("Accept JSON commands from other programs on this computer. \
+                                                        Every program and user on this computer can then control the simulation \
+                                                        and read and create files in the working directory.");
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui|{
+                                                    ui.label("Remote control enabled");
+                                                });
+                                            });
+                                        });
+                                        body.row(row_height, |mut row| {
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    ui.add(egui::DragValue::new(&mut remote.port).clamp_range(OSC_PORTS));
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui|{
+                                                    ui.label("Port");
+                                                });
+                                            });
+                                        });
+                                        body.row(row_height, |mut row| {
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                                                    let mut directory = remote.working_directory.to_string_lossy().into_owned();
+                                                    if ui
+                                                        .text_edit_singleline(&mut directory)
+                                                        .on_hover_text("Clients can only load files from this directory and save screenshots to it.")
+                                                        .changed()
+                                                    {
+                                                        remote.working_directory = PathBuf::from(directory);
+                                                    }
+                                                });
+                                            });
+                                            row.col(|ui| {
+                                                ui.with_layout(Layout::left_to_right(egui::Align::Center), |ui|{
+                                                    ui.label("Working directory");
+                                                });
+                                            });
+                                        });
+                                    });
+                            });
+
+                            if let Some(error) = &remote.error {
+                                ui.colored_label(egui::Color32::RED, error);
+                            } else if let Some(port) = remote.listening_port() {
+                                ui.label(format!("Listening on 127.0.0.1:{port}"));
+                            }
+                            ui.label("The server has no authentication, only enable it on computers you trust.");
+
+                            ui.add_space(5.);
+                            ui.separator();
+                            ui.add_space(5.);
+
+                            ui.heading("OSC Input");
+                            draw_osc_settings(ui, osc, sources);
+
+                            ui.add_space(5.);
 
                         });
                 });
             });
 }
+
+/// Draws the settings of the OSC input and the editor for the mappings.
+/// * `sources` - The ids of all sources.
+fn draw_osc_settings(ui: &mut egui::Ui, osc: &mut OscControl, sources: &[usize]) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut osc.enabled, "Enabled")
+            .on_hover_text("Receive OSC messages over UDP on all network interfaces");
+        ui.add(
+            egui::DragValue::new(&mut osc.port)
+                .clamp_range(1024..=65535)
+                .prefix("Port: "),
+        );
+    });
+
+    if let Some(error) = &osc.error {
+        ui.colored_label(egui::Color32::RED, error);
+    } else if osc.listening_port().is_some() {
+        ui.label(format!(
+            "Last message: {}",
+            osc.last_message.as_deref().unwrap_or("none")
+        ));
+    }
+
+    let mut removed = None;
+    egui::Grid::new("osc_mappings")
+        .num_columns(5)
+        .striped(true)
+        .show(ui, |ui| {
+            for (index, mapping) in osc.mappings.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut mapping.address).desired_width(120.));
+
+                {
+                    let source_target = match mapping.target {
+                        OscTarget::Source { .. } => mapping.target,
+                        _ => OscTarget::Source {
+                            id: sources.first().copied().unwrap_or_default(),
+                            parameter: SourceParameter::Frequency,
+                        },
+                    };
+                    egui::ComboBox::from_id_source(("osc_target", index))
+                        .selected_text(mapping.target.to_string())
+                        .show_ui(ui, |ui| {
+                            for target in OscTarget::TRANSPORT {
+                                ui.selectable_value(
+                                    &mut mapping.target,
+                                    target,
+                                    target.to_string(),
+                                );
+                            }
+                            if ui
+                                .selectable_label(
+                                    matches!(mapping.target, OscTarget::Source { .. }),
+                                    "Source",
+                                )
+                                .clicked()
+                            {
+                                mapping.target = source_target;
+                            }
+                        });
+                }
+
+                if let OscTarget::Source { id, parameter } = &mut mapping.target {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source(("osc_source", index))
+                            .selected_text(format!("Source {id}"))
+                            .show_ui(ui, |ui| {
+                                for &source in sources {
+                                    ui.selectable_value(id, source, format!("Source {source}"));
+                                }
+                            });
+                        egui::ComboBox::from_id_source(("osc_parameter", index))
+                            .selected_text(parameter.to_string())
+                            .show_ui(ui, |ui| {
+                                for option in SourceParameter::ALL {
+                                    ui.selectable_value(parameter, option, option.to_string());
+                                }
+                            });
+                    });
+                } else {
+                    ui.label("");
+                }
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut mapping.scaled, "Scale")
+                        .on_hover_text("Map values from 0 to 1 to the range");
+                    ui.add_enabled_ui(mapping.scaled, |ui| {
+                        ui.add(egui::DragValue::new(&mut mapping.min).speed(0.1));
+                        ui.label("to");
+                        ui.add(egui::DragValue::new(&mut mapping.max).speed(0.1));
+                    });
+                });
+
+                if ui.button("Remove").clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(index) = removed {
+        osc.mappings.remove(index);
+    }
+
+    if ui.button("Add Mapping").clicked() {
+        let mut mapping = OscMapping::default();
+        if let (OscTarget::Source { id, .. }, Some(&source)) =
+            (&mut mapping.target, sources.first())
+        {
+            *id = source;
+        }
+        osc.mappings.push(mapping);
+    }
+}
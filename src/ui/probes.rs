@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_file_dialog::FileDialogExt;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::Vec2;
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+use super::loading::SaveFileContents;
+use super::state::UiState;
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::simulation::plugin::ComponentIDs;
+
+/// Draws the window to add, edit, plot and export line and area probes.
+pub fn draw_probes(
+    mut commands: Commands,
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut ids: ResMut<ComponentIDs>,
+    mut line_probes: Query<(Entity, &mut LineProbe)>,
+    mut area_probes: Query<(Entity, &mut AreaProbe)>,
+) {
+    if !ui_state.show_probes {
+        return;
+    }
+
+    let mut line_probes = line_probes.iter_mut().collect::<Vec<_>>();
+    line_probes.sort_by_cached_key(|(_, probe)| probe.id);
+    let mut area_probes = area_probes.iter_mut().collect::<Vec<_>>();
+    area_probes.sort_by_cached_key(|(_, probe)| probe.id);
+
+    let delta_l = ui_state.delta_l;
+    let units = ui_state.pressure_units;
+    let mut show_probes = ui_state.show_probes;
+    let mut exports = vec![];
+
+    egui::Window::new("Probes")
+        .open(&mut show_probes)
+        .default_size(Vec2::new(450., 500.))
+        .resizable(true)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Add Line Probe")
+                    .on_hover_text("Sample the pressure of every cell between two points")
+                    .clicked()
+                {
+                    commands.spawn(LineProbe::new(
+                        ids.get_new_probe_id(),
+                        UVec2::new(SIMULATION_WIDTH / 7, SIMULATION_HEIGHT / 2),
+                        UVec2::new(SIMULATION_WIDTH * 6 / 7, SIMULATION_HEIGHT / 2),
+                    ));
+                }
+                if ui
+                    .button("Add Area Probe")
+                    .on_hover_text("Record the RMS and the maximum pressure over a rectangle")
+                    .clicked()
+                {
+                    commands.spawn(AreaProbe::new(
+                        ids.get_new_probe_id(),
+                        UVec2::new(SIMULATION_WIDTH * 3 / 7, SIMULATION_HEIGHT * 3 / 7),
+                        UVec2::new(SIMULATION_WIDTH * 4 / 7, SIMULATION_HEIGHT * 4 / 7),
+                    ));
+                }
+            });
+
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (entity, probe) in line_probes.iter_mut() {
+                    egui::CollapsingHeader::new(format!("Line Probe {}", probe.id))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Start");
+                                point_edit(ui, &mut probe.start);
+                                ui.label("End");
+                                point_edit(ui, &mut probe.end);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(format!("Length: {:.3} m", probe.length(delta_l)));
+                                ui.checkbox(
+                                    &mut probe.show_level,
+                                    format!("Show Level ({})", units.level_suffix()),
+                                )
+                                    .on_hover_text(
+                                        "Plot the RMS level averaged over the averaging time instead of the pressure",
+                                    );
+                            });
+
+                            Plot::new(format!("line_probe_{}", probe.id))
+                                .height(150.)
+                                .x_axis_label("Position (m)")
+                                .y_axis_label(if probe.show_level {
+                                    format!("Level ({})", units.level_suffix())
+                                } else {
+                                    units.pressure_column("Pressure")
+                                })
+                                .show(ui, |plot_ui| {
+                                    plot_ui.line(Line::new(PlotPoints::new(
+                                        probe.profile(delta_l, units),
+                                    )));
+                                });
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Export to CSV").clicked() {
+                                    exports.push((
+                                        format!("line_probe_{}.csv", probe.id),
+                                        probe.to_csv(delta_l, units),
+                                    ));
+                                }
+                                if ui.button("Delete").clicked() {
+                                    commands.entity(*entity).despawn();
+                                }
+                            });
+                        });
+                }
+
+                for (entity, probe) in area_probes.iter_mut() {
+                    egui::CollapsingHeader::new(format!("Area Probe {}", probe.id))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("From");
+                                point_edit(ui, &mut probe.start);
+                                ui.label("To");
+                                point_edit(ui, &mut probe.end);
+                            });
+                            if let Some([_, rms, max]) = probe.record.last() {
+                                ui.label(format!(
+                                    "{} cells, RMS: {:.4} {} ({:.1} {}), Max: {:.4} {}",
+                                    probe.cell_count(),
+                                    rms,
+                                    units.pressure_suffix(),
+                                    20. * rms.log10() + units.level_offset() as f64,
+                                    units.level_suffix(),
+                                    max,
+                                    units.pressure_suffix(),
+                                ));
+                            }
+
+                            Plot::new(format!("area_probe_{}", probe.id))
+                                .height(150.)
+                                .x_axis_label("Time (s)")
+                                .legend(Legend::default())
+                                .show(ui, |plot_ui| {
+                                    plot_ui.line(
+                                        Line::new(PlotPoints::new(
+                                            probe.record.iter().map(|x| [x[0], x[1]]).collect(),
+                                        ))
+                                        .name("RMS"),
+                                    );
+                                    plot_ui.line(
+                                        Line::new(PlotPoints::new(
+                                            probe.record.iter().map(|x| [x[0], x[2]]).collect(),
+                                        ))
+                                        .name("Max"),
+                                    );
+                                });
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Export to CSV").clicked() {
+                                    exports.push((
+                                        format!("area_probe_{}.csv", probe.id),
+                                        probe.to_csv(units),
+                                    ));
+                                }
+                                if ui.button("Delete").clicked() {
+                                    commands.entity(*entity).despawn();
+                                }
+                            });
+                        });
+                }
+            });
+        });
+
+    for (file_name, data) in exports {
+        commands
+            .dialog()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(&file_name)
+            .set_directory("./")
+            .set_title("Select a file to save to")
+            .save_file::<SaveFileContents>(data);
+    }
+
+    ui_state.show_probes = show_probes;
+}
+
+/// Edits a point in grid coordinates.
+pub fn point_edit(ui: &mut egui::Ui, point: &mut UVec2) {
+    ui.add(
+        egui::DragValue::new(&mut point.x)
+            .clamp_range(0..=SIMULATION_WIDTH - 1)
+            .prefix("x: "),
+    );
+    ui.add(
+        egui::DragValue::new(&mut point.y)
+            .clamp_range(0..=SIMULATION_HEIGHT - 1)
+            .prefix("y: "),
+    );
+}
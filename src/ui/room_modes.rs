@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::Vec2;
+use egui_extras::{Column, TableBuilder};
+
+use super::state::{RenderMode, UiState};
+use crate::components::wall::RectWall;
+use crate::events::Reset;
+use crate::math::room_modes::{nearest_mode, rectangular_room_modes};
+use crate::simulation::grid::Grid;
+use crate::simulation::mode_finder::ModeFinder;
+use crate::simulation::steady_state::{FrequencyBin, SteadyStateMap};
+
+/// Draws the window to detect the modes of a room and compare them to the analytical modes.
+pub fn draw_room_modes(
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut mode_finder: ResMut<ModeFinder>,
+    mut steady_state: ResMut<SteadyStateMap>,
+    mut reset_ev: EventWriter<Reset>,
+    rect_walls: Query<&RectWall>,
+    grid: Res<Grid>,
+) {
+    if !ui_state.show_room_modes {
+        return;
+    }
+
+    let mut rooms = rect_walls
+        .iter()
+        .filter(|wall| wall.is_hollow)
+        .collect::<Vec<_>>();
+    rooms.sort_by_cached_key(|wall| wall.id);
+    let room = mode_finder
+        .room
+        .and_then(|id| rooms.iter().find(|wall| wall.id == id).copied());
+
+    let speed = grid.wave_speed(ui_state.delta_l) as f64;
+    // the walls reflect at the center of their cells
+    let dimensions = room.map(|wall| {
+        let size = wall.rect.max - wall.rect.min;
+        [
+            size.x as f64 * ui_state.delta_l as f64,
+            size.y as f64 * ui_state.delta_l as f64,
+        ]
+    });
+    let analytical_modes = dimensions.map_or(vec![], |[width, height]| {
+        rectangular_room_modes(width, height, speed, mode_finder.max_frequency as f64)
+    });
+
+    let mut show_room_modes = ui_state.show_room_modes;
+
+    egui::Window::new("Room Modes")
+        .open(&mut show_room_modes)
+        .default_size(Vec2::new(450., 500.))
+        .resizable(true)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::ComboBox::from_label("Room")
+                .selected_text(match room {
+                    Some(wall) => format!("Rectangular Wall {}", wall.id),
+                    None => "-".to_string(),
+                })
+                .show_ui(ui, |ui| {
+                    for wall in &rooms {
+                        ui.selectable_value(
+                            &mut mode_finder.room,
+                            Some(wall.id),
+                            format!("Rectangular Wall {}", wall.id),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("A hollow rectangular wall that encloses the room");
+
+            if rooms.is_empty() {
+                ui.label("Draw a hollow rectangular wall to enclose a room.");
+            }
+
+            if let Some([width, height]) = dimensions {
+                ui.label(format!(
+                    "{:.2} m × {:.2} m, speed of sound on the grid: {:.1} m/s",
+                    width, height, speed
+                ));
+            }
+
+            ui.add(
+                egui::DragValue::new(&mut mode_finder.duration)
+                    .speed(0.01)
+                    .clamp_range(0.01..=10.)
+                    .prefix("Duration: ")
+                    .suffix(" s"),
+            )
+            .on_hover_text("The frequency resolution is the inverse of the duration");
+            ui.add(
+                egui::DragValue::new(&mut mode_finder.max_frequency)
+                    .speed(1.)
+                    .clamp_range(1. ..=(0.5 / grid.delta_t))
+                    .prefix("Max Frequency: ")
+                    .suffix(" Hz"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut mode_finder.min_prominence)
+                    .speed(0.1)
+                    .clamp_range(0. ..=100.)
+                    .prefix("Min Prominence: ")
+                    .suffix(" dB"),
+            );
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        room.is_some() && !mode_finder.is_measuring,
+                        egui::Button::new("Measure"),
+                    )
+                    .on_hover_text(
+                        "Reset the simulation, excite the room with an impulse and detect the peaks \
+                        in the spectra of several cells. Other sources keep playing.",
+                    )
+                    .clicked()
+                {
+                    if let Some(wall) = room {
+                        mode_finder.start(&wall.rect);
+                        ui_state.is_running = true;
+                        reset_ev.send(Reset { force: true });
+                    }
+                }
+
+                if mode_finder.is_measuring {
+                    let recorded = mode_finder.recorded_duration(grid.delta_t);
+                    ui.add(
+                        egui::ProgressBar::new(recorded / mode_finder.duration)
+                            .text(format!("{:.0} ms", recorded * 1000.)),
+                    );
+                }
+            });
+
+            ui.separator();
+            ui.heading("Detected Modes");
+
+            let mut shown_mode = None;
+
+            if mode_finder.modes.is_empty() {
+                ui.label("No modes detected yet.");
+            } else {
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .max_scroll_height(200.)
+                    .columns(Column::auto().at_least(70.), 4)
+                    .column(Column::remainder())
+                    .header(20., |mut header| {
+                        for title in ["Frequency", "Prominence", "Analytical", "Deviation", ""] {
+                            header.col(|ui| {
+                                ui.strong(title);
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        for mode in &mode_finder.modes {
+                            let nearest = nearest_mode(&analytical_modes, mode.frequency);
+                            body.row(20., |mut row| {
+                                row.col(|ui| {
+                                    ui.label(format!("{:.1} Hz", mode.frequency));
+                                });
+                                row.col(|ui| {
+                                    ui.label(format!("{:.1} dB", mode.prominence));
+                                });
+                                row.col(|ui| {
+                                    ui.label(match nearest {
+                                        Some(nearest) => format!(
+                                            "({}, {}) {:.1} Hz",
+                                            nearest.order[0], nearest.order[1], nearest.frequency
+                                        ),
+                                        None => "-".to_string(),
+                                    });
+                                });
+                                row.col(|ui| {
+                                    ui.label(match nearest {
+                                        Some(nearest) => format!(
+                                            "{:+.2} %",
+                                            (mode.frequency / nearest.frequency - 1.) * 100.
+                                        ),
+                                        None => "-".to_string(),
+                                    });
+                                });
+                                row.col(|ui| {
+                                    if ui
+                                        .button("Show Shape")
+                                        .on_hover_text(
+                                            "Excite the room again and render the steady state magnitude at this frequency",
+                                        )
+                                        .clicked()
+                                    {
+                                        shown_mode = Some(mode.frequency as f32);
+                                    }
+                                });
+                            });
+                        }
+                    });
+            }
+
+            if let Some(frequency) = shown_mode {
+                let index = match steady_state
+                    .bins
+                    .iter()
+                    .position(|bin| bin.frequency == frequency)
+                {
+                    Some(index) => index,
+                    None => {
+                        steady_state.bins.push(FrequencyBin::new(frequency));
+                        steady_state.bins.len() - 1
+                    }
+                };
+                steady_state.selected = index;
+                ui_state.render_mode = RenderMode::Magnitude;
+                ui_state.is_running = true;
+                mode_finder.excite();
+                reset_ev.send(Reset { force: true });
+            }
+
+            ui.separator();
+
+            egui::CollapsingHeader::new(format!("Analytical Modes ({})", analytical_modes.len()))
+                .show(ui, |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("analytical_modes")
+                        .max_height(200.)
+                        .show(ui, |ui| {
+                            for mode in &analytical_modes {
+                                ui.label(format!(
+                                    "({}, {}) {:.1} Hz",
+                                    mode.order[0], mode.order[1], mode.frequency
+                                ));
+                            }
+                        });
+                });
+        });
+
+    ui_state.show_room_modes = show_room_modes;
+}
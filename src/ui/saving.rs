@@ -1,20 +1,79 @@
+use egui_dock::Node;
 use serde::Serialize;
 
-use crate::components::microphone::Microphone;
-use crate::components::source::Source;
+use super::tabs::Tab;
+use crate::components::mic_array::MicArray;
+use crate::components::microphone::{Microphone, RecordState, RecordingPolicy};
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::components::source::{Source, SourceRecords};
 use crate::components::wall::{CircWall, RectWall};
+use crate::math::units::PressureUnits;
+use crate::remote::osc::{OscControl, OscSettings};
 use crate::render::gradient::Gradient;
+use crate::simulation::grid::{Cell, Grid};
+use crate::ui::state::UiState;
+
+/// Version of the scene file format, increased whenever the layout of [`SaveData`] changes.
+/// Older files are migrated when they are loaded.
+pub const SCENE_VERSION: u32 = 3;
+
+/// Magic bytes at the start of a checkpoint file, used to tell it apart from a JSON scene.
+pub const CHECKPOINT_MAGIC: &[u8; 8] = b"WFCHKPT\0";
+
+/// The solver and view settings that are saved with the scene. Used for serialization.
+#[derive(Serialize)]
+struct Settings<'a> {
+    delta_l: f32,
+    boundary_width: u32,
+    framerate: f64,
+    fft_window_size: usize,
+    reset_on_change: bool,
+    recording_policy: RecordingPolicy,
+    averaging_time: f32,
+    pressure_units: PressureUnits,
+    show_plots: bool,
+    plot_layout: &'a egui_dock::DockState<Tab>,
+    osc: OscSettings,
+}
 
 /// The data that is saved to a file. Used for serialization.
 #[derive(Serialize)]
 struct SaveData<'a> {
+    version: u32,
     sources: &'a Vec<&'a Source>,
     mics: &'a Vec<&'a Microphone>,
     rect_walls: &'a Vec<&'a RectWall>,
     circ_walls: &'a Vec<&'a CircWall>,
+    mic_arrays: &'a Vec<&'a MicArray>,
+    line_probes: &'a Vec<&'a LineProbe>,
+    area_probes: &'a Vec<&'a AreaProbe>,
     gradient: &'a Gradient,
     max_gradient: f32,
     min_gradient: f32,
+    settings: Settings<'a>,
+}
+
+/// A copy of the plot layout that can be written as JSON.
+/// The layout stores the rectangles of its nodes, which are infinite until the plots were shown
+/// and would be written as `null`. They are recomputed when the plots are drawn.
+fn finite_layout(plot_layout: &egui_dock::DockState<Tab>) -> egui_dock::DockState<Tab> {
+    let mut layout = plot_layout.clone();
+    let make_finite = |rect: &mut egui::Rect| {
+        if !rect.is_finite() {
+            *rect = egui::Rect::ZERO;
+        }
+    };
+    for (_, node) in layout.iter_all_nodes_mut() {
+        match node {
+            Node::Leaf { rect, viewport, .. } => {
+                make_finite(rect);
+                make_finite(viewport);
+            }
+            Node::Vertical { rect, .. } | Node::Horizontal { rect, .. } => make_finite(rect),
+            Node::Empty => {}
+        }
+    }
+    layout
 }
 
 /// Serializes the given data to a byte vector of JSON.
@@ -23,19 +82,120 @@ pub fn serialize(
     mics: &Vec<&Microphone>,
     rect_walls: &Vec<&RectWall>,
     circ_walls: &Vec<&CircWall>,
+    mic_arrays: &Vec<&MicArray>,
+    line_probes: &Vec<&LineProbe>,
+    area_probes: &Vec<&AreaProbe>,
     gradient: &Gradient,
-    max_gradient: f32,
-    min_gradient: f32,
+    ui_state: &UiState,
+    plot_layout: &egui_dock::DockState<Tab>,
+    osc: &OscControl,
 ) -> Result<Vec<u8>, serde_json::Error> {
+    let plot_layout = finite_layout(plot_layout);
     let save_data = SaveData {
+        version: SCENE_VERSION,
         sources,
         mics,
         rect_walls,
         circ_walls,
+        mic_arrays,
+        line_probes,
+        area_probes,
         gradient,
-        max_gradient,
-        min_gradient,
+        max_gradient: ui_state.max_gradient,
+        min_gradient: ui_state.min_gradient,
+        settings: Settings {
+            delta_l: ui_state.delta_l,
+            boundary_width: ui_state.boundary_width,
+            framerate: ui_state.framerate,
+            fft_window_size: ui_state.fft_window_size,
+            reset_on_change: ui_state.reset_on_change,
+            recording_policy: ui_state.recording_policy,
+            averaging_time: ui_state.averaging_time,
+            pressure_units: ui_state.pressure_units,
+            show_plots: ui_state.show_plots,
+            plot_layout: &plot_layout,
+            osc: osc.settings(),
+        },
     };
 
     serde_json::to_vec(&save_data)
 }
+
+/// The recording state of a microphone in a checkpoint.
+#[derive(Serialize)]
+struct MicRecord {
+    id: usize,
+    #[serde(flatten)]
+    state: RecordState,
+}
+
+/// The JSON header of a checkpoint. Used for serialization.
+#[derive(Serialize)]
+struct CheckpointHeader<'a> {
+    scene: serde_json::Value,
+    time_since_start: f32,
+    delta_l: f32,
+    boundary_width: u32,
+    mic_records: Vec<MicRecord>,
+    source_records: &'a SourceRecords,
+}
+
+/// Serializes a checkpoint that allows to continue the simulation where it stopped.
+///
+/// The file starts with [`CHECKPOINT_MAGIC`], followed by the length of the JSON header
+/// as a little endian u64 and the header itself. The header contains the `scene`
+/// (as produced by [`serialize`]), the simulation time, the solver settings, the mic records
+/// with the state of their decimation and stream, and the [`SourceRecords`].
+/// The maps of the field analyses ([`FieldMap`](crate::simulation::field_map::FieldMap) and
+/// [`SteadyStateMap`](crate::simulation::steady_state::SteadyStateMap)) are not saved,
+/// they restart when the checkpoint is loaded.
+/// After the header, `cur_cells`, `next_cells` and `pressure` of the grid follow
+/// as little endian f32 values, with the ports of a cell in the order bottom, left, top, right.
+pub fn serialize_checkpoint(
+    scene: &[u8],
+    grid: &Grid,
+    ui_state: &UiState,
+    time_since_start: f32,
+    mics: &[&Microphone],
+    source_records: &SourceRecords,
+) -> Result<Vec<u8>, serde_json::Error> {
+    let header = CheckpointHeader {
+        scene: serde_json::from_slice(scene)?,
+        time_since_start,
+        delta_l: ui_state.delta_l,
+        boundary_width: ui_state.boundary_width,
+        mic_records: mics
+            .iter()
+            .map(|mic| MicRecord {
+                id: mic.id,
+                state: mic.record_state(),
+            })
+            .collect(),
+        source_records,
+    };
+    let header = serde_json::to_vec(&header)?;
+
+    let cells = grid.cur_cells.len() + grid.next_cells.len();
+    let mut data = Vec::with_capacity(
+        CHECKPOINT_MAGIC.len() + 8 + header.len() + (cells * 4 + grid.pressure.len()) * 4,
+    );
+    data.extend_from_slice(CHECKPOINT_MAGIC);
+    data.extend_from_slice(&(header.len() as u64).to_le_bytes());
+    data.extend_from_slice(&header);
+    for Cell {
+        bottom,
+        left,
+        top,
+        right,
+    } in grid.cur_cells.iter().chain(&grid.next_cells)
+    {
+        for value in [bottom, left, top, right] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for value in &grid.pressure {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    Ok(data)
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::Vec2;
+
+use super::state::{ScreenshotFormat, ScreenshotSettings, SimTime, UiState};
+use crate::components::microphone::Microphone;
+use crate::components::probe::{AreaProbe, LineProbe};
+use crate::components::source::Source;
+use crate::math::constants::{SIMULATION_HEIGHT, SIMULATION_WIDTH};
+use crate::render::annotations::Overlays;
+use crate::render::gradient::Gradient;
+use crate::render::screenshot::export_screenshot;
+use crate::simulation::field_map::FieldMap;
+use crate::simulation::grid::Grid;
+use crate::simulation::steady_state::SteadyStateMap;
+
+/// Draws the dialog to export a screenshot with annotations.
+pub fn draw_screenshot_export(
+    mut commands: Commands,
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut settings: ResMut<ScreenshotSettings>,
+    grid: Res<Grid>,
+    gradient: Res<Gradient>,
+    field_map: Res<FieldMap>,
+    steady_state: Res<SteadyStateMap>,
+    sim_time: Res<SimTime>,
+    sources: Query<&Source>,
+    microphones: Query<&Microphone>,
+    line_probes: Query<&LineProbe>,
+    area_probes: Query<&AreaProbe>,
+) {
+    if !ui_state.show_screenshot_export {
+        return;
+    }
+
+    let mut show_screenshot_export = ui_state.show_screenshot_export;
+    let mut export = false;
+
+    egui::Window::new("Screenshot")
+        .open(&mut show_screenshot_export)
+        .default_size(Vec2::new(300., 300.))
+        .resizable(false)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::ComboBox::from_label("Format")
+                .selected_text(settings.format.to_string())
+                .show_ui(ui, |ui| {
+                    for format in [ScreenshotFormat::Png, ScreenshotFormat::Svg] {
+                        ui.selectable_value(&mut settings.format, format, format.to_string());
+                    }
+                });
+
+            ui.add(
+                egui::DragValue::new(&mut settings.scale)
+                    .clamp_range(1..=8)
+                    .prefix("Upscaling: ")
+                    .suffix("×"),
+            )
+            .on_hover_text("Size of a cell in pixels");
+            ui.label(format!(
+                "{} × {} px",
+                SIMULATION_WIDTH * settings.scale,
+                SIMULATION_HEIGHT * settings.scale
+            ));
+
+            ui.separator();
+
+            ui.checkbox(&mut settings.outlines, "Outlines")
+                .on_hover_text("Outline the sources, microphones, probes and walls");
+            ui.checkbox(&mut settings.color_bar, "Color bar");
+            ui.checkbox(&mut settings.scale_bar, "Scale bar");
+            ui.checkbox(&mut settings.time, "Simulation time");
+
+            ui.separator();
+
+            if ui.button("Export").clicked() {
+                export = true;
+            }
+        });
+
+    if export {
+        let overlays = Overlays::new(
+            sources.iter(),
+            microphones.iter(),
+            line_probes.iter(),
+            area_probes.iter(),
+        )
+        .with_walls(&grid, ui_state.boundary_width);
+
+        export_screenshot(
+            &settings,
+            &ui_state,
+            &grid,
+            &gradient,
+            &field_map,
+            &steady_state,
+            &overlays,
+            sim_time.time_since_start,
+            &mut commands,
+        );
+    }
+
+    ui_state.show_screenshot_export = show_screenshot_export;
+}
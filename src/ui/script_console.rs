@@ -0,0 +1,221 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use bevy_file_dialog::{DialogFileLoaded, FileDialogExt};
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use bevy_pixel_buffer::query::QueryPixelBuffer;
+use egui::Vec2;
+
+use super::loading::resize_pixel_buffer;
+use super::state::{ScriptConsole, UiState};
+use crate::scripting::engine::run_script;
+use crate::simulation::headless::HeadlessSimulation;
+
+/// Marker for script files loaded with the file dialog.
+pub struct ScriptFile;
+
+/// A console script that runs on the [`AsyncComputeTaskPool`].
+pub struct RunningScript {
+    task: Task<(HeadlessSimulation, Result<(), String>)>,
+    /// what the script printed since the last frame
+    output: Arc<Mutex<String>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl RunningScript {
+    /// Stops the script at its next operation, see [`run_script`].
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// Draws the console to edit and run scripts.
+pub fn draw_script_console(
+    mut commands: Commands,
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut console: ResMut<ScriptConsole>,
+) {
+    if !ui_state.show_script_console {
+        return;
+    }
+
+    let mut show_script_console = ui_state.show_script_console;
+
+    egui::Window::new("Script Console")
+        .open(&mut show_script_console)
+        .default_size(Vec2::new(500., 500.))
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                match console.running.as_ref() {
+                    Some(running) => {
+                        ui.spinner();
+                        if running.is_cancelled() {
+                            ui.label("Cancelling...");
+                        } else if ui
+                            .button("Cancel")
+                            .on_hover_text("Stop the script, its changes so far are kept")
+                            .clicked()
+                        {
+                            running.cancel();
+                        }
+                    }
+                    None => {
+                        if ui
+                            .button("Run")
+                            .on_hover_text("Run the script on the current scene")
+                            .clicked()
+                        {
+                            console.run_requested = true;
+                        }
+                    }
+                }
+                if ui.button("Open").clicked() {
+                    commands
+                        .dialog()
+                        .add_filter("Rhai script", &["rhai"])
+                        .set_directory("./")
+                        .set_title("Select a script to load")
+                        .load_file::<ScriptFile>();
+                }
+                if ui.button("Clear Output").clicked() {
+                    console.output.clear();
+                }
+            });
+
+            if console.running.is_some() {
+                ui.label(
+                    "The script runs on a copy of the scene, \
+                    changes made until it finishes are replaced by its result",
+                );
+            }
+
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_source("script_editor")
+                .max_height(300.)
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut console.script)
+                            .code_editor()
+                            .desired_rows(16)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_source("script_output")
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    ui.monospace(&console.output);
+                });
+        });
+
+    ui_state.show_script_console = show_script_console;
+}
+
+/// Puts a script loaded with the file dialog into the console.
+pub fn script_loaded(
+    mut ev_loaded: EventReader<DialogFileLoaded<ScriptFile>>,
+    mut console: ResMut<ScriptConsole>,
+) {
+    for file in ev_loaded.read() {
+        match String::from_utf8(file.contents.clone()) {
+            Ok(script) => console.script = script,
+            Err(_) => {
+                console.output += &format!("{} is not a text file\n", file.file_name);
+            }
+        }
+    }
+}
+
+/// Starts the script of the console on a copy of the simulation in the background
+/// and replaces the scene with the result once the script is finished.
+pub fn run_console_script(world: &mut World) {
+    if world.resource::<ScriptConsole>().run_requested {
+        world.resource_mut::<ScriptConsole>().run_requested = false;
+        if world.resource::<ScriptConsole>().running.is_none() {
+            start_script(world);
+        }
+    }
+
+    let mut console = world.resource_mut::<ScriptConsole>();
+    let Some(running) = console.running.as_mut() else {
+        return;
+    };
+    let printed = std::mem::take(&mut *running.output.lock().unwrap());
+    let finished = block_on(poll_once(&mut running.task));
+    console.output += &printed;
+    let Some((simulation, result)) = finished else {
+        return;
+    };
+
+    console.running = None;
+    if let Err(error) = result {
+        console.output += &format!("Error: {error}\n");
+    }
+    apply_script_result(world, simulation);
+}
+
+fn start_script(world: &mut World) {
+    let script = world.resource::<ScriptConsole>().script.clone();
+    let simulation = HeadlessSimulation::from_world(world);
+    let output = Arc::new(Mutex::new(String::new()));
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    let print_output = output.clone();
+    let task_cancel = cancel.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        run_script(
+            &script,
+            simulation,
+            move |text| {
+                let mut output = print_output.lock().unwrap();
+                output.push_str(text);
+                output.push('\n');
+            },
+            task_cancel,
+        )
+    });
+
+    world.resource_mut::<ScriptConsole>().running = Some(RunningScript {
+        task,
+        output,
+        cancel,
+    });
+}
+
+/// Replaces the scene with the simulation of a finished script.
+fn apply_script_result(world: &mut World, simulation: HeadlessSimulation) {
+    let boundary_width = world.resource::<UiState>().boundary_width;
+    let framerate = world.resource::<UiState>().framerate;
+    simulation.apply_to(world);
+
+    let mut ui_state = *world.resource::<UiState>();
+    if ui_state.boundary_width != boundary_width {
+        let mut state = SystemState::<QueryPixelBuffer>::new(world);
+        resize_pixel_buffer(&ui_state, &mut state.get_mut(world));
+    }
+    if ui_state.framerate != framerate {
+        // like in the ui, speeds above 60 Hz require the epilepsy warning to be confirmed
+        if !ui_state.read_epilepsy_warning {
+            ui_state.framerate = ui_state.framerate.min(60.);
+            world.insert_resource(ui_state);
+        }
+        world
+            .resource_mut::<Time<Fixed>>()
+            .set_timestep_hz(ui_state.framerate);
+    }
+}
@@ -2,6 +2,14 @@ use std::fmt;
 
 use bevy::prelude::*;
 
+use crate::components::microphone::RecordingPolicy;
+use crate::components::source::SourceType;
+use crate::export::wav::SampleFormat;
+use crate::math::room_acoustics::BandAnalysis;
+use crate::math::units::PressureUnits;
+use crate::simulation::templates::{Template, TemplateParameter};
+use crate::ui::script_console::RunningScript;
+
 /// A resource to store the current simulation time in seconds.
 #[derive(Default, Resource)]
 pub struct SimTime {
@@ -68,6 +76,288 @@ impl fmt::Display for FftScaling {
     }
 }
 
+/// What is drawn for every cell of the simulation.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RenderMode {
+    /// The instantaneous pressure
+    Pressure,
+    /// The RMS level over the last complete averaging window
+    Rms,
+    /// The highest absolute pressure since the last reset (max-hold)
+    Peak,
+    /// The equivalent continuous level over the averaging window
+    Leq,
+    /// The steady state magnitude at the selected frequency
+    Magnitude,
+    /// The steady state phase at the selected frequency
+    Phase,
+}
+
+impl RenderMode {
+    /// Whether the mode shows a level in dB that is accumulated over time
+    pub fn is_field_map(&self) -> bool {
+        matches!(self, RenderMode::Rms | RenderMode::Peak | RenderMode::Leq)
+    }
+
+    /// Whether the mode shows the steady state response at a single frequency
+    pub fn is_steady_state(&self) -> bool {
+        matches!(self, RenderMode::Magnitude | RenderMode::Phase)
+    }
+}
+
+impl fmt::Display for RenderMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderMode::Pressure => write!(f, "Pressure"),
+            RenderMode::Rms => write!(f, "RMS Level"),
+            RenderMode::Peak => write!(f, "Peak Level"),
+            RenderMode::Leq => write!(f, "Leq"),
+            RenderMode::Magnitude => write!(f, "Steady State Magnitude"),
+            RenderMode::Phase => write!(f, "Steady State Phase"),
+        }
+    }
+}
+
+/// The vector field that is drawn on top of the simulation.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VectorOverlay {
+    None,
+    /// The instantaneous particle velocity
+    Velocity,
+    /// The time averaged active intensity
+    Intensity,
+}
+
+impl fmt::Display for VectorOverlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VectorOverlay::None => write!(f, "None"),
+            VectorOverlay::Velocity => write!(f, "Particle Velocity"),
+            VectorOverlay::Intensity => write!(f, "Active Intensity"),
+        }
+    }
+}
+
+/// How multiple microphones are written to WAV files.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ChannelLayout {
+    /// One mono file per microphone
+    Mono,
+    /// One file with a channel per microphone
+    Multichannel,
+}
+
+impl fmt::Display for ChannelLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelLayout::Mono => write!(f, "Separate mono files"),
+            ChannelLayout::Multichannel => write!(f, "One multichannel file"),
+        }
+    }
+}
+
+/// A resource to store the settings of the WAV export dialog.
+#[derive(Resource)]
+pub struct WavExportSettings {
+    /// ids of the microphones to export
+    pub mic_ids: Vec<usize>,
+    pub sample_format: SampleFormat,
+    pub layout: ChannelLayout,
+    pub normalize: bool,
+    /// sample rate to resample to, `None` keeps the simulation sample rate
+    pub resample_rate: Option<u32>,
+    /// name of the exported file, mono files are named `<file_stem>_mic<id>.wav`
+    pub file_stem: String,
+    /// encoded mono files waiting for the user to pick a directory
+    pub pending: Vec<(usize, Vec<u8>)>,
+    pub error: Option<String>,
+}
+
+impl Default for WavExportSettings {
+    fn default() -> Self {
+        Self {
+            mic_ids: vec![],
+            sample_format: SampleFormat::Float32,
+            layout: ChannelLayout::Mono,
+            normalize: false,
+            resample_rate: None,
+            file_stem: "mics".to_string(),
+            pending: vec![],
+            error: None,
+        }
+    }
+}
+
+/// File formats of the screenshot export.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ScreenshotFormat {
+    Png,
+    /// the simulation is embedded as an image, the annotations are vectors
+    Svg,
+}
+
+impl fmt::Display for ScreenshotFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScreenshotFormat::Png => write!(f, "PNG"),
+            ScreenshotFormat::Svg => write!(f, "SVG"),
+        }
+    }
+}
+
+/// A resource to store the selected template and its parameters in the template gallery.
+#[derive(Resource)]
+pub struct TemplateGallery {
+    pub template: Template,
+    pub parameters: Vec<TemplateParameter>,
+}
+
+impl Default for TemplateGallery {
+    fn default() -> Self {
+        let template = Template::default();
+        Self {
+            template,
+            parameters: template.parameters(),
+        }
+    }
+}
+
+/// A resource to store the script and the output of the script console.
+#[derive(Resource)]
+pub struct ScriptConsole {
+    pub script: String,
+    pub output: String,
+    /// set by the run button, the script is started at the end of the frame
+    pub run_requested: bool,
+    /// the script that runs in the background
+    pub running: Option<RunningScript>,
+}
+
+impl Default for ScriptConsole {
+    fn default() -> Self {
+        Self {
+            script: "\
+clear();
+let source = add_source(200, 350, #{ type: \"sin\", frequency: 500.0 });
+let mic = add_mic(500, 350);
+add_rect_wall(340, 250, 360, 450);
+step(1000);
+let record = mic_record(mic);
+print(`${record.len()} samples, t = ${time()} s`);
+"
+            .to_string(),
+            output: String::new(),
+            run_requested: false,
+            running: None,
+        }
+    }
+}
+
+/// A resource to store the settings of the screenshot export dialog.
+#[derive(Resource, Clone, Copy)]
+pub struct ScreenshotSettings {
+    pub format: ScreenshotFormat,
+    /// size of a cell in pixels
+    pub scale: u32,
+    /// outlines of the sources, microphones, probes and walls
+    pub outlines: bool,
+    pub color_bar: bool,
+    pub scale_bar: bool,
+    pub time: bool,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        Self {
+            format: ScreenshotFormat::Png,
+            scale: 2,
+            outlines: true,
+            color_bar: true,
+            scale_bar: true,
+            time: true,
+        }
+    }
+}
+
+/// The impulse response of one microphone and its room acoustic parameters.
+pub struct IrResult {
+    pub mic_id: usize,
+    pub impulse_response: Vec<f64>,
+    /// broadband analysis followed by the octave bands
+    pub bands: Vec<BandAnalysis>,
+}
+
+/// A resource to store the settings and results of impulse response measurements.
+#[derive(Resource)]
+pub struct IrMeasurement {
+    /// id of the source used to excite the room
+    pub source_id: Option<usize>,
+    /// ids of the microphones to measure the impulse response at
+    pub mic_ids: Vec<usize>,
+    /// the signal the source plays during the measurement
+    pub excitation: SourceType,
+    /// the signal of the source before the measurement, `Some` while a measurement is running.
+    /// It is restored when the measurement is analyzed or canceled.
+    pub original_source: Option<SourceType>,
+    /// index of the band shown in the decay plot
+    pub band: usize,
+    pub results: Vec<IrResult>,
+}
+
+impl Default for IrMeasurement {
+    fn default() -> Self {
+        Self {
+            source_id: None,
+            mic_ids: vec![],
+            excitation: SourceType::default_sweep(),
+            original_source: None,
+            band: 0,
+            results: vec![],
+        }
+    }
+}
+
+/// The signal used as input of a transfer function.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TransferReference {
+    /// the emitted signal of the source with the given id
+    Source(usize),
+    /// the record of the microphone with the given id
+    Microphone(usize),
+}
+
+impl fmt::Display for TransferReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferReference::Source(id) => write!(f, "Source {}", id),
+            TransferReference::Microphone(id) => write!(f, "Microphone {}", id),
+        }
+    }
+}
+
+/// A resource to store the settings of the transfer function tab.
+#[derive(Resource)]
+pub struct TransferFunctionSettings {
+    pub reference: Option<TransferReference>,
+    /// id of the microphone used as output of the transfer function
+    pub mic_id: Option<usize>,
+    /// amount of samples per Welch segment
+    pub segment_size: usize,
+    /// maximum amount of averaged segments
+    pub averages: usize,
+}
+
+impl Default for TransferFunctionSettings {
+    fn default() -> Self {
+        Self {
+            reference: None,
+            mic_id: None,
+            segment_size: 1024,
+            averages: 16,
+        }
+    }
+}
+
 /// A resource to store the current state of the UI.
 /// This includes the current tool, the current place type, and various other settings.
 #[derive(Resource, PartialEq, Clone, Copy)]
@@ -102,6 +392,32 @@ pub struct UiState {
     pub min_gradient: f32,
     pub hide_gizmos: bool,
     pub show_new_warning: bool,
+    /// id of the [`MicArray`](crate::components::mic_array::MicArray) shown in the DOA tab
+    pub doa_array: Option<usize>,
+    pub show_wav_export: bool,
+    pub recording_policy: RecordingPolicy,
+    /// global record/pause control for all armed microphones
+    pub is_recording: bool,
+    pub render_mode: RenderMode,
+    /// whether pressures are shown as raw values or in Pa
+    pub pressure_units: PressureUnits,
+    /// lower end of the color scale of the field maps in dB
+    pub min_level: f32,
+    /// upper end of the color scale of the field maps in dB
+    pub max_level: f32,
+    /// time constant of the RMS level and length of the Leq window in seconds
+    pub averaging_time: f32,
+    pub vector_overlay: VectorOverlay,
+    /// distance between two arrows of the vector overlay in cells
+    pub vector_spacing: u32,
+    pub show_steady_state: bool,
+    pub show_room_modes: bool,
+    pub show_probes: bool,
+    pub show_field_recorder: bool,
+    pub show_animation_export: bool,
+    pub show_screenshot_export: bool,
+    pub show_template_gallery: bool,
+    pub show_script_console: bool,
 }
 
 impl Default for UiState {
@@ -138,6 +454,44 @@ impl Default for UiState {
             min_gradient: -2.,
             hide_gizmos: false,
             show_new_warning: false,
+            doa_array: None,
+            show_wav_export: false,
+            recording_policy: RecordingPolicy::default(),
+            is_recording: true,
+            render_mode: RenderMode::Pressure,
+            pressure_units: PressureUnits::Raw,
+            min_level: -40.,
+            max_level: 20.,
+            averaging_time: 0.1,
+            vector_overlay: VectorOverlay::None,
+            vector_spacing: 16,
+            show_steady_state: false,
+            show_room_modes: false,
+            show_probes: false,
+            show_field_recorder: false,
+            show_animation_export: false,
+            show_screenshot_export: false,
+            show_template_gallery: false,
+            show_script_console: false,
+        }
+    }
+}
+
+impl UiState {
+    /// The values at both ends of the color scale of the current render mode and their unit
+    pub fn color_scale(&self) -> (f32, f32, &'static str) {
+        match self.render_mode {
+            RenderMode::Pressure => (
+                self.min_gradient,
+                self.max_gradient,
+                self.pressure_units.pressure_suffix(),
+            ),
+            RenderMode::Phase => (-180., 180., "°"),
+            _ => (
+                self.min_level,
+                self.max_level,
+                self.pressure_units.level_suffix(),
+            ),
         }
     }
 }
@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::{Color32, Vec2};
+
+use super::state::{RenderMode, UiState};
+use crate::render::screenshot::export_steady_state;
+use crate::simulation::grid::Grid;
+use crate::simulation::steady_state::{FrequencyBin, SteadyStateMap};
+
+/// Draws the window to choose the frequencies of the steady state maps.
+pub fn draw_steady_state(
+    mut commands: Commands,
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut steady_state: ResMut<SteadyStateMap>,
+    grid: Res<Grid>,
+) {
+    if !ui_state.show_steady_state {
+        return;
+    }
+
+    let mut show_steady_state = ui_state.show_steady_state;
+    let mut export = false;
+
+    egui::Window::new("Steady State")
+        .open(&mut show_steady_state)
+        .default_size(Vec2::new(400., 400.))
+        .resizable(false)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(
+                "The magnitude and phase of every cell are calculated over blocks of whole periods \
+                that are at least as long as the averaging time.",
+            );
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(
+                    &mut ui_state.render_mode,
+                    RenderMode::Magnitude,
+                    "Magnitude",
+                );
+                ui.selectable_value(&mut ui_state.render_mode, RenderMode::Phase, "Phase");
+            });
+
+            ui.separator();
+
+            let mut removed = None;
+            let SteadyStateMap { bins, selected } = &mut *steady_state;
+            let can_remove = bins.len() > 1;
+
+            for (index, bin) in bins.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut *selected, index, "");
+
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut bin.frequency)
+                                .speed(1.)
+                                .clamp_range(1. ..=(0.5 / grid.delta_t))
+                                .suffix(" Hz"),
+                        )
+                        .changed()
+                    {
+                        bin.clear();
+                    }
+
+                    let block_size = bin.block_size(grid.delta_t, ui_state.averaging_time);
+                    ui.label(format!(
+                        "Block: {:.2} ms",
+                        block_size as f32 * grid.delta_t * 1000.
+                    ));
+
+                    if bin.is_converged() {
+                        ui.colored_label(Color32::GREEN, "Converged");
+                    } else {
+                        match bin.change {
+                            Some(change) => ui.colored_label(
+                                Color32::YELLOW,
+                                format!("Settling ({:.1} dB)", 20. * change.log10()),
+                            ),
+                            None => ui.label("Waiting for the first blocks"),
+                        }
+                        .on_hover_text(
+                            "Relative change of the complex amplitudes between the last two blocks",
+                        );
+                    }
+
+                    if ui
+                        .add_enabled(can_remove, egui::Button::new("Remove"))
+                        .clicked()
+                    {
+                        removed = Some(index);
+                    }
+                });
+            }
+
+            if let Some(index) = removed {
+                bins.remove(index);
+                if *selected >= bins.len() {
+                    *selected = bins.len() - 1;
+                }
+            }
+
+            ui.add_space(5.);
+
+            ui.horizontal(|ui| {
+                if ui.button("Add Frequency").clicked() {
+                    let frequency = bins.last().map_or(1000., |bin| bin.frequency * 2.);
+                    bins.push(FrequencyBin::new(frequency.min(0.5 / grid.delta_t)));
+                }
+
+                if ui
+                    .add_enabled(
+                        bins.iter().any(|bin| bin.blocks > 0),
+                        egui::Button::new("Export to CSV"),
+                    )
+                    .on_hover_text("Save the complex amplitudes of all frequencies to a CSV file")
+                    .clicked()
+                {
+                    export = true;
+                }
+            });
+        });
+
+    if export {
+        export_steady_state(&ui_state, &steady_state, &mut commands);
+    }
+
+    ui_state.show_steady_state = show_steady_state;
+}
@@ -1,17 +1,48 @@
+use std::path::Path;
+
+use bevy::ecs::entity::Entity;
 use bevy::ecs::system::{Commands, Resource};
+use bevy::ecs::world::World;
 use bevy::math::UVec2;
 use bevy_file_dialog::FileDialogExt;
 use bevy_pixel_buffer::pixel_buffer::PixelBufferSize;
 use bevy_pixel_buffer::query::PixelBuffersItem;
+use egui::Color32;
+use egui_extras::{Column, TableBuilder};
 use egui_plot::{GridMark, Line, Plot, PlotBounds, PlotPoints};
 use plotters::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use super::loading::SaveFileContents;
-use super::state::{FftScaling, UiState};
-use crate::components::microphone::Microphone;
+use super::state::{
+    FftScaling, IrMeasurement, IrResult, TransferFunctionSettings, TransferReference, UiState,
+};
+use crate::components::mic_array::MicArray;
+use crate::components::microphone::{Microphone, RecordingPolicy};
+use crate::components::source::{Source, SourceRecords, SourceType};
+use crate::events::Reset;
+use crate::math::beamforming::{delay_and_sum, estimate_doa, srp_phat};
 use crate::math::fft::calc_mic_spectrum;
+use crate::math::room_acoustics::{analyze_impulse_response, deconvolve};
+use crate::math::transfer_function::{align_records, transfer_function};
 use crate::math::transformations::interpolate;
 
+/// Formats an amount of bytes with a binary prefix, e.g. `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
 #[derive(Resource)]
 pub struct DockState {
     pub tree: egui_dock::DockState<Tab>,
@@ -24,17 +55,27 @@ impl Default for DockState {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Tab {
     Volume,
     Frequency,
     Spectrogram,
+    Doa,
+    RoomAcoustics,
+    TransferFunction,
 }
 
 pub struct PlotTabs<'a> {
     mics: &'a mut Vec<&'a mut Microphone>,
+    mic_arrays: &'a mut Vec<(Entity, &'a mut MicArray)>,
+    sources: &'a mut Vec<&'a mut Source>,
+    ir_measurement: &'a mut IrMeasurement,
+    source_records: &'a SourceRecords,
+    transfer_function: &'a mut TransferFunctionSettings,
     pixel_buffer: &'a mut PixelBuffersItem<'a>,
     commands: &'a mut Commands<'a, 'a>,
-    delta_t: f32,
+    /// time between two recorded microphone samples
+    sample_interval: f32,
     sim_time: f64,
     delta_time: f64,
     ui_state: &'a mut UiState,
@@ -43,23 +84,82 @@ pub struct PlotTabs<'a> {
 impl<'a> PlotTabs<'a> {
     pub fn new(
         mics: &'a mut Vec<&'a mut Microphone>,
+        mic_arrays: &'a mut Vec<(Entity, &'a mut MicArray)>,
+        sources: &'a mut Vec<&'a mut Source>,
+        ir_measurement: &'a mut IrMeasurement,
+        source_records: &'a SourceRecords,
+        transfer_function: &'a mut TransferFunctionSettings,
         pixel_buffer: &'a mut PixelBuffersItem<'a>,
         commands: &'a mut Commands<'a, 'a>,
-        delta_t: f32,
+        sample_interval: f32,
         sim_time: f64,
         delta_time: f64,
         ui_state: &'a mut UiState,
     ) -> Self {
         Self {
             mics,
+            mic_arrays,
+            sources,
+            ir_measurement,
+            source_records,
+            transfer_function,
             pixel_buffer,
             commands,
-            delta_t,
+            sample_interval,
             sim_time,
             ui_state,
             delta_time,
         }
     }
+
+    /// Pauses the simulation and restores the signal the measured source had before the measurement.
+    fn finish_measurement(&mut self) {
+        let Some(original) = self.ir_measurement.original_source.take() else {
+            return;
+        };
+        if let Some(source) = self
+            .sources
+            .iter_mut()
+            .find(|source| Some(source.id) == self.ir_measurement.source_id)
+        {
+            source.source_type = original;
+        }
+        self.ui_state.is_running = false;
+    }
+}
+
+/// Recreates the signal a microphone would have recorded of the excitation, at the times of its
+/// record and averaged like the record of a [`RecordingPolicy::Decimated`] policy.
+fn excitation_record(
+    source: &Source,
+    mic: &Microphone,
+    sample_interval: f32,
+    decimation: usize,
+) -> Vec<f64> {
+    let (Some(first), Some(last)) = (mic.record.first(), mic.record.last()) else {
+        return vec![];
+    };
+    let delta_t = sample_interval as f64 / decimation as f64;
+    // the first recorded sample averages the steps before it
+    let first_step = (first[0] / delta_t).round() as i64 - (decimation as i64 - 1);
+    let last_step = (last[0] / delta_t).round() as i64;
+
+    let policy = RecordingPolicy::Decimated {
+        factor: decimation,
+        seconds: (last[0] - first[0]) as f32 + 2. * sample_interval,
+    };
+    let mut reference = Microphone::default();
+    for step in first_step..=last_step {
+        let time = step as f64 * delta_t;
+        reference.record_sample(
+            time,
+            source.calc(time as f32) as f64,
+            policy,
+            delta_t as f32,
+            Path::new("."),
+        );
+    }
+    reference.record.iter().map(|x| x[1]).collect()
 }
 
 impl<'a> egui_dock::TabViewer for PlotTabs<'a> {
@@ -69,6 +169,9 @@ impl<'a> egui_dock::TabViewer for PlotTabs<'a> {
             Tab::Volume => "Volume".into(),
             Tab::Frequency => "Frequency".into(),
             Tab::Spectrogram => "Spectrogram".into(),
+            Tab::Doa => "DOA".into(),
+            Tab::RoomAcoustics => "Room Acoustics".into(),
+            Tab::TransferFunction => "Transfer Function".into(),
         }
     }
 
@@ -86,6 +189,18 @@ impl<'a> egui_dock::TabViewer for PlotTabs<'a> {
                 ui.horizontal(|ui| {
                     ui.checkbox(&mut self.ui_state.scroll_volume_plot, "Scroll Volume Plot");
 
+                    ui.add(egui::Separator::default().vertical());
+
+                    let memory_usage = self.mics.iter().map(|mic| mic.memory_usage()).sum();
+                    ui.label(format!(
+                        "{} ({})",
+                        self.ui_state.recording_policy,
+                        format_bytes(memory_usage)
+                    ))
+                    .on_hover_text(
+                        "The recording policy and memory used by the microphone recordings. The policy can be changed in the preferences.",
+                    );
+
                     ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
                         if ui
                             .button("Export to SVG")
@@ -152,7 +267,7 @@ impl<'a> egui_dock::TabViewer for PlotTabs<'a> {
                                     .x_labels(5)
                                     .y_labels(5)
                                     .y_label_formatter(&|x| format!("{:.2}", x))
-                                    .y_desc("Amplitude")
+                                    .y_desc(self.ui_state.pressure_units.pressure_label())
                                     .x_desc("Simulation Time (s)")
                                     .draw()
                                     .unwrap();
@@ -205,15 +320,21 @@ impl<'a> egui_dock::TabViewer for PlotTabs<'a> {
                 ui.separator();
 
                 let scroll_volume_plot = self.ui_state.scroll_volume_plot;
+                let units = self.ui_state.pressure_units;
 
                 Plot::new("mic_plot")
                     .allow_zoom([!scroll_volume_plot, !scroll_volume_plot])
                     .allow_drag(!scroll_volume_plot)
                     .allow_scroll(!scroll_volume_plot)
                     .x_axis_label("Simulation Time (ms)")
-                    .y_axis_label("Amplitude")
-                    .label_formatter(|_, value| {
-                        format!("Amplitude: {:.2}\nTime: {:.4} ms", value.y, value.x)
+                    .y_axis_label(units.pressure_label())
+                    .label_formatter(move |_, value| {
+                        format!(
+                            "{}: {:.2}\nTime: {:.4} ms",
+                            units.pressure_label(),
+                            value.y,
+                            value.x
+                        )
                     })
                     .legend(egui_plot::Legend::default())
                     .show(ui, |plot_ui| {
@@ -345,7 +466,7 @@ impl<'a> egui_dock::TabViewer for PlotTabs<'a> {
                             let mapped_spectrum = calc_mic_spectrum(
                                 mic,
                                 self.ui_state.fft_scaling,
-                                self.delta_t,
+                                self.sample_interval,
                                 self.ui_state.fft_window_size,
                             );
 
@@ -484,10 +605,710 @@ impl<'a> egui_dock::TabViewer for PlotTabs<'a> {
                     pixel_size: UVec2::new(1, 1),
                 };
             }
+            Tab::Doa => {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Array")
+                        .selected_text(match self.ui_state.doa_array {
+                            Some(id) => format!("Array {}", id),
+                            None => "None".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            for (_, array) in self.mic_arrays.iter() {
+                                ui.selectable_value(
+                                    &mut self.ui_state.doa_array,
+                                    Some(array.id),
+                                    format!("Array {}", array.id),
+                                );
+                            }
+                        });
+
+                    if ui
+                        .button("New Array")
+                        .on_hover_text("Create a new array containing all microphones")
+                        .clicked()
+                    {
+                        let id = self
+                            .mic_arrays
+                            .iter()
+                            .map(|(_, array)| array.id + 1)
+                            .max()
+                            .unwrap_or(0);
+                        self.commands.spawn(MicArray::new(
+                            id,
+                            self.mics.iter().map(|mic| mic.id).collect(),
+                        ));
+                        self.ui_state.doa_array = Some(id);
+                    }
+
+                    if let Some((entity, array)) = self
+                        .mic_arrays
+                        .iter_mut()
+                        .find(|(_, array)| Some(array.id) == self.ui_state.doa_array)
+                    {
+                        ui.add(egui::Separator::default().vertical());
+
+                        ui.menu_button("Array Microphones", |ui| {
+                            for mic in self.mics.iter() {
+                                let mut is_member = array.mic_ids.contains(&mic.id);
+                                if ui
+                                    .checkbox(&mut is_member, format!("Microphone {}", mic.id))
+                                    .changed()
+                                {
+                                    if is_member {
+                                        array.mic_ids.push(mic.id);
+                                    } else {
+                                        array.mic_ids.retain(|id| *id != mic.id);
+                                    }
+                                }
+                            }
+                        });
+
+                        ui.add(egui::Separator::default().vertical());
+
+                        ui.add(
+                            egui::Slider::new(&mut array.steering_angle, 0.0..=360.0)
+                                .text("Steering Angle (°)"),
+                        );
+
+                        if ui
+                            .add(egui::Button::new("Delete").fill(Color32::DARK_RED))
+                            .clicked()
+                        {
+                            self.commands.entity(*entity).despawn();
+                            self.ui_state.doa_array = None;
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                let Some((_, array)) = self
+                    .mic_arrays
+                    .iter()
+                    .find(|(_, array)| Some(array.id) == self.ui_state.doa_array)
+                else {
+                    ui.add_space(20.);
+                    ui.vertical_centered(|ui| {
+                        ui.label("Create or select a microphone array to estimate the direction of arrival.")
+                    });
+                    return;
+                };
+
+                let mics = self.mics.iter().map(|mic| &**mic).collect::<Vec<_>>();
+                let array_mics = array.mics(&mics);
+
+                let doa_map = srp_phat(
+                    &array_mics,
+                    self.ui_state.delta_l,
+                    self.sample_interval,
+                    self.ui_state.fft_window_size,
+                    2.,
+                );
+                let beamformer_output = delay_and_sum(
+                    &array_mics,
+                    array.steering_angle,
+                    self.ui_state.delta_l,
+                    self.sample_interval,
+                );
+
+                ui.label(match estimate_doa(&doa_map) {
+                    Some(angle) => format!("Estimated direction of arrival: {:.1}°", angle),
+                    None => "At least two microphones are needed for a DOA estimate.".to_string(),
+                });
+
+                let steering_angle = array.steering_angle as f64;
+
+                ui.columns(2, |columns| {
+                    Plot::new("doa_plot")
+                        .data_aspect(1.)
+                        .allow_zoom([false, false])
+                        .allow_scroll(false)
+                        .allow_drag(false)
+                        .allow_boxed_zoom(false)
+                        .include_x(-1.1)
+                        .include_x(1.1)
+                        .include_y(-1.1)
+                        .include_y(1.1)
+                        .show_axes(false)
+                        .label_formatter(|_, value| {
+                            format!(
+                                "Angle: {:.1}°\nPower: {:.2}",
+                                value.y.atan2(value.x).to_degrees().rem_euclid(360.),
+                                value.x.hypot(value.y)
+                            )
+                        })
+                        .legend(egui_plot::Legend::default())
+                        .show(&mut columns[0], |plot_ui| {
+                            let unit_circle = (0..=360)
+                                .map(|angle| {
+                                    let (sin, cos) = (angle as f64).to_radians().sin_cos();
+                                    [cos, sin]
+                                })
+                                .collect::<Vec<_>>();
+                            plot_ui.line(
+                                Line::new(PlotPoints::new(unit_circle)).color(Color32::DARK_GRAY),
+                            );
+
+                            let mut points = doa_map
+                                .iter()
+                                .map(|[angle, power]| {
+                                    let (sin, cos) = angle.to_radians().sin_cos();
+                                    [power * cos, power * sin]
+                                })
+                                .collect::<Vec<_>>();
+                            if let Some(first) = points.first().copied() {
+                                points.push(first);
+                            }
+                            plot_ui.line(Line::new(PlotPoints::new(points)).name("SRP-PHAT"));
+
+                            let (sin, cos) = steering_angle.to_radians().sin_cos();
+                            plot_ui.line(
+                                Line::new(PlotPoints::new(vec![[0., 0.], [cos, sin]]))
+                                    .name("Steering Direction"),
+                            );
+                        });
+
+                    Plot::new("beamformer_plot")
+                        .x_axis_label("Simulation Time (ms)")
+                        .y_axis_label(self.ui_state.pressure_units.pressure_label())
+                        .legend(egui_plot::Legend::default())
+                        .show(&mut columns[1], |plot_ui| {
+                            let values = beamformer_output
+                                .iter()
+                                .map(|x| [x[0] * 1000., x[1]])
+                                .collect();
+                            plot_ui
+                                .line(Line::new(PlotPoints::new(values)).name("Beamformer Output"));
+                        });
+                });
+            }
+            Tab::RoomAcoustics => {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Source")
+                        .selected_text(match self.ir_measurement.source_id {
+                            Some(id) => format!("Source {}", id),
+                            None => "None".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            for source in self.sources.iter() {
+                                ui.selectable_value(
+                                    &mut self.ir_measurement.source_id,
+                                    Some(source.id),
+                                    format!("Source {}", source.id),
+                                );
+                            }
+                        });
+
+                    ui.menu_button("Microphones", |ui| {
+                        for mic in self.mics.iter() {
+                            let mut selected = self.ir_measurement.mic_ids.contains(&mic.id);
+                            if ui
+                                .checkbox(&mut selected, format!("Microphone {}", mic.id))
+                                .changed()
+                            {
+                                if selected {
+                                    self.ir_measurement.mic_ids.push(mic.id);
+                                } else {
+                                    self.ir_measurement.mic_ids.retain(|id| *id != mic.id);
+                                }
+                            }
+                        }
+                    });
+
+                    let measuring = self.ir_measurement.original_source.is_some();
+
+                    // the analysis recreates the excitation, it cannot change during a measurement
+                    ui.add_enabled_ui(!measuring, |ui| {
+                        egui::ComboBox::from_label("Excitation")
+                            .selected_text(self.ir_measurement.excitation.to_string())
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut self.ir_measurement.excitation,
+                                    SourceType::default_impulse(),
+                                    "Impulse",
+                                );
+                                ui.selectable_value(
+                                    &mut self.ir_measurement.excitation,
+                                    SourceType::default_sweep(),
+                                    "Sine Sweep",
+                                );
+                            });
+                    });
+
+                    ui.add(egui::Separator::default().vertical());
+
+                    let ready = self.ir_measurement.source_id.is_some()
+                        && !self.ir_measurement.mic_ids.is_empty();
+
+                    if ui
+                        .add_enabled(ready, egui::Button::new("Measure"))
+                        .on_hover_text("Reset the simulation and excite it with the selected source. Other sources should be removed for a clean measurement.")
+                        .clicked()
+                    {
+                        if let Some(source) = self
+                            .sources
+                            .iter_mut()
+                            .find(|source| Some(source.id) == self.ir_measurement.source_id)
+                        {
+                            // keep the signal from before the first of repeated measurements
+                            self.ir_measurement
+                                .original_source
+                                .get_or_insert(source.source_type);
+                            source.source_type = self.ir_measurement.excitation;
+                        }
+                        for mic in self.mics.iter_mut() {
+                            if self.ir_measurement.mic_ids.contains(&mic.id) {
+                                mic.armed = true;
+                            }
+                        }
+                        self.ir_measurement.results.clear();
+                        self.ui_state.is_recording = true;
+                        self.ui_state.is_running = true;
+                        self.commands.add(|world: &mut World| {
+                            world.send_event(Reset { force: true });
+                        });
+                    }
+
+                    if ui
+                        .add_enabled(measuring, egui::Button::new("Cancel"))
+                        .on_hover_text("Stop the measurement and restore the signal of the source")
+                        .clicked()
+                    {
+                        self.finish_measurement();
+                    }
+
+                    if ui
+                        .add_enabled(ready, egui::Button::new("Analyze"))
+                        .on_hover_text("Calculate the impulse responses from the current recordings. This stops a running measurement and restores the signal of the source.")
+                        .clicked()
+                    {
+                        if let Some(source) = self
+                            .sources
+                            .iter()
+                            .find(|source| Some(source.id) == self.ir_measurement.source_id)
+                        {
+                            // the excitation is recreated from the source signal at the recorded times
+                            let excitation_source = Source {
+                                source_type: self.ir_measurement.excitation,
+                                ..**source
+                            };
+                            let sample_interval = self.sample_interval as f64;
+                            let decimation = self.ui_state.recording_policy.decimation();
+
+                            self.ir_measurement.results = self
+                                .mics
+                                .iter()
+                                .filter(|mic| self.ir_measurement.mic_ids.contains(&mic.id))
+                                .map(|mic| {
+                                    let response =
+                                        mic.record.iter().map(|x| x[1]).collect::<Vec<_>>();
+                                    let excitation = excitation_record(
+                                        &excitation_source,
+                                        mic,
+                                        self.sample_interval,
+                                        decimation,
+                                    );
+                                    let impulse_response = deconvolve(&response, &excitation);
+                                    let bands =
+                                        analyze_impulse_response(&impulse_response, sample_interval);
+                                    IrResult {
+                                        mic_id: mic.id,
+                                        impulse_response,
+                                        bands,
+                                    }
+                                })
+                                .collect();
+                        }
+                        self.finish_measurement();
+                    }
+
+                    if ui
+                        .add_enabled(
+                            !self.ir_measurement.results.is_empty(),
+                            egui::Button::new("Export to CSV"),
+                        )
+                        .on_hover_text("Save the room acoustic parameters to a CSV file")
+                        .clicked()
+                    {
+                        let mut wtr = csv::Writer::from_writer(vec![]);
+                        wtr.write_record([
+                            "microphone",
+                            "band (Hz)",
+                            "EDT (s)",
+                            "T20 (s)",
+                            "T30 (s)",
+                            "C50 (dB)",
+                            "C80 (dB)",
+                            "D50",
+                            "centre time (s)",
+                        ])
+                        .unwrap();
+                        for result in &self.ir_measurement.results {
+                            for band in &result.bands {
+                                let p = band.parameters;
+                                let mut record = vec![
+                                    result.mic_id.to_string(),
+                                    band.center_frequency
+                                        .map(|f| f.to_string())
+                                        .unwrap_or_else(|| "broadband".to_string()),
+                                ];
+                                record.extend(
+                                    [p.edt, p.t20, p.t30, p.c50, p.c80, p.d50, p.centre_time]
+                                        .iter()
+                                        .map(|value| value.map(|v| v.to_string()).unwrap_or_default()),
+                                );
+                                wtr.write_record(&record).unwrap();
+                            }
+                        }
+
+                        self.commands
+                            .dialog()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("room_acoustics.csv")
+                            .set_directory("./")
+                            .set_title("Select a file to save to")
+                            .save_file::<SaveFileContents>(wtr.into_inner().unwrap());
+                    }
+                });
+
+                ui.separator();
+
+                if self.ir_measurement.results.is_empty() {
+                    ui.add_space(20.);
+                    ui.vertical_centered(|ui| {
+                        ui.label("Select a source and microphones, measure and analyze to calculate room acoustic parameters.")
+                    });
+                    return;
+                }
+
+                let band_names = self.ir_measurement.results[0]
+                    .bands
+                    .iter()
+                    .map(|band| match band.center_frequency {
+                        Some(frequency) => format!("{} Hz", frequency),
+                        None => "Broadband".to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                self.ir_measurement.band = self.ir_measurement.band.min(band_names.len() - 1);
+
+                ui.columns(2, |columns| {
+                    let format = |value: Option<f64>, precision: usize| {
+                        value
+                            .map(|v| format!("{:.*}", precision, v))
+                            .unwrap_or_else(|| "-".to_string())
+                    };
+
+                    TableBuilder::new(&mut columns[0])
+                        .striped(true)
+                        .column(Column::auto())
+                        .column(Column::auto())
+                        .columns(Column::remainder(), 7)
+                        .header(20., |mut header| {
+                            for title in [
+                                "Mic", "Band", "EDT (s)", "T20 (s)", "T30 (s)", "C50 (dB)",
+                                "C80 (dB)", "D50", "Ts (ms)",
+                            ] {
+                                header.col(|ui| {
+                                    ui.strong(title);
+                                });
+                            }
+                        })
+                        .body(|mut body| {
+                            for result in &self.ir_measurement.results {
+                                for (band, name) in result.bands.iter().zip(&band_names) {
+                                    let p = band.parameters;
+                                    body.row(18., |mut row| {
+                                        row.col(|ui| {
+                                            ui.label(result.mic_id.to_string());
+                                        });
+                                        row.col(|ui| {
+                                            ui.label(name);
+                                        });
+                                        for text in [
+                                            format(p.edt, 2),
+                                            format(p.t20, 2),
+                                            format(p.t30, 2),
+                                            format(p.c50, 1),
+                                            format(p.c80, 1),
+                                            format(p.d50, 2),
+                                            format(p.centre_time.map(|t| t * 1000.), 1),
+                                        ] {
+                                            row.col(|ui| {
+                                                ui.label(text);
+                                            });
+                                        }
+                                    });
+                                }
+                            }
+                        });
+
+                    let ui = &mut columns[1];
+                    egui::ComboBox::from_label("Band")
+                        .selected_text(&band_names[self.ir_measurement.band])
+                        .show_ui(ui, |ui| {
+                            for (index, name) in band_names.iter().enumerate() {
+                                ui.selectable_value(&mut self.ir_measurement.band, index, name);
+                            }
+                        });
+
+                    let sample_interval = self.sample_interval as f64;
+                    Plot::new("decay_plot")
+                        .x_axis_label("Time (ms)")
+                        .y_axis_label("Energy Decay (dB)")
+                        .include_y(0.)
+                        .include_y(-60.)
+                        .legend(egui_plot::Legend::default())
+                        .show(ui, |plot_ui| {
+                            for result in &self.ir_measurement.results {
+                                if let Some(band) = result.bands.get(self.ir_measurement.band) {
+                                    let values = band
+                                        .decay_curve
+                                        .iter()
+                                        .enumerate()
+                                        // the end of the curve drops to -inf
+                                        .filter(|(_, level)| level.is_finite())
+                                        .map(|(i, level)| {
+                                            [i as f64 * sample_interval * 1000., *level]
+                                        })
+                                        .collect();
+                                    plot_ui.line(
+                                        Line::new(PlotPoints::new(values))
+                                            .name(format!("Microphone {}", result.mic_id)),
+                                    );
+                                }
+                            }
+                        });
+                });
+            }
+            Tab::TransferFunction => {
+                let settings = &mut *self.transfer_function;
+
+                // the reference signal and the microphone record as [time, value] pairs
+                let input = match settings.reference {
+                    Some(TransferReference::Source(id)) => self.source_records.get(id),
+                    Some(TransferReference::Microphone(id)) => self
+                        .mics
+                        .iter()
+                        .find(|mic| mic.id == id)
+                        .map(|mic| mic.record.as_slice()),
+                    None => None,
+                };
+                let output = settings
+                    .mic_id
+                    .and_then(|id| self.mics.iter().find(|mic| mic.id == id))
+                    .map(|mic| mic.record.as_slice());
+
+                let result = match (input, output) {
+                    (Some(input), Some(output)) => {
+                        let (input, output) =
+                            align_records(input, output, self.sample_interval as f64);
+                        if input.is_empty() {
+                            None
+                        } else {
+                            Some(transfer_function(
+                                &input,
+                                &output,
+                                self.sample_interval as f64,
+                                settings.segment_size,
+                                settings.averages,
+                            ))
+                        }
+                    }
+                    _ => None,
+                };
+
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Reference")
+                        .selected_text(match settings.reference {
+                            Some(reference) => reference.to_string(),
+                            None => "None".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            for source in self.sources.iter() {
+                                let reference = TransferReference::Source(source.id);
+                                ui.selectable_value(
+                                    &mut settings.reference,
+                                    Some(reference),
+                                    reference.to_string(),
+                                );
+                            }
+                            for mic in self.mics.iter() {
+                                let reference = TransferReference::Microphone(mic.id);
+                                ui.selectable_value(
+                                    &mut settings.reference,
+                                    Some(reference),
+                                    reference.to_string(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("The signal of a source is recorded once it is selected");
+
+                    egui::ComboBox::from_label("Microphone")
+                        .selected_text(match settings.mic_id {
+                            Some(id) => format!("Microphone {}", id),
+                            None => "None".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            for mic in self.mics.iter() {
+                                ui.selectable_value(
+                                    &mut settings.mic_id,
+                                    Some(mic.id),
+                                    format!("Microphone {}", mic.id),
+                                );
+                            }
+                        });
+
+                    ui.add(egui::Separator::default().vertical());
+
+                    egui::ComboBox::from_label("Segment Size")
+                        .selected_text(settings.segment_size.to_string())
+                        .show_ui(ui, |ui| {
+                            for size in [256, 512, 1024, 2048, 4096, 8192] {
+                                ui.selectable_value(
+                                    &mut settings.segment_size,
+                                    size,
+                                    size.to_string(),
+                                );
+                            }
+                        });
+
+                    ui.add(egui::DragValue::new(&mut settings.averages).clamp_range(1..=256))
+                        .on_hover_text(
+                            "The maximum amount of segments averaged with Welch's method",
+                        );
+                    ui.label("Averages");
+
+                    ui.add(egui::Separator::default().vertical());
+
+                    if ui
+                        .add_enabled(result.is_some(), egui::Button::new("Export to CSV"))
+                        .on_hover_text("Save the transfer function and coherence to a CSV file")
+                        .clicked()
+                    {
+                        if let Some(result) = &result {
+                            let mut wtr = csv::Writer::from_writer(vec![]);
+                            wtr.write_record([
+                                "frequency (Hz)",
+                                "magnitude (dB)",
+                                "phase (°)",
+                                "group delay (s)",
+                                "coherence",
+                            ])
+                            .unwrap();
+                            let rows = result
+                                .frequencies
+                                .iter()
+                                .zip(&result.magnitude)
+                                .zip(&result.phase)
+                                .zip(&result.group_delay)
+                                .zip(&result.coherence);
+                            for ((((frequency, magnitude), phase), group_delay), coherence) in rows
+                            {
+                                wtr.write_record(&[
+                                    frequency.to_string(),
+                                    magnitude.to_string(),
+                                    phase.to_string(),
+                                    group_delay.to_string(),
+                                    coherence.to_string(),
+                                ])
+                                .unwrap();
+                            }
+
+                            self.commands
+                                .dialog()
+                                .add_filter("CSV", &["csv"])
+                                .set_file_name("transfer_function.csv")
+                                .set_directory("./")
+                                .set_title("Select a file to save to")
+                                .save_file::<SaveFileContents>(wtr.into_inner().unwrap());
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                let Some(result) = result else {
+                    ui.add_space(20.);
+                    ui.vertical_centered(|ui| {
+                        ui.label(if input.is_some() && output.is_some() {
+                            "The reference and the microphone have to be recorded at the same times. Reset the simulation to record both."
+                        } else {
+                            "Select a reference and a microphone to calculate the transfer function."
+                        })
+                    });
+                    return;
+                };
+
+                if result.frequencies.is_empty() {
+                    ui.add_space(20.);
+                    ui.vertical_centered(|ui| {
+                        ui.label("Not enough samples recorded for one segment.")
+                    });
+                    return;
+                }
+
+                // plot frequencies on a logarithmic axis
+                let series = |values: &[f64]| {
+                    result
+                        .frequencies
+                        .iter()
+                        .zip(values)
+                        .filter(|(_, value)| value.is_finite())
+                        .map(|(frequency, value)| [frequency.log10(), *value])
+                        .collect::<Vec<_>>()
+                };
+                let plot_height = ui.available_height() / 2. - 10.;
+
+                ui.columns(2, |columns| {
+                    for (column, plots) in columns.iter_mut().zip([
+                        [
+                            ("Magnitude (dB)", &result.magnitude),
+                            ("Phase (°)", &result.phase),
+                        ],
+                        [
+                            ("Group Delay (ms)", &result.group_delay),
+                            ("Coherence", &result.coherence),
+                        ],
+                    ]) {
+                        for (label, values) in plots {
+                            let mut points = series(values);
+                            if label == "Group Delay (ms)" {
+                                points.iter_mut().for_each(|point| point[1] *= 1000.);
+                            }
+                            Plot::new(label)
+                                .height(plot_height)
+                                .x_axis_label("Frequency (Hz)")
+                                .y_axis_label(label)
+                                .x_axis_formatter(|mark, _, _| {
+                                    format!("{:.0}", 10_f64.powf(mark.value))
+                                })
+                                .label_formatter(move |_, value| {
+                                    format!(
+                                        "{}: {:.2}\nFrequency: {:.2} (Hz)",
+                                        label,
+                                        value.y,
+                                        10_f64.powf(value.x)
+                                    )
+                                })
+                                .show(column, |plot_ui| {
+                                    plot_ui.line(Line::new(PlotPoints::new(points)));
+                                });
+                        }
+                    }
+                });
+            }
         };
     }
 }
 
 pub fn create_tree() -> egui_dock::DockState<Tab> {
-    egui_dock::DockState::new(vec![Tab::Volume, Tab::Frequency, Tab::Spectrogram])
+    egui_dock::DockState::new(vec![
+        Tab::Volume,
+        Tab::Frequency,
+        Tab::Spectrogram,
+        Tab::Doa,
+        Tab::RoomAcoustics,
+        Tab::TransferFunction,
+    ])
 }
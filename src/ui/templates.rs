@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::Vec2;
+
+use super::loading::SceneEntities;
+use super::state::{TemplateGallery, UiState};
+use crate::events::{Reset, UpdateWalls};
+use crate::simulation::plugin::ComponentIDs;
+use crate::simulation::templates::{ParameterKind, Template};
+
+/// Draws the gallery to create a new scene from a template.
+pub fn draw_template_gallery(
+    mut commands: Commands,
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut gallery: ResMut<TemplateGallery>,
+    mut ids: ResMut<ComponentIDs>,
+    mut wall_update_ev: EventWriter<UpdateWalls>,
+    mut reset_ev: EventWriter<Reset>,
+    scene: SceneEntities,
+) {
+    if !ui_state.show_template_gallery {
+        return;
+    }
+
+    let mut show_template_gallery = ui_state.show_template_gallery;
+    let mut create = false;
+
+    egui::Window::new("New from Template")
+        .open(&mut show_template_gallery)
+        .default_size(Vec2::new(550., 350.))
+        .resizable(false)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal_top(|ui| {
+                ui.vertical(|ui| {
+                    ui.set_width(160.);
+                    for template in Template::ALL {
+                        if ui
+                            .selectable_label(gallery.template == template, template.to_string())
+                            .clicked()
+                            && gallery.template != template
+                        {
+                            gallery.template = template;
+                            gallery.parameters = template.parameters();
+                        }
+                    }
+                });
+
+                ui.separator();
+
+                ui.vertical(|ui| {
+                    ui.set_width(350.);
+                    ui.heading(gallery.template.to_string());
+                    ui.label(gallery.template.description());
+
+                    ui.separator();
+
+                    egui::Grid::new("template_parameters")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            for parameter in gallery.parameters.iter_mut() {
+                                ui.label(parameter.name);
+                                let drag_value = egui::DragValue::new(&mut parameter.value)
+                                    .clamp_range(parameter.range.clone());
+                                ui.add(match parameter.kind {
+                                    ParameterKind::Length => {
+                                        drag_value.fixed_decimals(0).suffix(" px")
+                                    }
+                                    ParameterKind::Frequency => {
+                                        drag_value.speed(10).fixed_decimals(0).suffix(" Hz")
+                                    }
+                                    ParameterKind::Count => drag_value.speed(0.1).fixed_decimals(0),
+                                    ParameterKind::Factor => {
+                                        drag_value.speed(0.01).fixed_decimals(2)
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Create")
+                            .on_hover_text("Replace the current scene with the template")
+                            .clicked()
+                        {
+                            create = true;
+                        }
+                        if ui.button("Reset Parameters").clicked() {
+                            gallery.parameters = gallery.template.parameters();
+                        }
+                    });
+                });
+            });
+        });
+
+    if create {
+        scene.despawn_all(&mut commands);
+        ids.reset();
+
+        for parameter in gallery.parameters.iter_mut() {
+            if parameter.kind != ParameterKind::Factor {
+                parameter.value = parameter.value.round();
+            }
+        }
+        gallery
+            .template
+            .build(&gallery.parameters, &mut ids)
+            .spawn(&mut commands);
+
+        wall_update_ev.send(UpdateWalls);
+        reset_ev.send(Reset { force: true });
+        show_template_gallery = false;
+    }
+
+    ui_state.show_template_gallery = show_template_gallery;
+}
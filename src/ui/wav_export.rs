@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use bevy_file_dialog::{DialogDirectoryPickCanceled, DialogDirectoryPicked, FileDialogExt};
+use bevy_pixel_buffer::bevy_egui::EguiContexts;
+use egui::Vec2;
+
+use super::loading::SaveFileContents;
+use super::state::{ChannelLayout, UiState, WavExportSettings};
+use crate::components::microphone::Microphone;
+use crate::export::wav::{align, encode_wav, normalize, resample, sample_interval, SampleFormat};
+use crate::simulation::grid::Grid;
+
+/// Marker for the directory dialog of the mono WAV export.
+pub struct WavExportDirectory;
+
+/// Draws the dialog to export microphone recordings as WAV files.
+pub fn draw_wav_export(
+    mut commands: Commands,
+    mut egui_context: EguiContexts,
+    mut ui_state: ResMut<UiState>,
+    mut settings: ResMut<WavExportSettings>,
+    mics: Query<&Microphone>,
+    grid: Res<Grid>,
+) {
+    if !ui_state.show_wav_export {
+        return;
+    }
+
+    let mut mics = mics.iter().collect::<Vec<_>>();
+    mics.sort_by_cached_key(|mic| mic.id);
+
+    // the records keep the spacing they were recorded with, even if the policy changed since
+    let records = mics
+        .iter()
+        .filter(|mic| settings.mic_ids.contains(&mic.id))
+        .map(|mic| mic.record.as_slice())
+        .collect::<Vec<_>>();
+    let interval = sample_interval(&records).unwrap_or(grid.delta_t as f64);
+    let sample_rate = 1. / interval;
+    let mut show_wav_export = ui_state.show_wav_export;
+
+    egui::Window::new("Export Microphones")
+        .open(&mut show_wav_export)
+        .default_size(Vec2::new(400., 400.))
+        .resizable(false)
+        .collapsible(false)
+        .constrain(true)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.heading("Microphones");
+            for mic in &mics {
+                let mut selected = settings.mic_ids.contains(&mic.id);
+                if ui
+                    .checkbox(
+                        &mut selected,
+                        format!("Microphone {} ({} samples)", mic.id, mic.record.len()),
+                    )
+                    .changed()
+                {
+                    if selected {
+                        settings.mic_ids.push(mic.id);
+                    } else {
+                        settings.mic_ids.retain(|id| *id != mic.id);
+                    }
+                }
+            }
+
+            ui.separator();
+            ui.heading("Format");
+
+            egui::ComboBox::from_label("Sample Format")
+                .selected_text(settings.sample_format.to_string())
+                .show_ui(ui, |ui| {
+                    for format in [
+                        SampleFormat::Int16,
+                        SampleFormat::Int24,
+                        SampleFormat::Float32,
+                    ] {
+                        ui.selectable_value(
+                            &mut settings.sample_format,
+                            format,
+                            format.to_string(),
+                        );
+                    }
+                });
+
+            egui::ComboBox::from_label("Channels")
+                .selected_text(settings.layout.to_string())
+                .show_ui(ui, |ui| {
+                    for layout in [ChannelLayout::Mono, ChannelLayout::Multichannel] {
+                        ui.selectable_value(&mut settings.layout, layout, layout.to_string());
+                    }
+                });
+
+            egui::ComboBox::from_label("Sample Rate")
+                .selected_text(match settings.resample_rate {
+                    Some(rate) => format!("{} Hz", rate),
+                    None => format!("Simulation ({:.0} Hz)", sample_rate),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(
+                        &mut settings.resample_rate,
+                        None,
+                        format!("Simulation ({:.0} Hz)", sample_rate),
+                    );
+                    for rate in [44_100, 48_000] {
+                        ui.selectable_value(
+                            &mut settings.resample_rate,
+                            Some(rate),
+                            format!("{} Hz", rate),
+                        );
+                    }
+                });
+
+            ui.checkbox(&mut settings.normalize, "Normalize")
+                .on_hover_text(
+                    "Scale all exported channels by the same factor to a peak of 0 dBFS",
+                );
+
+            ui.horizontal(|ui| {
+                ui.label("File Name");
+                ui.text_edit_singleline(&mut settings.file_stem);
+            });
+            if settings.layout == ChannelLayout::Mono {
+                ui.label(format!(
+                    "Asks for a directory and writes {}_mic<id>.wav for every microphone",
+                    settings.file_stem
+                ));
+            }
+
+            ui.add_space(5.);
+
+            if ui
+                .add_enabled(!settings.mic_ids.is_empty(), egui::Button::new("Export"))
+                .clicked()
+            {
+                let selected = mics
+                    .iter()
+                    .filter(|mic| settings.mic_ids.contains(&mic.id))
+                    .collect::<Vec<_>>();
+
+                // records can start and stop at different times (arming, ring buffers),
+                // so the channels are aligned by the times of their samples
+                let mut channels = align(&records, interval)
+                    .into_iter()
+                    .map(|samples| match settings.resample_rate {
+                        Some(rate) => resample(&samples, sample_rate, rate as f64),
+                        None => samples,
+                    })
+                    .collect::<Vec<_>>();
+
+                if settings.normalize {
+                    normalize(&mut channels, 1.);
+                }
+
+                let rate = settings.resample_rate.unwrap_or(sample_rate.round() as u32);
+
+                settings.error = None;
+                match settings.layout {
+                    ChannelLayout::Mono => {
+                        let format = settings.sample_format;
+                        settings.pending = selected
+                            .iter()
+                            .zip(channels)
+                            .map(|(mic, channel)| (mic.id, encode_wav(&[channel], rate, format)))
+                            .collect();
+                        commands
+                            .dialog()
+                            .set_directory("./")
+                            .set_title("Select a directory to save to")
+                            .pick_directory_path::<WavExportDirectory>();
+                    }
+                    ChannelLayout::Multichannel => {
+                        commands
+                            .dialog()
+                            .add_filter("WAV", &["wav"])
+                            .set_file_name(format!("{}.wav", settings.file_stem))
+                            .set_directory("./")
+                            .set_title("Select a file to save to")
+                            .save_file::<SaveFileContents>(encode_wav(
+                                &channels,
+                                rate,
+                                settings.sample_format,
+                            ));
+                    }
+                }
+            }
+
+            if let Some(error) = &settings.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+        });
+
+    ui_state.show_wav_export = show_wav_export;
+}
+
+/// Writes the pending mono files into the picked directory.
+pub fn wav_directory_picked(
+    mut picked: EventReader<DialogDirectoryPicked<WavExportDirectory>>,
+    mut canceled: EventReader<DialogDirectoryPickCanceled<WavExportDirectory>>,
+    mut settings: ResMut<WavExportSettings>,
+) {
+    if canceled.read().count() > 0 {
+        settings.pending.clear();
+    }
+    for event in picked.read() {
+        let pending = std::mem::take(&mut settings.pending);
+        for (id, contents) in pending {
+            let path = event
+                .path
+                .join(format!("{}_mic{}.wav", settings.file_stem, id));
+            if let Err(err) = std::fs::write(&path, contents) {
+                settings.error = Some(format!("Could not write {}: {}", path.display(), err));
+            }
+        }
+    }
+}
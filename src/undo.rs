@@ -48,7 +48,7 @@ fn update_state(
     let sources = sources.iter().copied().collect::<Vec<_>>();
     let mics = mics
         .iter()
-        .map(|mic| Microphone::new(mic.x, mic.y, mic.id))
+        .map(|mic| mic.without_record())
         .collect::<Vec<_>>();
     let rect_walls = rect_walls.iter().copied().collect::<Vec<_>>();
     let circle_walls = circle_walls.iter().copied().collect::<Vec<_>>();
@@ -112,7 +112,7 @@ pub fn undo_event(
         let sources = q_sources.iter().map(|x| *x.1).collect::<Vec<_>>();
         let mics = q_mics
             .iter()
-            .map(|(_, mic)| Microphone::new(mic.x, mic.y, mic.id))
+            .map(|(_, mic)| mic.without_record())
             .collect::<Vec<_>>();
         let rect_walls = q_rect_walls.iter().map(|x| *x.1).collect::<Vec<_>>();
         let circle_walls = q_circle_walls.iter().map(|x| *x.1).collect::<Vec<_>>();
@@ -0,0 +1,345 @@
+//! Compares small headless simulations against analytic solutions of the wave equation.
+//!
+//! The scenes are kept short, so waves reflected by the absorbing boundary never reach
+//! the microphones. The tolerances leave room for the dispersion of the grid, while
+//! a regression in the solver, the sources or the walls exceeds them by far.
+
+use std::f64::consts::{FRAC_PI_4, PI, SQRT_2};
+
+use bevy::math::UVec2;
+use wavefront::components::source::SourceType;
+use wavefront::math::constants::PROPAGATION_SPEED;
+use wavefront::math::dispersion::dispersion_error;
+use wavefront::simulation::headless::HeadlessSimulation;
+
+/// Position of the source in the free field scenes, in the middle of the simulation area
+const SOURCE: (u32, u32) = (350, 350);
+
+/// Period of the sinusoidal sources in time steps
+const SIN_PERIOD: f64 = 40.;
+
+/// Distance between two pulses of the gaussian sources in time steps, only the first one is used
+const PULSE_PERIOD: f64 = 400.;
+
+/// Standard deviation of the gaussian pulses relative to a quarter of [`PULSE_PERIOD`]
+const PULSE_STD_DEV: f32 = 0.06;
+
+/// A sin with a period of [`SIN_PERIOD`] steps and amplitude 1.
+fn sin_source(delta_t: f64) -> SourceType {
+    SourceType::Sin {
+        phase: 0.,
+        frequency: (1. / (SIN_PERIOD * delta_t)) as f32,
+        amplitude: 1.,
+    }
+}
+
+/// A smooth pulse that peaks after half of [`PULSE_PERIOD`] steps.
+fn pulse_source(delta_t: f64) -> SourceType {
+    SourceType::Gauss {
+        phase: 0.,
+        // the gaussian repeats every 4 / (2π f) seconds
+        frequency: (4. / (2. * PI * PULSE_PERIOD * delta_t)) as f32,
+        amplitude: 1.,
+        std_dev: PULSE_STD_DEV,
+    }
+}
+
+/// Simulates the scene and returns the pressure of every microphone per time step.
+fn record(simulation: &mut HeadlessSimulation, mics: &[(u32, u32)], steps: usize) -> Vec<Vec<f64>> {
+    let ids = mics
+        .iter()
+        .map(|&(x, y)| simulation.add_microphone(x, y))
+        .collect::<Vec<_>>();
+    simulation.step(steps);
+    ids.into_iter()
+        .map(|id| {
+            simulation
+                .mic_record(id)
+                .expect("the microphone exists")
+                .iter()
+                .map(|[_, pressure]| *pressure)
+                .collect()
+        })
+        .collect()
+}
+
+/// Bessel function of the first kind of order 0, rational approximation from Numerical Recipes.
+fn bessel_j0(x: f64) -> f64 {
+    let ax = x.abs();
+    if ax < 8. {
+        let y = x * x;
+        let numerator = 57568490574.0
+            + y * (-13362590354.0
+                + y * (651619640.7 + y * (-11214424.18 + y * (77392.33017 + y * -184.9052456))));
+        let denominator = 57568490411.0
+            + y * (1029532985.0 + y * (9494680.718 + y * (59272.64853 + y * (267.8532712 + y))));
+        numerator / denominator
+    } else {
+        let (p, q) = bessel_asymptotic(ax);
+        let xx = ax - FRAC_PI_4;
+        (2. / (PI * ax)).sqrt() * (xx.cos() * p - 8. / ax * xx.sin() * q)
+    }
+}
+
+/// Bessel function of the second kind of order 0, rational approximation from Numerical Recipes.
+fn bessel_y0(x: f64) -> f64 {
+    if x < 8. {
+        let y = x * x;
+        let numerator = -2957821389.0
+            + y * (7062834065.0
+                + y * (-512359803.6 + y * (10879881.29 + y * (-86327.92757 + y * 228.4622733))));
+        let denominator = 40076544269.0
+            + y * (745249964.8 + y * (7189466.438 + y * (47447.26470 + y * (226.1030244 + y))));
+        numerator / denominator + 2. / PI * bessel_j0(x) * x.ln()
+    } else {
+        let (p, q) = bessel_asymptotic(x);
+        let xx = x - FRAC_PI_4;
+        (2. / (PI * x)).sqrt() * (xx.sin() * p + 8. / x * xx.cos() * q)
+    }
+}
+
+/// The polynomials of the asymptotic expansions of `J0` and `Y0` for `x >= 8`.
+fn bessel_asymptotic(x: f64) -> (f64, f64) {
+    let y = (8. / x).powi(2);
+    let p = 1.
+        + y * (-0.1098628627e-2
+            + y * (0.2734510407e-4 + y * (-0.2073370639e-5 + y * 0.2093887211e-6)));
+    let q = -0.1562499995e-1
+        + y * (0.1430488765e-3
+            + y * (-0.6911147651e-5 + y * (0.7621095161e-6 - y * 0.934935152e-7)));
+    (p, q)
+}
+
+/// Magnitude and phase of the outgoing Hankel function `H0⁽²⁾(x) = J0(x) - i Y0(x)`,
+/// the pressure of a point source in two dimensions for a time dependency of `e^(iωt)`.
+fn hankel(x: f64) -> (f64, f64) {
+    let (re, im) = (bessel_j0(x), -bessel_y0(x));
+    (re.hypot(im), im.atan2(re))
+}
+
+/// Magnitude and phase of a single DFT bin over `samples`, the angular frequency is per sample.
+fn dft(signal: &[f64], omega: f64, samples: std::ops::Range<usize>) -> (f64, f64) {
+    let (re, im) = samples.fold((0., 0.), |(re, im), n| {
+        let phase = omega * n as f64;
+        (re + signal[n] * phase.cos(), im - signal[n] * phase.sin())
+    });
+    (re.hypot(im), im.atan2(re))
+}
+
+/// The time step of the highest value, interpolated with a parabola.
+fn peak_step(signal: &[f64], samples: std::ops::Range<usize>) -> f64 {
+    let start = samples.start;
+    let index = samples
+        .max_by(|&a, &b| signal[a].total_cmp(&signal[b]))
+        .expect("the range is not empty");
+    assert!(
+        index > start && index + 1 < signal.len(),
+        "the peak is at the edge of the range"
+    );
+    let (a, b, c) = (signal[index - 1], signal[index], signal[index + 1]);
+    index as f64 + 0.5 * (a - c) / (a - 2. * b + c)
+}
+
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2. * PI) - PI
+}
+
+#[test]
+fn delta_t_follows_from_delta_l() {
+    for delta_l in [0.001, 0.00715, 0.05] {
+        let mut simulation = HeadlessSimulation::new();
+        simulation.ui_state_mut().delta_l = delta_l;
+        simulation.step(1);
+
+        let expected = delta_l / PROPAGATION_SPEED;
+        assert!((simulation.delta_t() - expected).abs() <= expected * 1e-6);
+        assert!((simulation.grid().delta_t - expected).abs() <= expected * 1e-6);
+        assert!((simulation.time() - expected).abs() <= expected * 1e-6);
+    }
+}
+
+/// Waves in a two dimensional TLM mesh travel with `delta_l / (delta_t √2)`.
+#[test]
+fn speed_of_sound() {
+    let delta_l = 0.01;
+    let mut simulation = HeadlessSimulation::new();
+    simulation.ui_state_mut().delta_l = delta_l;
+    let delta_t = (delta_l / PROPAGATION_SPEED) as f64;
+    simulation.add_source(SOURCE.0, SOURCE.1, pulse_source(delta_t));
+
+    let (x, y) = SOURCE;
+    // pairs of microphones 50 and 250 cells away, along an axis and along the diagonal
+    let pairs = [
+        ((x + 50, y), (x + 250, y), 200.),
+        ((x, y - 50), (x, y - 250), 200.),
+        ((x + 35, y + 35), (x + 177, y + 177), 142. * SQRT_2),
+    ];
+    let mics = pairs
+        .iter()
+        .flat_map(|&(near, far, _)| [near, far])
+        .collect::<Vec<_>>();
+    let steps = 600;
+    let records = record(&mut simulation, &mics, steps);
+
+    let expected = simulation.grid().wave_speed(delta_l) as f64;
+    assert!((expected - PROPAGATION_SPEED as f64 / SQRT_2).abs() < 1e-3 * expected);
+
+    for (i, (_, _, distance)) in pairs.iter().enumerate() {
+        let near = peak_step(&records[2 * i], 1..steps - 1);
+        let far = peak_step(&records[2 * i + 1], 1..steps - 1);
+        let speed = distance * delta_l as f64 / ((far - near) * delta_t);
+        assert!(
+            (speed / expected - 1.).abs() < 0.01,
+            "pair {i}: measured {speed} m/s, expected {expected} m/s"
+        );
+    }
+}
+
+/// The steady state field of a sinusoidal point source is proportional to `H0⁽²⁾(kr)`.
+#[test]
+fn free_field_propagation_follows_hankel_function() {
+    let mut simulation = HeadlessSimulation::new();
+    let delta_l = simulation.ui_state().delta_l;
+    let delta_t = simulation.delta_t() as f64;
+    let source_type = sin_source(delta_t);
+    let SourceType::Sin { frequency, .. } = source_type else {
+        unreachable!()
+    };
+    simulation.add_source(SOURCE.0, SOURCE.1, source_type);
+
+    let distances = [20, 40, 80, 160];
+    let mics = distances
+        .iter()
+        .map(|r| (SOURCE.0 + r, SOURCE.1))
+        .collect::<Vec<_>>();
+    let records = record(&mut simulation, &mics, 600);
+
+    // the wave number on the grid includes its dispersion along the axes
+    let wave_speed = simulation.grid().wave_speed(delta_l) as f64;
+    let wavenumber = 2. * PI * frequency as f64
+        / wave_speed
+        / (1. - dispersion_error(frequency, delta_l).expect("below the cutoff") as f64);
+
+    // five periods after the wave front passed the farthest microphone
+    let omega = 2. * PI / SIN_PERIOD;
+    let window = 400..600;
+    let measured = records
+        .iter()
+        .map(|record| dft(record, omega, window.clone()))
+        .collect::<Vec<_>>();
+    let expected = distances
+        .iter()
+        .map(|r| hankel(wavenumber * *r as f64 * delta_l as f64))
+        .collect::<Vec<_>>();
+
+    for i in 1..distances.len() {
+        let amplitude_ratio = measured[i].0 / measured[0].0;
+        let expected_ratio = expected[i].0 / expected[0].0;
+        assert!(
+            (amplitude_ratio / expected_ratio - 1.).abs() < 0.02,
+            "{} cells: amplitude ratio {amplitude_ratio}, expected {expected_ratio}",
+            distances[i]
+        );
+
+        let phase = wrap_angle(measured[i].1 - measured[0].1);
+        let expected_phase = wrap_angle(expected[i].1 - expected[0].1);
+        assert!(
+            wrap_angle(phase - expected_phase).abs() < 0.05,
+            "{} cells: phase {phase}, expected {expected_phase}",
+            distances[i]
+        );
+    }
+}
+
+/// A rigid wall reflects like an image source mirrored at the wall.
+#[test]
+fn reflection_from_rigid_wall() {
+    let mut free = HeadlessSimulation::new();
+    let delta_t = free.delta_t() as f64;
+    free.add_source(SOURCE.0, SOURCE.1, pulse_source(delta_t));
+
+    let wall = SOURCE.0 + 60;
+    let mics = [(SOURCE.0 + 30, SOURCE.1), (SOURCE.0 + 30, SOURCE.1 + 40)];
+    let mirrored = mics.map(|(x, y)| (2 * wall - x, y));
+
+    let mut with_wall = HeadlessSimulation::new();
+    with_wall.add_source(SOURCE.0, SOURCE.1, pulse_source(delta_t));
+    with_wall.add_rect_wall(UVec2::new(wall, 100), UVec2::new(wall + 5, 600), false, 1.);
+
+    let steps = 400;
+    let with_wall = record(&mut with_wall, &mics, steps);
+    let free = record(
+        &mut free,
+        &[mics[0], mics[1], mirrored[0], mirrored[1]],
+        steps,
+    );
+
+    // after the direct pulse and before the reflection arrives a second time after passing the source
+    let window = 280..380;
+    for i in 0..mics.len() {
+        let reflection = with_wall[i]
+            .iter()
+            .zip(&free[i])
+            .map(|(with_wall, free)| with_wall - free)
+            .collect::<Vec<_>>();
+        let image = &free[mics.len() + i];
+
+        let energy = |signal: &[f64]| window.clone().map(|n| signal[n] * signal[n]).sum::<f64>();
+        let reflection_factor = (energy(&reflection) / energy(image)).sqrt();
+        assert!(
+            (reflection_factor - 1.).abs() < 0.03,
+            "microphone {i}: reflection factor {reflection_factor}"
+        );
+
+        let delay = peak_step(&reflection, window.clone()) - peak_step(image, window.clone());
+        assert!(
+            delay.abs() < 2.,
+            "microphone {i}: reflection is {delay} steps late"
+        );
+    }
+}
+
+/// The axial modes of a duct with rigid ends are at `n c / 2L`.
+#[test]
+fn rigid_duct_modes() {
+    let mut simulation = HeadlessSimulation::new();
+    let delta_l = simulation.ui_state().delta_l as f64;
+    let delta_t = simulation.delta_t() as f64;
+
+    // the duct is wide enough that the cells next to its sides barely slow down plane waves
+    let (start, length, width) = (100, 100, 40);
+    simulation.add_rect_wall(
+        UVec2::new(start, 340),
+        UVec2::new(start + length, 340 + width),
+        true,
+        1.,
+    );
+    let center = 340 + width / 2;
+    simulation.add_source(start + 2, center, SourceType::Impulse { amplitude: 1. });
+    let steps = 1000;
+    let records = record(&mut simulation, &[(start + length - 2, center)], steps);
+
+    // a Hann window keeps the neighboring modes from shifting the peaks
+    let windowed = records[0]
+        .iter()
+        .enumerate()
+        .map(|(n, p)| p * (0.5 - 0.5 * (2. * PI * n as f64 / steps as f64).cos()))
+        .collect::<Vec<_>>();
+
+    let wave_speed = simulation.grid().wave_speed(delta_l as f32) as f64;
+    for mode in 1..=3 {
+        let expected = mode as f64 * wave_speed / (2. * length as f64 * delta_l);
+        let found = (0..=2000)
+            .map(|i| expected * (0.9 + 0.2 * i as f64 / 2000.))
+            .max_by(|&a, &b| {
+                let magnitude =
+                    |frequency: f64| dft(&windowed, 2. * PI * frequency * delta_t, 0..steps).0;
+                magnitude(a).total_cmp(&magnitude(b))
+            })
+            .unwrap();
+        assert!(
+            (found / expected - 1.).abs() < 0.01,
+            "mode {mode}: found {found} Hz, expected {expected} Hz"
+        );
+    }
+}
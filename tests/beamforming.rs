@@ -0,0 +1,94 @@
+//! Steers microphone arrays at waves simulated on the mesh.
+
+use std::f64::consts::PI;
+
+use wavefront::components::source::SourceType;
+use wavefront::math::beamforming::{delay_and_sum, estimate_doa, srp_phat};
+use wavefront::simulation::headless::HeadlessSimulation;
+
+/// Center of the array, in the middle of the simulation area
+const CENTER: (f64, f64) = (350., 350.);
+
+/// Distance of the source from the center of the array in cells,
+/// far enough that the wave front is almost plane across the array
+const SOURCE_DISTANCE: f64 = 250.;
+
+/// Largest error of the estimated direction in degrees
+const TOLERANCE: f64 = 3.;
+
+/// Simulates a pulse arriving from `angle` (in °, counterclockwise from the right)
+/// at a circular array of eight microphones.
+fn simulate_array(angle: f64) -> HeadlessSimulation {
+    let mut simulation = HeadlessSimulation::new();
+    let delta_t = simulation.delta_t() as f64;
+
+    // the y-axis of the grid points down
+    let (sin, cos) = angle.to_radians().sin_cos();
+    simulation.add_source(
+        (CENTER.0 + SOURCE_DISTANCE * cos).round() as u32,
+        (CENTER.1 - SOURCE_DISTANCE * sin).round() as u32,
+        SourceType::Gauss {
+            phase: 0.,
+            // a single pulse after 200 steps
+            frequency: (4. / (2. * PI * 400. * delta_t)) as f32,
+            amplitude: 1.,
+            std_dev: 0.06,
+        },
+    );
+    for i in 0..8 {
+        let (sin, cos) = (i as f64 * PI / 4.).sin_cos();
+        simulation.add_microphone(
+            (CENTER.0 + 12. * cos).round() as u32,
+            (CENTER.1 - 12. * sin).round() as u32,
+        );
+    }
+    simulation.step(700);
+    simulation
+}
+
+fn angle_error(a: f64, b: f64) -> f64 {
+    ((a - b).rem_euclid(360.)).min((b - a).rem_euclid(360.))
+}
+
+#[test]
+fn srp_phat_finds_direction_of_arrival() {
+    for angle in [30., 135., 250.] {
+        let mut simulation = simulate_array(angle);
+        let delta_l = simulation.ui_state().delta_l;
+        let delta_t = simulation.delta_t();
+        let mics = simulation.microphones();
+        let mics = mics.iter().collect::<Vec<_>>();
+
+        let map = srp_phat(&mics, delta_l, delta_t, 512, 1.);
+        let estimate = estimate_doa(&map).expect("the map is not empty");
+        assert!(
+            angle_error(estimate, angle) <= TOLERANCE,
+            "estimated {estimate}°, expected {angle}°"
+        );
+    }
+}
+
+#[test]
+fn delay_and_sum_is_loudest_towards_source() {
+    let angle = 60.;
+    let mut simulation = simulate_array(angle);
+    let delta_l = simulation.ui_state().delta_l;
+    let delta_t = simulation.delta_t();
+    let mics = simulation.microphones();
+    let mics = mics.iter().collect::<Vec<_>>();
+
+    let energy = |steering_angle: f32| {
+        delay_and_sum(&mics, steering_angle, delta_l, delta_t)
+            .iter()
+            .map(|[_, value]| value * value)
+            .sum::<f64>()
+    };
+    let loudest = (0..360)
+        .map(|steering_angle| steering_angle as f32)
+        .max_by(|&a, &b| energy(a).total_cmp(&energy(b)))
+        .expect("the range is not empty") as f64;
+    assert!(
+        angle_error(loudest, angle) <= TOLERANCE,
+        "loudest towards {loudest}°, expected {angle}°"
+    );
+}
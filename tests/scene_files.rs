@@ -0,0 +1,188 @@
+//! Loads scene files of every version and rejects invalid ones.
+
+use serde_json::{json, Value};
+use wavefront::components::microphone::Microphone;
+use wavefront::components::source::{Source, SourceType};
+use wavefront::components::wall::{CircWall, RectWall};
+use wavefront::remote::osc::OscControl;
+use wavefront::render::gradient::Gradient;
+use wavefront::simulation::validation::MAX_RADIUS;
+use wavefront::ui::loading::{migrate, validate_file};
+use wavefront::ui::saving::{serialize, CHECKPOINT_MAGIC, SCENE_VERSION};
+use wavefront::ui::state::UiState;
+use wavefront::ui::tabs::DockState;
+
+/// A scene with a source, a microphone and two walls as saved by the current version.
+fn current_scene() -> Value {
+    let source = Source::new(100, 350, SourceType::default_sin(), 0);
+    let mic = Microphone::new(500, 350, 0);
+    let rect_wall = RectWall::new(300, 200, 320, 500, false, 1., 0);
+    let circ_wall = CircWall::new(600, 600, 50, true, 0.5, 1);
+
+    let data = serialize(
+        &vec![&source],
+        &vec![&mic],
+        &vec![&rect_wall],
+        &vec![&circ_wall],
+        &vec![],
+        &vec![],
+        &vec![],
+        &Gradient::default(),
+        &UiState::default(),
+        &DockState::default().tree,
+        &OscControl::default(),
+    )
+    .expect("the scene can be serialized");
+    serde_json::from_slice(&data).unwrap()
+}
+
+/// The scene as saved before the settings were added.
+fn version_2_scene() -> Value {
+    let mut scene = current_scene();
+    let object = scene.as_object_mut().unwrap();
+    object.remove("settings");
+    object.insert("version".to_string(), json!(2));
+    scene
+}
+
+/// The scene as saved before versioning, mic arrays and probes were added.
+fn version_1_scene() -> Value {
+    let mut scene = version_2_scene();
+    let object = scene.as_object_mut().unwrap();
+    for key in ["version", "mic_arrays", "line_probes", "area_probes"] {
+        object.remove(key);
+    }
+    scene
+}
+
+fn load(scene: &Value) -> Result<(), Vec<String>> {
+    validate_file(&serde_json::to_vec(scene).unwrap())
+}
+
+/// Loads the current scene after `change` was applied and returns the reported problems.
+fn errors_after(change: impl FnOnce(&mut Value)) -> Vec<String> {
+    let mut scene = current_scene();
+    change(&mut scene);
+    load(&scene).expect_err("the scene is invalid")
+}
+
+#[test]
+fn current_scene_loads() {
+    assert_eq!(current_scene()["version"], json!(SCENE_VERSION));
+    load(&current_scene()).unwrap();
+}
+
+#[test]
+fn version_1_is_migrated() {
+    let mut scene = version_1_scene();
+    migrate(&mut scene, 1);
+    for key in ["mic_arrays", "line_probes", "area_probes"] {
+        assert_eq!(scene[key], json!([]), "{key} is added");
+    }
+    assert_eq!(scene["sources"], current_scene()["sources"]);
+
+    load(&version_1_scene()).unwrap();
+}
+
+#[test]
+fn version_2_is_migrated() {
+    let mut scene = version_2_scene();
+    migrate(&mut scene, 2);
+    assert_eq!(
+        scene,
+        version_2_scene(),
+        "only the optional settings are missing"
+    );
+
+    load(&version_2_scene()).unwrap();
+}
+
+#[test]
+fn rejects_newer_versions() {
+    let errors = errors_after(|scene| scene["version"] = json!(SCENE_VERSION + 1));
+    assert!(errors[0].contains("newer version"), "{errors:?}");
+}
+
+#[test]
+fn rejects_malformed_files() {
+    assert!(validate_file(b"not a scene").is_err());
+    assert!(validate_file(b"[1, 2, 3]").is_err());
+    assert!(load(&json!({ "version": 3 })).is_err());
+
+    let mut truncated = CHECKPOINT_MAGIC.to_vec();
+    truncated.extend_from_slice(&100u64.to_le_bytes());
+    truncated.extend_from_slice(b"{}");
+    assert_eq!(
+        validate_file(&truncated),
+        Err(vec!["The checkpoint file is truncated.".to_string()])
+    );
+}
+
+#[test]
+fn rejects_positions_outside_of_the_simulation_area() {
+    let errors = errors_after(|scene| {
+        scene["sources"][0]["x"] = json!(700);
+        scene["mics"][0]["y"] = json!(1000);
+        scene["circ_walls"][0]["center"] = json!([900, 10]);
+    });
+    assert_eq!(errors.len(), 3, "{errors:?}");
+    assert!(errors[0].starts_with("Source 0"), "{errors:?}");
+    assert!(errors[1].starts_with("Microphone 0"), "{errors:?}");
+    assert!(errors[2].starts_with("Circular wall 1"), "{errors:?}");
+}
+
+#[test]
+fn rejects_invalid_circle_radii() {
+    for radius in [0, MAX_RADIUS + 1, u32::MAX] {
+        let errors = errors_after(|scene| scene["circ_walls"][0]["radius"] = json!(radius));
+        assert_eq!(
+            errors,
+            [format!(
+                "Circular wall 1 has a radius of {radius}, expected a value between 1 and {MAX_RADIUS}"
+            )]
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_walls() {
+    let errors = errors_after(|scene| {
+        let min = scene["rect_walls"][0]["rect"]["min"].clone();
+        let max = scene["rect_walls"][0]["rect"]["max"].clone();
+        scene["rect_walls"][0]["rect"]["min"] = max;
+        scene["rect_walls"][0]["rect"]["max"] = min;
+        scene["circ_walls"][0]["reflection_factor"] = json!(1.5);
+    });
+    assert_eq!(
+        errors,
+        [
+            "Rectangular wall 0 has a negative size",
+            "Circular wall 1 has a reflection factor of 1.5, expected a value between 0 and 1",
+        ]
+    );
+}
+
+#[test]
+fn rejects_invalid_settings() {
+    let errors = errors_after(|scene| {
+        scene["min_gradient"] = json!(1.);
+        scene["max_gradient"] = json!(-1.);
+        scene["settings"]["boundary_width"] = json!(1);
+        scene["settings"]["fft_window_size"] = json!(1000);
+        scene["settings"]["osc"]["port"] = json!(80);
+    });
+    assert_eq!(errors.len(), 4, "{errors:?}");
+    assert!(
+        errors[0].starts_with("Invalid gradient range"),
+        "{errors:?}"
+    );
+    assert!(
+        errors[1].starts_with("Invalid boundary width"),
+        "{errors:?}"
+    );
+    assert!(
+        errors[2].starts_with("Invalid FFT window size"),
+        "{errors:?}"
+    );
+    assert!(errors[3].starts_with("Invalid OSC port"), "{errors:?}");
+}